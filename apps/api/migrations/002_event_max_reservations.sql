@@ -0,0 +1,4 @@
+-- Migration 002: Maximum reservations per event
+-- Caps the number of parties (reservations) independently of the spot capacity
+
+ALTER TABLE events ADD COLUMN max_reservations INTEGER CHECK (max_reservations IS NULL OR max_reservations > 0);
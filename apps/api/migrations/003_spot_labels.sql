@@ -0,0 +1,6 @@
+-- Migration 003: Spot labels
+-- Optional per-spot labels (e.g. "Row A Seat 3") requested at reserve time
+-- and carried onto the reservation tokens issued at confirmation
+
+ALTER TABLE reservations ADD COLUMN spot_labels TEXT;         -- JSON array of labels, one per spot
+ALTER TABLE reservation_tokens ADD COLUMN label TEXT;         -- Optional field (Option<String> in Rust)
@@ -0,0 +1,4 @@
+-- Migration 004: Maximum spots per reservation
+-- Limits how many spots a single reservation may request for an event
+
+ALTER TABLE events ADD COLUMN max_spots_per_reservation INTEGER CHECK (max_spots_per_reservation IS NULL OR max_spots_per_reservation > 0);
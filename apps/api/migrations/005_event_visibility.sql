@@ -0,0 +1,4 @@
+-- Migration 005: Event visibility
+-- Unlisted events are reachable by id but hidden from listings; private events require auth
+
+ALTER TABLE events ADD COLUMN visibility TEXT NOT NULL DEFAULT 'public' CHECK (visibility IN ('public', 'unlisted', 'private'));
@@ -0,0 +1,21 @@
+-- Migration 006: Reservation holds
+-- Spots set aside for a short TTL while a user completes checkout
+
+CREATE TABLE IF NOT EXISTS reservation_holds (
+    id TEXT PRIMARY KEY,
+    event_id TEXT NOT NULL,
+    spot_count INTEGER NOT NULL,
+    hold_token TEXT NOT NULL,
+
+    -- Stored as INTEGER Unix epoch timestamps so expiry can be compared directly
+    created_at INTEGER NOT NULL,
+    expires_at INTEGER NOT NULL,
+
+    FOREIGN KEY (event_id) REFERENCES events (id) ON DELETE CASCADE,
+
+    UNIQUE(hold_token),
+    CHECK (spot_count > 0),
+    CHECK (expires_at > created_at)
+);
+
+CREATE INDEX IF NOT EXISTS idx_reservation_holds_event_expires ON reservation_holds(event_id, expires_at);
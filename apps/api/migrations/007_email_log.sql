@@ -0,0 +1,13 @@
+-- Migration 007: Email delivery log
+-- One row per send attempt so operators can see which emails succeeded or failed
+
+CREATE TABLE IF NOT EXISTS email_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    recipient TEXT NOT NULL,
+    kind TEXT NOT NULL CHECK (kind IN ('verification', 'confirmation')),
+    status TEXT NOT NULL CHECK (status IN ('sent', 'failed')),
+    error TEXT,
+    at INTEGER NOT NULL DEFAULT (unixepoch())
+);
+
+CREATE INDEX IF NOT EXISTS idx_email_log_recipient ON email_log(recipient);
@@ -0,0 +1,5 @@
+-- Migration 008: Reservation window
+-- Optional times outside of which reservations are not accepted
+
+ALTER TABLE events ADD COLUMN reservations_open_at INTEGER;
+ALTER TABLE events ADD COLUMN reservations_close_at INTEGER;
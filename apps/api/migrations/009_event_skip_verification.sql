@@ -0,0 +1,4 @@
+-- Migration 009: Skip email verification
+-- Trusted/internal events can confirm reservations immediately
+
+ALTER TABLE events ADD COLUMN skip_verification BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,5 @@
+-- Migration 010: Cancelled events
+-- Cancelled events keep the 'closed' status (so the existing CHECK constraint still holds)
+-- and are told apart from finished ones by a cancellation time
+
+ALTER TABLE events ADD COLUMN cancelled_at INTEGER;
@@ -0,0 +1,12 @@
+-- Migration 011: Reservation token audit trail
+-- Records manual corrections to token state, such as reverting a mis-scan
+
+CREATE TABLE IF NOT EXISTS reservation_token_audit (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    token TEXT NOT NULL,
+    action TEXT NOT NULL CHECK (action IN ('unscan')),
+    previous_used_at TEXT,
+    at INTEGER NOT NULL DEFAULT (unixepoch())
+);
+
+CREATE INDEX IF NOT EXISTS idx_reservation_token_audit_token ON reservation_token_audit(token);
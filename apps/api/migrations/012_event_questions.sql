@@ -0,0 +1,17 @@
+-- Migration 012: Custom event questions
+-- Organizers can ask extra questions (t-shirt size, company) that are answered at reserve time
+
+CREATE TABLE IF NOT EXISTS event_questions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    event_id TEXT NOT NULL,
+    key TEXT NOT NULL,                                -- Stable identifier answers are keyed by
+    prompt TEXT NOT NULL,
+    required INTEGER NOT NULL DEFAULT 0,              -- Boolean: 0 = optional, 1 = required
+    position INTEGER NOT NULL DEFAULT 0,              -- Display order
+    FOREIGN KEY (event_id) REFERENCES events(id) ON DELETE CASCADE,
+    UNIQUE (event_id, key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_event_questions_event_id ON event_questions(event_id);
+
+ALTER TABLE reservations ADD COLUMN answers TEXT;    -- JSON object of answers keyed by question key
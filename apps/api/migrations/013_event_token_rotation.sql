@@ -0,0 +1,4 @@
+-- Migration 013: Rotating ticket tokens
+-- High-security events show time-windowed ticket codes so screenshots stop scanning once the window passes
+
+ALTER TABLE events ADD COLUMN token_rotation_minutes INTEGER;   -- Optional field (Option<u32> in Rust); NULL means tokens never rotate
@@ -0,0 +1,11 @@
+-- Migration 014: Friendly event slugs
+-- Human-readable identifiers for event URLs, e.g. "summer-concert-k3x9qa"
+
+ALTER TABLE events ADD COLUMN slug TEXT NOT NULL DEFAULT '';
+
+-- Backfill existing events from their name plus the start of their id, which keeps them unique
+UPDATE events
+SET slug = trim(lower(replace(replace(name, ' ', '-'), '/', '-')), '-') || '-' || substr(id, 1, 6)
+WHERE slug = '';
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_events_slug ON events(slug);
@@ -0,0 +1,9 @@
+-- Migration 015: Reservation cancellation tokens
+-- Lets a guest cancel a confirmed reservation from the confirmation email without logging in
+
+ALTER TABLE reservations ADD COLUMN cancellation_token TEXT;   -- Optional field; set when the reservation is confirmed
+
+-- Reservations confirmed before this migration get a token so their guests can cancel too
+UPDATE reservations SET cancellation_token = lower(hex(randomblob(16))) WHERE status = 'confirmed' AND cancellation_token IS NULL;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_reservations_cancellation_token ON reservations(cancellation_token);
@@ -0,0 +1,4 @@
+-- Migration 016: Minimum spots per reservation
+-- Group-only events can require a minimum party size for each reservation
+
+ALTER TABLE events ADD COLUMN min_spots_per_reservation INTEGER CHECK (min_spots_per_reservation IS NULL OR min_spots_per_reservation > 0);
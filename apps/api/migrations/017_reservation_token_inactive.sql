@@ -0,0 +1,31 @@
+-- Migration 017: Inactive reservation tokens
+-- Large events can pre-issue tokens when a reservation is made; they stay inactive until the
+-- reservation is confirmed, so confirming only flips their status instead of inserting rows.
+-- SQLite can't alter a CHECK constraint, so the table is rebuilt with 'inactive' allowed.
+
+CREATE TABLE reservation_tokens_new (
+    id TEXT PRIMARY KEY,
+    reservation_id TEXT NOT NULL,
+    token TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'active' CHECK (status IN ('inactive', 'active', 'used', 'expired')),
+    created_at INTEGER NOT NULL DEFAULT (unixepoch()),
+    used_at INTEGER,
+    label TEXT,
+    FOREIGN KEY (reservation_id) REFERENCES reservations (id) ON DELETE CASCADE,
+    UNIQUE(token),
+    CHECK (created_at > 0),
+    CHECK (used_at IS NULL OR used_at >= created_at),
+    CHECK (LENGTH(token) > 0)
+);
+
+INSERT INTO reservation_tokens_new (id, reservation_id, token, status, created_at, used_at, label)
+SELECT id, reservation_id, token, status, created_at, used_at, label FROM reservation_tokens;
+
+DROP TABLE reservation_tokens;
+ALTER TABLE reservation_tokens_new RENAME TO reservation_tokens;
+
+CREATE INDEX IF NOT EXISTS idx_reservation_tokens_reservation_id ON reservation_tokens(reservation_id);
+CREATE INDEX IF NOT EXISTS idx_reservation_tokens_token ON reservation_tokens(token);
+CREATE INDEX IF NOT EXISTS idx_reservation_tokens_status ON reservation_tokens(status);
+CREATE INDEX IF NOT EXISTS idx_reservation_tokens_created_at ON reservation_tokens(created_at);
+CREATE INDEX IF NOT EXISTS idx_reservation_tokens_reservation_status ON reservation_tokens(reservation_id, status);
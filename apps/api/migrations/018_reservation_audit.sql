@@ -0,0 +1,14 @@
+-- Migration 018: Reservation audit trail
+-- Records admin changes to a confirmed reservation, such as adding or removing spots
+
+CREATE TABLE IF NOT EXISTS reservation_audit (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    reservation_id TEXT NOT NULL,
+    action TEXT NOT NULL CHECK (action IN ('adjust_spots')),
+    previous_spot_count INTEGER,
+    spot_count INTEGER,
+    at INTEGER NOT NULL DEFAULT (unixepoch()),
+    FOREIGN KEY (reservation_id) REFERENCES reservations (id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_reservation_audit_reservation_id ON reservation_audit(reservation_id);
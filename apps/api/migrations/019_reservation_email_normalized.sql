@@ -0,0 +1,18 @@
+-- Migration 019: Normalized reservation emails
+-- Emails are stored trimmed and lowercased so the (event_id, user_email) unique constraint catches
+-- addresses that differ only by case. The address as the guest typed it is kept in user_email_original.
+
+ALTER TABLE reservations ADD COLUMN user_email_original TEXT;
+
+UPDATE reservations SET user_email_original = user_email;
+
+-- Rows that would collide with another reservation for the same event keep their stored address;
+-- POST /admin/reservations/dedupe cancels the duplicate pending ones
+UPDATE reservations SET user_email = LOWER(TRIM(user_email))
+WHERE user_email != LOWER(TRIM(user_email))
+  AND NOT EXISTS (
+      SELECT 1 FROM reservations other
+      WHERE other.event_id = reservations.event_id
+        AND other.id != reservations.id
+        AND LOWER(TRIM(other.user_email)) = LOWER(TRIM(reservations.user_email))
+  );
@@ -0,0 +1,7 @@
+-- Migration 020: Event series
+-- Recurring events (e.g. a weekly class) share a series_id so they can be listed together.
+-- The series id is the id of the event the series started from.
+
+ALTER TABLE events ADD COLUMN series_id TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_events_series_id ON events(series_id);
@@ -0,0 +1,4 @@
+-- Migration 021: Reservation party name
+-- Optional name for the group a reservation is for (e.g. "Smith family"); falls back to user_name when NULL.
+
+ALTER TABLE reservations ADD COLUMN party_name TEXT;
@@ -0,0 +1,19 @@
+-- Migration 022: Cancellation emails in the delivery log
+-- SQLite can't alter a CHECK constraint, so the table is rebuilt with 'cancellation' added to the allowed kinds.
+
+CREATE TABLE email_log_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    recipient TEXT NOT NULL,
+    kind TEXT NOT NULL CHECK (kind IN ('verification', 'confirmation', 'cancellation')),
+    status TEXT NOT NULL CHECK (status IN ('sent', 'failed')),
+    error TEXT,
+    at INTEGER NOT NULL DEFAULT (unixepoch())
+);
+
+INSERT INTO email_log_new (id, recipient, kind, status, error, at)
+SELECT id, recipient, kind, status, error, at FROM email_log;
+
+DROP TABLE email_log;
+ALTER TABLE email_log_new RENAME TO email_log;
+
+CREATE INDEX IF NOT EXISTS idx_email_log_recipient ON email_log(recipient);
@@ -0,0 +1,4 @@
+-- Migration 023: Require name match at check-in
+-- Higher-assurance events make door staff enter the guest's name, which must match the reservation
+
+ALTER TABLE events ADD COLUMN require_name_match BOOLEAN NOT NULL DEFAULT FALSE;
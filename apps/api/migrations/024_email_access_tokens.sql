@@ -0,0 +1,35 @@
+-- Migration 024: Email access tokens
+-- Short-lived tokens emailed to an address so its owner can list their tickets across events
+-- (GET /my/tokens) without the endpoint revealing who has reserved to anyone who asks.
+
+CREATE TABLE IF NOT EXISTS email_access_tokens (
+    token TEXT PRIMARY KEY,
+    email TEXT NOT NULL,
+
+    -- Stored as INTEGER Unix epoch timestamps so expiry can be compared directly
+    created_at INTEGER NOT NULL,
+    expires_at INTEGER NOT NULL,
+
+    CHECK (LENGTH(token) > 0),
+    CHECK (expires_at > created_at)
+);
+
+CREATE INDEX IF NOT EXISTS idx_email_access_tokens_email ON email_access_tokens(email);
+
+-- SQLite can't alter a CHECK constraint, so the delivery log is rebuilt with 'access' added to the allowed kinds
+CREATE TABLE email_log_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    recipient TEXT NOT NULL,
+    kind TEXT NOT NULL CHECK (kind IN ('verification', 'confirmation', 'cancellation', 'access')),
+    status TEXT NOT NULL CHECK (status IN ('sent', 'failed')),
+    error TEXT,
+    at INTEGER NOT NULL DEFAULT (unixepoch())
+);
+
+INSERT INTO email_log_new (id, recipient, kind, status, error, at)
+SELECT id, recipient, kind, status, error, at FROM email_log;
+
+DROP TABLE email_log;
+ALTER TABLE email_log_new RENAME TO email_log;
+
+CREATE INDEX IF NOT EXISTS idx_email_log_recipient ON email_log(recipient);
@@ -0,0 +1,5 @@
+-- Migration 025: Reservation refund status
+-- Payment happens outside the app, so cancellations record whether a refund is owed for finance to
+-- reconcile: 'pending' when the cancellation policy grants one, 'issued' once it has been paid out.
+
+ALTER TABLE reservations ADD COLUMN refund_status TEXT NOT NULL DEFAULT 'none' CHECK (refund_status IN ('none', 'pending', 'issued'));
@@ -0,0 +1,24 @@
+-- Migration 026: Reservation cancellation time
+-- Records when a reservation was cancelled, and logs each cancellation in the reservation audit trail
+-- at the same instant. SQLite can't alter a CHECK constraint, so the audit table is rebuilt with
+-- 'cancel' added to the allowed actions.
+
+ALTER TABLE reservations ADD COLUMN cancelled_at INTEGER;
+
+CREATE TABLE reservation_audit_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    reservation_id TEXT NOT NULL,
+    action TEXT NOT NULL CHECK (action IN ('adjust_spots', 'cancel')),
+    previous_spot_count INTEGER,
+    spot_count INTEGER,
+    at INTEGER NOT NULL DEFAULT (unixepoch()),
+    FOREIGN KEY (reservation_id) REFERENCES reservations (id) ON DELETE CASCADE
+);
+
+INSERT INTO reservation_audit_new (id, reservation_id, action, previous_spot_count, spot_count, at)
+SELECT id, reservation_id, action, previous_spot_count, spot_count, at FROM reservation_audit;
+
+DROP TABLE reservation_audit;
+ALTER TABLE reservation_audit_new RENAME TO reservation_audit;
+
+CREATE INDEX IF NOT EXISTS idx_reservation_audit_reservation_id ON reservation_audit(reservation_id);
@@ -0,0 +1,7 @@
+-- Migration 027: Reservation token short codes
+-- A short code can be typed in by hand at venues without a scanner. Codes are only unique within an
+-- event, which the application checks when issuing them; tokens issued before this have none.
+
+ALTER TABLE reservation_tokens ADD COLUMN short_code TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_reservation_tokens_short_code ON reservation_tokens(short_code);
@@ -0,0 +1,5 @@
+-- Migration 028: Event sales closed
+-- Organizers can stop sales before an event is full (e.g. to hold seats back). The flag is separate
+-- from status, so closing sales neither marks the event full nor changes how spots are counted.
+
+ALTER TABLE events ADD COLUMN sales_closed INTEGER NOT NULL DEFAULT 0;
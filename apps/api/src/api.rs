@@ -3,41 +3,48 @@ use time::OffsetDateTime;
 use uuid::Uuid;
 use validator::Validate;
 
-#[derive(Debug, Deserialize, Validate)]
-#[validate(schema(function = "validate_event_times", message = "End time must be after start time"))]
-pub struct OpenEventRequest {
-    #[validate(length(min = 1, max = 255, message = "Event name must be between 1 and 255 characters"))]
-    pub name: String,
-    #[validate(length(max = 1000, message = "Description must be less than 1000 characters"))]
-    pub description: Option<String>,
-    #[validate(length(max = 255, message = "Location must be less than 255 characters"))]
-    pub location: Option<String>,
-    #[validate(range(min = 1, max = 10000, message = "Capacity must be between 1 and 10000"))]
-    pub capacity: u32,
-    #[serde(with = "time::serde::iso8601")]
-    pub start_time: OffsetDateTime,
-    #[serde(with = "time::serde::iso8601")]
-    pub end_time: OffsetDateTime,
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EventVisibility {
+    #[default]
+    Public,
+    Unlisted,
+    Private,
 }
 
-// Custom validation function for event times
-fn validate_event_times(event: &OpenEventRequest) -> Result<(), validator::ValidationError> {
-    if event.end_time <= event.start_time {
-        return Err(validator::ValidationError::new("invalid_time_range"));
-    }
-    Ok(())
-}
-
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub enum EventStatus {
+    #[serde(alias = "open")]
     Open,
+    #[serde(alias = "full")]
     Full,
+    #[serde(alias = "finished")]
     Finished,
+    #[serde(alias = "cancelled")]
+    Cancelled,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateEventStatusRequest {
+    pub status: EventStatus,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EventStatusResponse {
+    pub event_id: Uuid,
+    pub status: EventStatus,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SalesStatusResponse {
+    pub event_id: Uuid,
+    pub sales_closed: bool,
 }
 
 #[derive(Debug, Serialize)]
 pub struct OpenEventResponse {
     pub id: Uuid,
+    pub slug: String,
     pub name: String,
     pub description: Option<String>,
     #[serde(with = "time::serde::iso8601")]
@@ -46,6 +53,18 @@ pub struct OpenEventResponse {
     pub end_time: OffsetDateTime,
     pub capacity: u32,
     pub location: Option<String>,
+    pub max_spots_per_reservation: Option<u32>,
+    pub min_spots_per_reservation: Option<u32>,
+    pub visibility: EventVisibility,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub reservations_open_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub reservations_close_at: Option<OffsetDateTime>,
+    pub skip_verification: bool,
+    pub require_name_match: bool,
+    pub token_rotation_minutes: Option<u32>,
+    pub series_id: Option<Uuid>,
+    pub sales_closed: bool,
     #[serde(with = "time::serde::iso8601")]
     pub created_at: OffsetDateTime,
     #[serde(with = "time::serde::iso8601")]
@@ -63,12 +82,78 @@ pub struct ReserveRequest {
     pub user_email: String,
     #[validate(range(min = 1, max = 10000, message = "Spot count must be between 1 and 10000"))]
     pub spot_count: u32,
+    pub labels: Option<Vec<String>>,
+    /// Token from `POST /events/{id}/hold`, completing a held checkout
+    pub hold_token: Option<String>,
+    /// Answers to the event's custom questions, keyed by question key
+    pub answers: Option<serde_json::Value>,
+    /// Who the reservation is for (e.g. "Smith family"), when it differs from `user_name`
+    #[validate(length(max = 255, message = "Party name must be at most 255 characters"))]
+    pub party_name: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
 pub struct ReserveResponse {
     pub reservation_id: Uuid,
     pub status: ReservationStatus,
+    /// When a pending reservation stops being verifiable; omitted when links don't expire
+    #[serde(with = "time::serde::iso8601::option", skip_serializing_if = "Option::is_none")]
+    pub verification_expires_at: Option<OffsetDateTime>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DuplicateEventRequest {
+    #[serde(with = "time::serde::iso8601")]
+    pub start_time: OffsetDateTime,
+    /// Add the copy to the source event's series, starting one if needed
+    #[serde(default)]
+    pub attach_to_series: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReservationStatusQuery {
+    pub email: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReservationStatusResponse {
+    /// Whether the email holds a pending or confirmed reservation; cancelled ones don't count
+    pub has_reservation: bool,
+    /// `pending`, `confirmed` or, when the email's only reservation was cancelled, `cancelled`
+    pub status: Option<String>,
+    #[serde(with = "time::serde::iso8601::option", skip_serializing_if = "Option::is_none")]
+    pub cancelled_at: Option<OffsetDateTime>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ResendVerificationRequest {
+    pub event_id: Uuid,
+    #[validate(email(message = "Invalid email address"))]
+    pub user_email: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VerificationLinkResponse {
+    pub reservation_id: Uuid,
+    pub verification_url: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VerificationStatusResponse {
+    pub verified: bool,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub verified_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::iso8601::option", skip_serializing_if = "Option::is_none")]
+    pub cancelled_at: Option<OffsetDateTime>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct VerifyEmailQuery {
+    #[serde(default)]
+    pub redirect: bool,
+    /// Respond with the full `RetrieveReservationResponse` instead of `VerifyEmailResponse`
+    #[serde(default)]
+    pub expand: bool,
 }
 
 #[derive(Debug, Serialize)]
@@ -83,6 +168,7 @@ pub struct VerifyEmailResponse {
 pub enum ReservationStatus {
     Pending,
     Confirmed,
+    Cancelled,
 }
 
 #[derive(Debug, Serialize)]
@@ -91,7 +177,10 @@ pub struct RetrieveReservationResponse {
     pub user_name: String,
     pub user_email: String,
     pub status: ReservationStatus,
-    pub reservation_tokens: Vec<String>,
+    pub reservation_tokens: Vec<ReservationTokenResponse>,
+    /// For events that rotate ticket codes, when the active tokens' codes change; fetch again then
+    #[serde(with = "time::serde::iso8601::option", skip_serializing_if = "Option::is_none")]
+    pub tokens_rotate_at: Option<OffsetDateTime>,
     #[serde(with = "time::serde::iso8601")]
     pub created_at: OffsetDateTime,
     #[serde(with = "time::serde::iso8601")]
@@ -101,6 +190,43 @@ pub struct RetrieveReservationResponse {
     pub event: RetrieveReservationEventResponse,
 }
 
+#[derive(Debug, Default, Deserialize)]
+pub struct RetrieveReservationQuery {
+    #[serde(default)]
+    pub include_pending: bool,
+}
+
+/// What an unverified reservation may see about itself: no tokens or personal details
+#[derive(Debug, Serialize)]
+pub struct PendingReservationSummaryResponse {
+    pub reservation_id: Uuid,
+    pub status: ReservationStatus,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::iso8601::option", skip_serializing_if = "Option::is_none")]
+    pub verification_expires_at: Option<OffsetDateTime>,
+    pub event: RetrieveReservationEventResponse,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CancelledReservationSummaryResponse {
+    pub reservation_id: Uuid,
+    pub status: ReservationStatus,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::iso8601")]
+    pub cancelled_at: OffsetDateTime,
+    pub event: RetrieveReservationEventResponse,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum RetrieveReservationResult {
+    Confirmed(RetrieveReservationResponse),
+    Pending(PendingReservationSummaryResponse),
+    Cancelled(CancelledReservationSummaryResponse),
+}
+
 #[derive(Debug, Serialize)]
 pub struct RetrieveReservationEventResponse {
     pub id: Uuid,
@@ -112,4 +238,411 @@ pub struct RetrieveReservationEventResponse {
     pub end_time: OffsetDateTime,
     pub capacity: u32,
     pub location: Option<String>,
-}
\ No newline at end of file
+    pub status: EventStatus,
+    pub spots_confirmed: u32,
+    pub spots_remaining: u32,
+}
+#[derive(Debug, Serialize, Deserialize)]
+pub enum ReservationTokenStatus {
+    #[serde(alias = "inactive")]
+    Inactive,
+    #[serde(alias = "active")]
+    Active,
+    #[serde(alias = "used")]
+    Used,
+    #[serde(alias = "expired")]
+    Expired,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReservationTokenResponse {
+    pub token: String,
+    pub reservation_id: Uuid,
+    pub label: Option<String>,
+    /// For typing in at the door when the QR code can't be scanned
+    pub short_code: Option<String>,
+    pub status: ReservationTokenStatus,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub used_at: Option<OffsetDateTime>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EventTokensQuery {
+    pub status: Option<ReservationTokenStatus>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExpireActiveTokensResponse {
+    pub event_id: Uuid,
+    pub expired_count: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VersionResponse {
+    pub version: &'static str,
+    pub git_sha: Option<&'static str>,
+    pub migration_version: Option<i64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ScanQuery {
+    /// Guest name entered at the door; required for events with `require_name_match`
+    pub name: Option<String>,
+    /// Set when the scanned value is a hand-typed short code, which is only unique within its event
+    pub event_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BulkCancelQuery {
+    /// Comma-separated statuses to cancel: `pending`, `confirmed` or both; defaults to `pending`
+    pub status: Option<String>,
+    /// Email each cancelled reservation
+    #[serde(default)]
+    pub notify: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BulkCancelResponse {
+    pub event_id: Uuid,
+    pub cancelled_pending: usize,
+    pub cancelled_confirmed: usize,
+    /// Cancellation emails successfully sent
+    pub notified: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MigrationHealthResponse {
+    pub up_to_date: bool,
+    pub pending: Vec<PendingMigrationResponse>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PendingMigrationResponse {
+    pub version: i64,
+    pub description: String,
+    /// `not_applied`, `checksum_mismatch` or `unknown_to_binary`
+    pub reason: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FinishPastEventsResponse {
+    pub finished_event_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReconcileEventResponse {
+    pub event_id: Uuid,
+    pub capacity: u32,
+    /// Recomputed from the confirmed reservations
+    pub confirmed_spots: u32,
+    pub status: EventStatusChange,
+    pub tokens: TokenCorrections,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EventStatusChange {
+    pub before: EventStatus,
+    pub after: EventStatus,
+}
+
+/// How many tokens were relabelled to match their reservation's status
+#[derive(Debug, Serialize)]
+pub struct TokenCorrections {
+    /// Tokens of cancelled reservations that were still live
+    pub expired: u64,
+    /// Tokens of confirmed reservations still waiting for activation
+    pub activated: u64,
+    /// Tokens of pending reservations that were already scannable
+    pub deactivated: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MoveReservationRequest {
+    pub target_event_id: Uuid,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MoveReservationResponse {
+    pub reservation_id: Uuid,
+    pub event_id: Uuid,
+    pub reservation_tokens: Vec<ReservationTokenResponse>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct AdjustSpotsRequest {
+    #[validate(range(min = 1, max = 10000, message = "Spot count must be between 1 and 10000"))]
+    pub spot_count: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AdjustSpotsResponse {
+    pub reservation_id: Uuid,
+    pub event_id: Uuid,
+    pub spot_count: u32,
+    pub reservation_tokens: Vec<ReservationTokenResponse>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CancelReservationResponse {
+    pub reservation_id: Uuid,
+    pub event_id: Uuid,
+    pub spot_count: u32,
+    pub refund_status: RefundStatus,
+    #[serde(with = "time::serde::iso8601")]
+    pub cancelled_at: OffsetDateTime,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RefundStatus {
+    None,
+    Pending,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct EventQuestionRequest {
+    #[validate(length(min = 1, max = 64, message = "Question key must be between 1 and 64 characters"))]
+    pub key: String,
+    #[validate(length(min = 1, max = 500, message = "Question prompt must be between 1 and 500 characters"))]
+    pub prompt: String,
+    #[serde(default)]
+    pub required: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EventQuestionResponse {
+    pub key: String,
+    pub prompt: String,
+    pub required: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReservationAnswersResponse {
+    pub reservation_id: Uuid,
+    pub user_name: String,
+    pub user_email: String,
+    pub answers: serde_json::Value,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RosterEntryResponse {
+    pub reservation_id: Uuid,
+    pub user_name: String,
+    pub user_email: String,
+    /// The party name given at reserve time, or `user_name` when none was
+    pub party_name: String,
+    pub spot_count: u32,
+    pub status: ReservationStatus,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RemainingCheckinsResponse {
+    pub reservation_id: Uuid,
+    pub spot_count: u32,
+    pub remaining_checkins: usize,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReservationTimelineBucket {
+    #[default]
+    Hour,
+    Day,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReservationTimelineQuery {
+    #[serde(default)]
+    pub bucket: ReservationTimelineBucket,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReservationTimelineEntry {
+    #[serde(with = "time::serde::iso8601")]
+    pub bucket_start: OffsetDateTime,
+    pub spots: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EventAnalyticsResponse {
+    pub event_id: Uuid,
+    pub capacity: u32,
+    pub confirmed_spots: u32,
+    pub fill_rate: f64,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub peak_hour: Option<OffsetDateTime>,
+    pub average_party_size: Option<f64>,
+    pub spots_remaining: u32,
+    /// Set once `spots_remaining` is within `NEAR_CAPACITY_THRESHOLD` of capacity, for sellout alerts
+    pub near_capacity: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReservationTimelineResponse {
+    pub event_id: Uuid,
+    pub buckets: Vec<ReservationTimelineEntry>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct HoldRequest {
+    #[validate(range(min = 1, max = 10000, message = "Spot count must be between 1 and 10000"))]
+    pub spot_count: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HoldResponse {
+    pub hold_token: String,
+    pub event_id: Uuid,
+    pub spot_count: u32,
+    #[serde(with = "time::serde::iso8601")]
+    pub expires_at: OffsetDateTime,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EmailLogQuery {
+    pub email: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EmailLogEntryResponse {
+    pub to: String,
+    pub kind: String,
+    pub status: String,
+    pub error: Option<String>,
+    #[serde(with = "time::serde::iso8601")]
+    pub at: OffsetDateTime,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TokenRecordResponse {
+    pub token: String,
+    pub status: String,
+    pub label: Option<String>,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub used_at: Option<OffsetDateTime>,
+    pub reservation_id: Uuid,
+    pub reservation_status: String,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub reservation_cancelled_at: Option<OffsetDateTime>,
+    pub event_id: Uuid,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ReservationSearchQuery {
+    #[validate(length(min = 3, message = "Search needs at least 3 characters of the email"))]
+    pub email_contains: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReservationSearchResultResponse {
+    pub reservation_id: Uuid,
+    /// Masked, e.g. `j***@example.com`
+    pub user_email: String,
+    pub user_name: String,
+    pub status: String,
+    pub spot_count: u32,
+    pub event_id: Uuid,
+    pub event_name: String,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct MyTokensAccessRequest {
+    #[validate(email(message = "Invalid email address"))]
+    pub email: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MyTokensQuery {
+    pub email: String,
+    /// Token from the email sent by `POST /my/tokens/access`
+    pub access_token: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MyTokenResponse {
+    pub token: String,
+    pub reservation_id: Uuid,
+    pub label: Option<String>,
+    pub event: MyTokenEventResponse,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MyTokenEventResponse {
+    pub id: Uuid,
+    pub name: String,
+    #[serde(with = "time::serde::iso8601")]
+    pub start_time: OffsetDateTime,
+    #[serde(with = "time::serde::iso8601")]
+    pub end_time: OffsetDateTime,
+    pub location: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EventSearchQuery {
+    pub q: Option<String>,
+    #[serde(default, with = "time::serde::iso8601::option")]
+    pub from: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::iso8601::option")]
+    pub to: Option<OffsetDateTime>,
+    pub location: Option<String>,
+    #[serde(default)]
+    pub only_available: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AdminEventsQuery {
+    pub status: Option<EventStatus>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DailyEventsQuery {
+    /// Day to report on as `YYYY-MM-DD`, in UTC
+    pub date: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AdminEventResponse {
+    #[serde(flatten)]
+    pub event: OpenEventResponse,
+    /// Confirmed reservations (parties)
+    pub reservation_count: u32,
+    /// Spots held by confirmed reservations
+    pub reserved_spots: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportEventBackupResponse {
+    pub event_id: Uuid,
+    pub reservations: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportEventsResponse {
+    pub imported: usize,
+    pub event_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DedupeReservationsQuery {
+    pub event_id: Uuid,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ResendPendingResponse {
+    pub event_id: Uuid,
+    pub sent: usize,
+    pub failed: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DedupeReservationsResponse {
+    pub event_id: Uuid,
+    pub kept_reservation_ids: Vec<Uuid>,
+    pub cancelled_reservation_ids: Vec<Uuid>,
+}
@@ -0,0 +1,44 @@
+use axum::extract::{FromRequestParts, OptionalFromRequestParts};
+use axum::http::request::Parts;
+
+use crate::error::AppError;
+use crate::AppState;
+
+/// Header machine clients use to authenticate with one of the configured `API_KEYS`
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Extractor for callers allowed to use admin endpoints
+#[derive(Debug, Clone)]
+pub struct AdminAuth;
+
+impl AdminAuth {
+    fn authorize(parts: &Parts, state: &AppState) -> Option<Self> {
+        let presented = parts.headers.get(API_KEY_HEADER)?.to_str().ok()?;
+
+        state.config.api_keys
+            .iter()
+            .any(|key| constant_time_eq(key.as_bytes(), presented.as_bytes()))
+            .then_some(AdminAuth)
+    }
+}
+
+impl FromRequestParts<AppState> for AdminAuth {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        Self::authorize(parts, state).ok_or(AppError::Unauthorized)
+    }
+}
+
+impl OptionalFromRequestParts<AppState> for AdminAuth {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Option<Self>, Self::Rejection> {
+        Ok(Self::authorize(parts, state))
+    }
+}
+
+/// Compare secrets without short-circuiting on the first differing byte
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+/// Format version written into every backup; imports reject documents from other versions
+pub const BACKUP_VERSION: u32 = 1;
+
+/// A whole event as a single JSON document: the event, its questions, and every reservation
+/// with its tokens. Ids are left out because an import always creates new ones; secret token
+/// values are kept so tickets and links already sent out keep working after a restore.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct EventBackup {
+    pub version: u32,
+    pub event: BackedUpEvent,
+    pub questions: Vec<BackedUpQuestion>,
+    pub reservations: Vec<BackedUpReservation>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct BackedUpEvent {
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(with = "time::serde::iso8601")]
+    pub start_time: OffsetDateTime,
+    #[serde(with = "time::serde::iso8601")]
+    pub end_time: OffsetDateTime,
+    pub capacity: u32,
+    pub location: Option<String>,
+    pub max_reservations: Option<u32>,
+    pub max_spots_per_reservation: Option<u32>,
+    pub min_spots_per_reservation: Option<u32>,
+    pub visibility: String,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub reservations_open_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub reservations_close_at: Option<OffsetDateTime>,
+    pub skip_verification: bool,
+    #[serde(default)]
+    pub require_name_match: bool,
+    pub token_rotation_minutes: Option<u32>,
+    /// Stored status: `open`, `full` or `closed`
+    pub status: String,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub cancelled_at: Option<OffsetDateTime>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct BackedUpQuestion {
+    pub key: String,
+    pub prompt: String,
+    pub required: bool,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct BackedUpReservation {
+    pub user_name: String,
+    pub user_email: String,
+    pub spot_count: u32,
+    pub spot_labels: Option<Vec<String>>,
+    pub answers: Option<serde_json::Value>,
+    #[serde(default)]
+    pub party_name: Option<String>,
+    /// Stored status: `pending`, `confirmed` or `cancelled`
+    pub status: String,
+    pub verification_token: String,
+    pub cancellation_token: Option<String>,
+    /// Stored refund status: `none`, `pending` or `issued`
+    #[serde(default = "default_refund_status")]
+    pub refund_status: String,
+    #[serde(default, with = "time::serde::iso8601::option")]
+    pub cancelled_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub verified_at: Option<OffsetDateTime>,
+    pub tokens: Vec<BackedUpToken>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct BackedUpToken {
+    pub token: String,
+    pub label: Option<String>,
+    /// Stored status: `inactive`, `active`, `used` or `expired`
+    pub status: String,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub used_at: Option<OffsetDateTime>,
+    #[serde(default)]
+    pub short_code: Option<String>,
+}
+
+/// Backups written before refunds were tracked carry no refund status
+fn default_refund_status() -> String {
+    "none".to_string()
+}
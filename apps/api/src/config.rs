@@ -1,26 +1,68 @@
 use std::env;
+use thiserror::Error;
+
+/// Placeholder secrets shipped as defaults; fine locally, never in production
+const DEFAULT_JWT_SECRET: &str = "your-jwt-secret-key-change-this-in-production";
+const DEFAULT_SESSION_SECRET: &str = "your-session-secret-change-this-in-production";
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("Invalid configuration: {}", .0.join("; "))]
+    Invalid(Vec<String>),
+}
 
 /// Application configuration loaded from environment variables
 #[derive(Debug, Clone)]
 pub struct Config {
+    #[cfg_attr(not(test), expect(dead_code))]
     pub database_url: String,
+    #[cfg_attr(not(test), expect(dead_code))]
     pub base_url: String,
     pub app_name: String,
     pub app_environment: String,
     pub app_url: String,
+    #[cfg_attr(not(test), expect(dead_code))]
     pub port: u16,
+    pub request_timeout_seconds: u64,
     pub email_from: String,
+    #[expect(dead_code)]
     pub email_from_name: String,
+    pub email_from_verification: Option<String>,
+    pub email_from_confirmation: Option<String>,
     pub email_provider: String,
+    pub email_send_timeout_seconds: u64,
     pub jwt_secret: String,
     pub session_secret: String,
     pub rate_limit_requests_per_minute: u32,
+    #[expect(dead_code)]
     pub rate_limit_burst: u32,
+    #[expect(dead_code)]
     pub log_level: String,
+    #[expect(dead_code)]
     pub log_format: String,
     pub cors_allowed_origins: Vec<String>,
     pub cors_allowed_methods: Vec<String>,
     pub cors_allowed_headers: Vec<String>,
+    pub cors_max_age_seconds: u64,
+    pub max_event_capacity: u32,
+    pub random_event_min_capacity: u32,
+    pub random_event_max_capacity: u32,
+    pub random_event_max_spots: u32,
+    pub verification_token_length: Option<usize>,
+    pub verification_ttl_hours: Option<u64>,
+    pub reservation_hold_ttl_seconds: u64,
+    pub refund_cutoff_hours: Option<u64>,
+    pub my_tokens_access_ttl_minutes: u64,
+    pub confirm_redirect_url: Option<String>,
+    pub api_keys: Vec<String>,
+    pub scan_dedupe_seconds: u64,
+    pub blocked_email_domains: Vec<String>,
+    pub count_pending_toward_capacity: bool,
+    pub near_capacity_threshold: f64,
+    pub verification_emails_per_hour: u32,
+    pub pre_issue_reservation_tokens: bool,
+    pub wallet_pass_type_id: String,
+    pub wallet_team_id: String,
 }
 
 impl Config {
@@ -41,16 +83,30 @@ impl Config {
                 .unwrap_or_else(|_| "8000".to_string())
                 .parse()
                 .unwrap_or(8000),
+            request_timeout_seconds: env::var("REQUEST_TIMEOUT_SECONDS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30),
             email_from: env::var("EMAIL_FROM")
                 .unwrap_or_else(|_| "noreply@quick-res.example.com".to_string()),
             email_from_name: env::var("EMAIL_FROM_NAME")
                 .unwrap_or_else(|_| "Quick Reservations".to_string()),
+            email_from_verification: env::var("EMAIL_FROM_VERIFICATION")
+                .ok()
+                .filter(|from| !from.trim().is_empty()),
+            email_from_confirmation: env::var("EMAIL_FROM_CONFIRMATION")
+                .ok()
+                .filter(|from| !from.trim().is_empty()),
             email_provider: env::var("EMAIL_PROVIDER")
                 .unwrap_or_else(|_| "console".to_string()),
+            email_send_timeout_seconds: env::var("EMAIL_SEND_TIMEOUT_SECONDS")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .unwrap_or(10),
             jwt_secret: env::var("JWT_SECRET")
-                .unwrap_or_else(|_| "your-jwt-secret-key-change-this-in-production".to_string()),
+                .unwrap_or_else(|_| DEFAULT_JWT_SECRET.to_string()),
             session_secret: env::var("SESSION_SECRET")
-                .unwrap_or_else(|_| "your-session-secret-change-this-in-production".to_string()),
+                .unwrap_or_else(|_| DEFAULT_SESSION_SECRET.to_string()),
             rate_limit_requests_per_minute: env::var("RATE_LIMIT_REQUESTS_PER_MINUTE")
                 .unwrap_or_else(|_| "60".to_string())
                 .parse()
@@ -64,24 +120,173 @@ impl Config {
             log_format: env::var("LOG_FORMAT")
                 .unwrap_or_else(|_| "json".to_string()),
             cors_allowed_origins: env::var("CORS_ALLOWED_ORIGINS")
-                .unwrap_or_else(|_| "http://localhost:3000,http://localhost:8080".to_string())
+                .unwrap_or_else(|_| "http://localhost:3000,http://localhost:5173,http://localhost:8080".to_string())
                 .split(',')
                 .map(|s| s.trim().to_string())
                 .collect(),
             cors_allowed_methods: env::var("CORS_ALLOWED_METHODS")
-                .unwrap_or_else(|_| "GET,POST,PUT,DELETE,OPTIONS".to_string())
+                .unwrap_or_else(|_| "GET,POST,PUT,PATCH,DELETE,OPTIONS".to_string())
                 .split(',')
                 .map(|s| s.trim().to_string())
                 .collect(),
             cors_allowed_headers: env::var("CORS_ALLOWED_HEADERS")
-                .unwrap_or_else(|_| "Content-Type,Authorization,X-Requested-With".to_string())
+                .unwrap_or_else(|_| "Content-Type,Authorization,X-Requested-With,X-Api-Key".to_string())
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .collect(),
+            cors_max_age_seconds: env::var("CORS_MAX_AGE_SECONDS")
+                .unwrap_or_else(|_| "3600".to_string())
+                .parse()
+                .unwrap_or(3600),
+            max_event_capacity: env::var("MAX_EVENT_CAPACITY")
+                .unwrap_or_else(|_| "10000".to_string())
+                .parse()
+                .unwrap_or(10000),
+            random_event_min_capacity: env::var("RANDOM_EVENT_MIN_CAPACITY")
+                .unwrap_or_else(|_| "20".to_string())
+                .parse()
+                .unwrap_or(20),
+            random_event_max_capacity: env::var("RANDOM_EVENT_MAX_CAPACITY")
+                .unwrap_or_else(|_| "100".to_string())
+                .parse()
+                .unwrap_or(100),
+            random_event_max_spots: env::var("RANDOM_EVENT_MAX_SPOTS")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()
+                .unwrap_or(5),
+            verification_token_length: env::var("VERIFICATION_TOKEN_LENGTH")
+                .ok()
+                .and_then(|len| len.parse().ok()),
+            verification_ttl_hours: env::var("VERIFICATION_TTL_HOURS")
+                .ok()
+                .and_then(|hours| hours.parse().ok()),
+            reservation_hold_ttl_seconds: env::var("RESERVATION_HOLD_TTL_SECONDS")
+                .unwrap_or_else(|_| "300".to_string())
+                .parse()
+                .unwrap_or(300),
+            refund_cutoff_hours: env::var("REFUND_CUTOFF_HOURS")
+                .ok()
+                .and_then(|hours| hours.parse().ok()),
+            my_tokens_access_ttl_minutes: env::var("MY_TOKENS_ACCESS_TTL_MINUTES")
+                .unwrap_or_else(|_| "15".to_string())
+                .parse()
+                .unwrap_or(15),
+            confirm_redirect_url: env::var("CONFIRM_REDIRECT_URL")
+                .ok()
+                .filter(|url| !url.trim().is_empty()),
+            api_keys: env::var("API_KEYS")
+                .unwrap_or_default()
                 .split(',')
                 .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
                 .collect(),
+            scan_dedupe_seconds: env::var("SCAN_DEDUPE_SECONDS")
+                .unwrap_or_else(|_| "2".to_string())
+                .parse()
+                .unwrap_or(2),
+            blocked_email_domains: env::var("BLOCKED_EMAIL_DOMAINS")
+                .unwrap_or_default()
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect(),
+            count_pending_toward_capacity: env::var("COUNT_PENDING_TOWARD_CAPACITY")
+                .map(|value| value.trim().eq_ignore_ascii_case("true"))
+                .unwrap_or(false),
+            near_capacity_threshold: env::var("NEAR_CAPACITY_THRESHOLD")
+                .unwrap_or_else(|_| "0.1".to_string())
+                .parse()
+                .unwrap_or(0.1),
+            verification_emails_per_hour: env::var("VERIFICATION_EMAILS_PER_HOUR")
+                .unwrap_or_else(|_| "3".to_string())
+                .parse()
+                .unwrap_or(3),
+            pre_issue_reservation_tokens: env::var("PRE_ISSUE_RESERVATION_TOKENS")
+                .map(|value| value.trim().eq_ignore_ascii_case("true"))
+                .unwrap_or(false),
+            wallet_pass_type_id: env::var("WALLET_PASS_TYPE_ID")
+                .unwrap_or_else(|_| "pass.com.example.quick-res".to_string()),
+            wallet_team_id: env::var("WALLET_TEAM_ID")
+                .unwrap_or_default(),
         })
     }
 
+    /// Address verification emails come from: `EMAIL_FROM_VERIFICATION`, falling back to `EMAIL_FROM`
+    pub fn verification_email_from(&self) -> &str {
+        self.email_from_verification.as_deref().unwrap_or(&self.email_from)
+    }
+
+    /// Address confirmation emails come from: `EMAIL_FROM_CONFIRMATION`, falling back to `EMAIL_FROM`
+    pub fn confirmation_email_from(&self) -> &str {
+        self.email_from_confirmation.as_deref().unwrap_or(&self.email_from)
+    }
+
+    /// How long a pending reservation has to be verified, if verification links expire at all
+    pub fn verification_ttl(&self) -> Option<time::Duration> {
+        self.verification_ttl_hours.map(|hours| time::Duration::hours(hours as i64))
+    }
+
+    /// How long before an event starts a guest can cancel and still be owed a refund, if refunds are given at all
+    pub fn refund_cutoff(&self) -> Option<time::Duration> {
+        self.refund_cutoff_hours.map(|hours| time::Duration::hours(hours as i64))
+    }
+
+    /// Check for settings that are unsafe or incomplete, reporting every problem at once
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut problems = Vec::new();
+
+        if self.request_timeout_seconds == 0 {
+            problems.push("REQUEST_TIMEOUT_SECONDS must be at least 1".to_string());
+        }
+
+        if self.max_event_capacity == 0 {
+            problems.push("MAX_EVENT_CAPACITY must be at least 1".to_string());
+        }
+        if self.random_event_min_capacity == 0 {
+            problems.push("RANDOM_EVENT_MIN_CAPACITY must be at least 1".to_string());
+        }
+        if self.random_event_min_capacity > self.random_event_max_capacity {
+            problems.push("RANDOM_EVENT_MIN_CAPACITY must not be greater than RANDOM_EVENT_MAX_CAPACITY".to_string());
+        }
+        if self.random_event_max_capacity > self.max_event_capacity {
+            problems.push("RANDOM_EVENT_MAX_CAPACITY must not be greater than MAX_EVENT_CAPACITY".to_string());
+        }
+        if self.random_event_max_spots == 0 {
+            problems.push("RANDOM_EVENT_MAX_SPOTS must be at least 1".to_string());
+        }
+
+        if !(0.0..=1.0).contains(&self.near_capacity_threshold) {
+            problems.push("NEAR_CAPACITY_THRESHOLD must be between 0 and 1".to_string());
+        }
+
+        if self.is_production() {
+            if self.jwt_secret == DEFAULT_JWT_SECRET {
+                problems.push("JWT_SECRET must be changed from the default in production".to_string());
+            }
+            if self.session_secret == DEFAULT_SESSION_SECRET {
+                problems.push("SESSION_SECRET must be changed from the default in production".to_string());
+            }
+
+            match self.email_provider.to_lowercase().as_str() {
+                "smtp" if env::var("SMTP_HOST").is_err() => {
+                    problems.push("SMTP_HOST is required when EMAIL_PROVIDER=smtp".to_string());
+                }
+                "sendgrid" if self.sendgrid_api_key().is_none() => {
+                    problems.push("SENDGRID_API_KEY is required when EMAIL_PROVIDER=sendgrid".to_string());
+                }
+                _ => {}
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Invalid(problems))
+        }
+    }
+
     /// Get SMTP configuration if email provider is set to SMTP
+    #[cfg_attr(not(test), expect(dead_code))]
     pub fn smtp_config(&self) -> Option<SmtpConfig> {
         if self.email_provider.to_lowercase() == "smtp" {
             Some(SmtpConfig {
@@ -117,6 +322,7 @@ impl Config {
     }
 
     /// Check if the application is running in development
+    #[cfg_attr(not(test), expect(dead_code))]
     pub fn is_development(&self) -> bool {
         self.app_environment.to_lowercase() == "development"
     }
@@ -124,11 +330,13 @@ impl Config {
 
 /// SMTP configuration for email sending
 #[derive(Debug, Clone)]
+#[cfg_attr(not(test), expect(dead_code))]
 pub struct SmtpConfig {
     pub host: String,
     pub port: u16,
     pub username: Option<String>,
     pub password: Option<String>,
+    #[cfg_attr(test, expect(dead_code))]
     pub tls: bool,
 }
 
@@ -158,6 +366,27 @@ mod tests {
         env::remove_var("CORS_ALLOWED_ORIGINS");
         env::remove_var("CORS_ALLOWED_METHODS");
         env::remove_var("CORS_ALLOWED_HEADERS");
+        env::remove_var("CORS_MAX_AGE_SECONDS");
+        env::remove_var("MAX_EVENT_CAPACITY");
+        env::remove_var("RANDOM_EVENT_MIN_CAPACITY");
+        env::remove_var("RANDOM_EVENT_MAX_CAPACITY");
+        env::remove_var("RANDOM_EVENT_MAX_SPOTS");
+        env::remove_var("RESERVATION_HOLD_TTL_SECONDS");
+        env::remove_var("MY_TOKENS_ACCESS_TTL_MINUTES");
+        env::remove_var("REFUND_CUTOFF_HOURS");
+        env::remove_var("NEAR_CAPACITY_THRESHOLD");
+        env::remove_var("CONFIRM_REDIRECT_URL");
+        env::remove_var("API_KEYS");
+        env::remove_var("SCAN_DEDUPE_SECONDS");
+        env::remove_var("VERIFICATION_TTL_HOURS");
+        env::remove_var("BLOCKED_EMAIL_DOMAINS");
+        env::remove_var("COUNT_PENDING_TOWARD_CAPACITY");
+        env::remove_var("VERIFICATION_EMAILS_PER_HOUR");
+        env::remove_var("PRE_ISSUE_RESERVATION_TOKENS");
+        env::remove_var("EMAIL_SEND_TIMEOUT_SECONDS");
+        env::remove_var("REQUEST_TIMEOUT_SECONDS");
+        env::remove_var("EMAIL_FROM_VERIFICATION");
+        env::remove_var("EMAIL_FROM_CONFIRMATION");
         
         let config = Config::from_env().unwrap();
         
@@ -166,6 +395,27 @@ mod tests {
         assert_eq!(config.app_name, "Quick Reservations");
         assert_eq!(config.port, 8000);
         assert_eq!(config.email_provider, "console");
+        assert_eq!(config.cors_max_age_seconds, 3600);
+        assert_eq!(config.max_event_capacity, 10000);
+        assert_eq!(config.random_event_min_capacity, 20);
+        assert_eq!(config.random_event_max_capacity, 100);
+        assert_eq!(config.random_event_max_spots, 5);
+        assert_eq!(config.reservation_hold_ttl_seconds, 300);
+        assert_eq!(config.my_tokens_access_ttl_minutes, 15);
+        assert_eq!(config.refund_cutoff(), None);
+        assert_eq!(config.confirm_redirect_url, None);
+        assert!(config.api_keys.is_empty());
+        assert_eq!(config.scan_dedupe_seconds, 2);
+        assert_eq!(config.verification_ttl(), None);
+        assert!(config.blocked_email_domains.is_empty());
+        assert!(!config.count_pending_toward_capacity);
+        assert_eq!(config.near_capacity_threshold, 0.1);
+        assert_eq!(config.verification_emails_per_hour, 3);
+        assert!(!config.pre_issue_reservation_tokens);
+        assert_eq!(config.email_send_timeout_seconds, 10);
+        assert_eq!(config.request_timeout_seconds, 30);
+        assert_eq!(config.verification_email_from(), "noreply@quick-res.example.com");
+        assert_eq!(config.confirmation_email_from(), "noreply@quick-res.example.com");
     }
 
     #[test]
@@ -293,4 +543,52 @@ mod tests {
         
         env::remove_var("APP_ENVIRONMENT");
     }
+
+    #[test]
+    fn test_validate_rejects_default_secrets_in_production() {
+        let mut config = Config::from_env().unwrap();
+        config.app_environment = "production".to_string();
+        config.email_provider = "console".to_string();
+        config.jwt_secret = DEFAULT_JWT_SECRET.to_string();
+        config.session_secret = DEFAULT_SESSION_SECRET.to_string();
+
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("JWT_SECRET"));
+        assert!(err.contains("SESSION_SECRET"));
+    }
+
+    #[test]
+    fn test_validate_accepts_production_config_with_real_secrets() {
+        let mut config = Config::from_env().unwrap();
+        config.app_environment = "production".to_string();
+        config.email_provider = "console".to_string();
+        config.jwt_secret = "a-real-jwt-secret".to_string();
+        config.session_secret = "a-real-session-secret".to_string();
+
+        assert!(config.validate().is_ok());
+
+        // Development is allowed to keep the placeholders
+        config.app_environment = "development".to_string();
+        config.jwt_secret = DEFAULT_JWT_SECRET.to_string();
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_inverted_random_event_capacity_range() {
+        let mut config = Config::from_env().unwrap();
+        config.random_event_min_capacity = 50;
+        config.random_event_max_capacity = 10;
+
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("RANDOM_EVENT_MIN_CAPACITY must not be greater than RANDOM_EVENT_MAX_CAPACITY"));
+    }
+
+    #[test]
+    fn test_validate_rejects_zero_random_event_max_spots() {
+        let mut config = Config::from_env().unwrap();
+        config.random_event_max_spots = 0;
+
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("RANDOM_EVENT_MAX_SPOTS must be at least 1"));
+    }
 }
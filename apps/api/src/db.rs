@@ -1,8 +1,11 @@
-use sqlx::{Pool, Sqlite, SqlitePool};
+use sqlx::{Pool, QueryBuilder, Sqlite, SqliteConnection, SqlitePool, Transaction};
 use std::env;
 use uuid::Uuid;
+use time::format_description::well_known::Rfc3339;
 use time::OffsetDateTime;
 use thiserror::Error;
+use crate::backup;
+use crate::email::{EmailError, EmailKind};
 use crate::models;
 
 #[derive(Debug, Error)]
@@ -15,6 +18,22 @@ pub enum DatabaseError {
     EventNotFound,
     #[error("Reservation not found")]
     ReservationNotFound,
+    #[error("Reservation token not found")]
+    ReservationTokenNotFound,
+    #[error("A reservation already exists for this email and event")]
+    DuplicateReservation,
+    #[error("Hold not found")]
+    HoldNotFound,
+    #[error("Backup contains tokens that already exist")]
+    BackupConflict,
+    #[error("Database is busy")]
+    Busy,
+    #[error("No unused short code found for the event")]
+    ShortCodesExhausted,
+    #[error("This event only accepts scanned ticket codes")]
+    TicketCodeRequired,
+    #[error("Ticket code has expired; refresh it and scan again")]
+    TicketCodeExpired,
 }
 
 // Database Models - Used for database operations and internal data representation
@@ -22,13 +41,26 @@ pub enum DatabaseError {
 #[derive(Debug, sqlx::FromRow)]
 struct EventRow {
     id: String,  // Store UUID as TEXT in SQLite
+    slug: String,
     name: String,
     description: Option<String>,
     start_time: OffsetDateTime,
     end_time: OffsetDateTime,
     capacity: u32,
     location: Option<String>,
+    max_reservations: Option<u32>,
+    max_spots_per_reservation: Option<u32>,
+    min_spots_per_reservation: Option<u32>,
+    visibility: String,
+    reservations_open_at: Option<OffsetDateTime>,
+    reservations_close_at: Option<OffsetDateTime>,
+    skip_verification: bool,
+    require_name_match: bool,
+    token_rotation_minutes: Option<u32>,
+    series_id: Option<String>,
+    sales_closed: bool,
     status: String,
+    cancelled_at: Option<OffsetDateTime>,
     created_at: OffsetDateTime,
     updated_at: OffsetDateTime,
 }
@@ -37,12 +69,24 @@ impl From<EventRow> for models::Event<models::Open> {
     fn from(row: EventRow) -> Self {
         models::Event {
             id: Uuid::parse_str(&row.id).expect("Invalid UUID in database"),
+            slug: row.slug,
             name: row.name,
             description: row.description,
             start_time: row.start_time,
             end_time: row.end_time,
             capacity: row.capacity,
             location: row.location,
+            max_reservations: row.max_reservations,
+            max_spots_per_reservation: row.max_spots_per_reservation,
+            min_spots_per_reservation: row.min_spots_per_reservation,
+            visibility: models::EventVisibility::from_db(&row.visibility),
+            reservations_open_at: row.reservations_open_at,
+            reservations_close_at: row.reservations_close_at,
+            skip_verification: row.skip_verification,
+            require_name_match: row.require_name_match,
+            token_rotation_minutes: row.token_rotation_minutes,
+            series_id: row.series_id.map(|id| Uuid::parse_str(&id).expect("Invalid series UUID in database")),
+            sales_closed: row.sales_closed,
             created_at: row.created_at,
             updated_at: row.updated_at,
             status: models::Open,
@@ -55,12 +99,24 @@ impl From<EventRow> for models::Event<models::Full> {
     fn from(row: EventRow) -> Self {
         models::Event {
             id: Uuid::parse_str(&row.id).expect("Invalid UUID in database"),
+            slug: row.slug,
             name: row.name,
             description: row.description,
             start_time: row.start_time,
             end_time: row.end_time,
             capacity: row.capacity,
             location: row.location,
+            max_reservations: row.max_reservations,
+            max_spots_per_reservation: row.max_spots_per_reservation,
+            min_spots_per_reservation: row.min_spots_per_reservation,
+            visibility: models::EventVisibility::from_db(&row.visibility),
+            reservations_open_at: row.reservations_open_at,
+            reservations_close_at: row.reservations_close_at,
+            skip_verification: row.skip_verification,
+            require_name_match: row.require_name_match,
+            token_rotation_minutes: row.token_rotation_minutes,
+            series_id: row.series_id.map(|id| Uuid::parse_str(&id).expect("Invalid series UUID in database")),
+            sales_closed: row.sales_closed,
             created_at: row.created_at,
             updated_at: row.updated_at,
             status: models::Full,
@@ -68,6 +124,93 @@ impl From<EventRow> for models::Event<models::Full> {
     }
 }
 
+impl From<EventRow> for models::Event<models::Finished> {
+    fn from(row: EventRow) -> Self {
+        models::Event {
+            id: Uuid::parse_str(&row.id).expect("Invalid UUID in database"),
+            slug: row.slug,
+            name: row.name,
+            description: row.description,
+            start_time: row.start_time,
+            end_time: row.end_time,
+            capacity: row.capacity,
+            location: row.location,
+            max_reservations: row.max_reservations,
+            max_spots_per_reservation: row.max_spots_per_reservation,
+            min_spots_per_reservation: row.min_spots_per_reservation,
+            visibility: models::EventVisibility::from_db(&row.visibility),
+            reservations_open_at: row.reservations_open_at,
+            reservations_close_at: row.reservations_close_at,
+            skip_verification: row.skip_verification,
+            require_name_match: row.require_name_match,
+            token_rotation_minutes: row.token_rotation_minutes,
+            series_id: row.series_id.map(|id| Uuid::parse_str(&id).expect("Invalid series UUID in database")),
+            sales_closed: row.sales_closed,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+            status: models::Finished,
+        }
+    }
+}
+
+impl From<EventRow> for models::Event<models::Cancelled> {
+    fn from(row: EventRow) -> Self {
+        models::Event {
+            id: Uuid::parse_str(&row.id).expect("Invalid UUID in database"),
+            slug: row.slug,
+            name: row.name,
+            description: row.description,
+            start_time: row.start_time,
+            end_time: row.end_time,
+            capacity: row.capacity,
+            location: row.location,
+            max_reservations: row.max_reservations,
+            max_spots_per_reservation: row.max_spots_per_reservation,
+            min_spots_per_reservation: row.min_spots_per_reservation,
+            visibility: models::EventVisibility::from_db(&row.visibility),
+            reservations_open_at: row.reservations_open_at,
+            reservations_close_at: row.reservations_close_at,
+            skip_verification: row.skip_verification,
+            require_name_match: row.require_name_match,
+            token_rotation_minutes: row.token_rotation_minutes,
+            series_id: row.series_id.map(|id| Uuid::parse_str(&id).expect("Invalid series UUID in database")),
+            sales_closed: row.sales_closed,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+            status: models::Cancelled,
+        }
+    }
+}
+
+impl From<EventRow> for models::AnyEvent {
+    fn from(row: EventRow) -> Self {
+        // Finished and cancelled events are both stored with the schema's 'closed' status
+        match (row.status.as_str(), row.cancelled_at.is_some()) {
+            ("full", _) => models::AnyEvent::Full(row.into()),
+            ("closed", true) => models::AnyEvent::Cancelled(row.into()),
+            ("closed", false) => models::AnyEvent::Finished(row.into()),
+            _ => models::AnyEvent::Open(row.into()),
+        }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct AdminEventRow {
+    #[sqlx(flatten)]
+    event: EventRow,
+    reservation_count: u32,
+    reserved_spots: u32,
+}
+
+impl From<AdminEventRow> for models::AdminEventSummary {
+    fn from(row: AdminEventRow) -> Self {
+        models::AdminEventSummary {
+            event: row.event.into(),
+            reservation_count: row.reservation_count,
+            reserved_spots: row.reserved_spots,
+        }
+    }
+}
 
 #[derive(Debug, sqlx::FromRow)]
 struct ReservationRow {
@@ -76,6 +219,12 @@ struct ReservationRow {
     user_name: String,
     user_email: String,
     spot_count: u32,
+    spot_labels: Option<String>,
+    answers: Option<String>,
+    party_name: Option<String>,
+    cancellation_token: Option<String>,
+    refund_status: String,
+    cancelled_at: Option<OffsetDateTime>,
     status: String,
     verification_token: String,
     created_at: OffsetDateTime,
@@ -83,11 +232,303 @@ struct ReservationRow {
     verified_at: Option<OffsetDateTime>,
 }
 
+/// A reservation joined with its event; event columns are prefixed to avoid clashing
 #[derive(Debug, sqlx::FromRow)]
-struct ReservationTokenRow {
+struct ReservationWithEventRow {
+    #[sqlx(flatten)]
+    reservation: ReservationRow,
+    event_slug: String,
+    event_name: String,
+    event_description: Option<String>,
+    event_start_time: OffsetDateTime,
+    event_end_time: OffsetDateTime,
+    event_capacity: u32,
+    event_location: Option<String>,
+    event_max_reservations: Option<u32>,
+    event_max_spots_per_reservation: Option<u32>,
+    event_min_spots_per_reservation: Option<u32>,
+    event_visibility: String,
+    event_reservations_open_at: Option<OffsetDateTime>,
+    event_reservations_close_at: Option<OffsetDateTime>,
+    event_skip_verification: bool,
+    event_require_name_match: bool,
+    event_token_rotation_minutes: Option<u32>,
+    event_series_id: Option<String>,
+    event_sales_closed: bool,
+    event_status: String,
+    event_cancelled_at: Option<OffsetDateTime>,
+    event_created_at: OffsetDateTime,
+    event_updated_at: OffsetDateTime,
+}
+
+impl ReservationWithEventRow {
+    fn split(self) -> (ReservationRow, EventRow) {
+        let event = EventRow {
+            id: self.reservation.event_id.clone(),
+            slug: self.event_slug,
+            name: self.event_name,
+            description: self.event_description,
+            start_time: self.event_start_time,
+            end_time: self.event_end_time,
+            capacity: self.event_capacity,
+            location: self.event_location,
+            max_reservations: self.event_max_reservations,
+            max_spots_per_reservation: self.event_max_spots_per_reservation,
+            min_spots_per_reservation: self.event_min_spots_per_reservation,
+            visibility: self.event_visibility,
+            reservations_open_at: self.event_reservations_open_at,
+            reservations_close_at: self.event_reservations_close_at,
+            skip_verification: self.event_skip_verification,
+            require_name_match: self.event_require_name_match,
+            token_rotation_minutes: self.event_token_rotation_minutes,
+            series_id: self.event_series_id,
+            sales_closed: self.event_sales_closed,
+            status: self.event_status,
+            cancelled_at: self.event_cancelled_at,
+            created_at: self.event_created_at,
+            updated_at: self.event_updated_at,
+        };
+        (self.reservation, event)
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct HoldRow {
+    id: String,
+    event_id: String,
+    spot_count: u32,
+    hold_token: String,
+    created_at: OffsetDateTime,
+    expires_at: OffsetDateTime,
+}
+
+impl From<HoldRow> for models::HoldingReservation {
+    fn from(row: HoldRow) -> Self {
+        models::Hold {
+            id: Uuid::parse_str(&row.id).expect("Invalid UUID in database"),
+            event_id: Uuid::parse_str(&row.event_id).expect("Invalid UUID in database"),
+            spot_count: row.spot_count,
+            status: models::Holding {
+                hold_token: row.hold_token,
+                created_at: row.created_at,
+                expires_at: row.expires_at,
+            },
+        }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct EmailLogRow {
+    recipient: String,
+    kind: String,
+    status: String,
+    error: Option<String>,
+    at: OffsetDateTime,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct TokenRecordRow {
+    token: String,
+    status: String,
+    label: Option<String>,
+    created_at: OffsetDateTime,
+    used_at: Option<OffsetDateTime>,
+    reservation_id: String,
+    reservation_status: String,
+    reservation_cancelled_at: Option<OffsetDateTime>,
+    event_id: String,
+}
+
+impl From<TokenRecordRow> for models::TokenRecord {
+    fn from(row: TokenRecordRow) -> Self {
+        models::TokenRecord {
+            token: row.token,
+            status: row.status,
+            label: row.label,
+            created_at: row.created_at,
+            used_at: row.used_at,
+            reservation_id: Uuid::parse_str(&row.reservation_id).expect("Invalid UUID in database"),
+            reservation_status: row.reservation_status,
+            reservation_cancelled_at: row.reservation_cancelled_at,
+            event_id: Uuid::parse_str(&row.event_id).expect("Invalid UUID in database"),
+        }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct WalletTokenRow {
+    token: String,
+    label: Option<String>,
+    reservation_id: String,
+    event_id: String,
+    event_name: String,
+    event_start_time: OffsetDateTime,
+    event_end_time: OffsetDateTime,
+    event_location: Option<String>,
+}
+
+impl From<WalletTokenRow> for models::WalletToken {
+    fn from(row: WalletTokenRow) -> Self {
+        models::WalletToken {
+            token: row.token,
+            label: row.label,
+            reservation_id: Uuid::parse_str(&row.reservation_id).expect("Invalid UUID in database"),
+            event_id: Uuid::parse_str(&row.event_id).expect("Invalid UUID in database"),
+            event_name: row.event_name,
+            event_start_time: row.event_start_time,
+            event_end_time: row.event_end_time,
+            event_location: row.event_location,
+        }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct QrSheetTicketRow {
+    token: String,
+    label: Option<String>,
+    short_code: Option<String>,
+    user_name: String,
+    party_name: Option<String>,
+}
+
+impl From<QrSheetTicketRow> for models::QrSheetTicket {
+    fn from(row: QrSheetTicketRow) -> Self {
+        models::QrSheetTicket {
+            token: row.token,
+            // The party the reservation is for is who turns up at the door
+            guest_name: row.party_name.unwrap_or(row.user_name),
+            label: row.label,
+            short_code: row.short_code,
+        }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct ReservationSearchRow {
     id: String,
+    user_email: String,
+    user_name: String,
+    status: String,
+    spot_count: u32,
+    event_id: String,
+    event_name: String,
+    created_at: OffsetDateTime,
+}
+
+impl From<ReservationSearchRow> for models::ReservationSearchResult {
+    fn from(row: ReservationSearchRow) -> Self {
+        models::ReservationSearchResult {
+            reservation_id: Uuid::parse_str(&row.id).expect("Invalid UUID in database"),
+            user_email: row.user_email,
+            user_name: row.user_name,
+            status: row.status,
+            spot_count: row.spot_count,
+            event_id: Uuid::parse_str(&row.event_id).expect("Invalid UUID in database"),
+            event_name: row.event_name,
+            created_at: row.created_at,
+        }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct EventQuestionRow {
+    key: String,
+    prompt: String,
+    required: bool,
+}
+
+impl From<EventQuestionRow> for models::EventQuestion {
+    fn from(row: EventQuestionRow) -> Self {
+        models::EventQuestion {
+            key: row.key,
+            prompt: row.prompt,
+            required: row.required,
+        }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct ReservationAnswersRow {
+    id: String,
+    user_name: String,
+    user_email: String,
+    answers: String,
+}
+
+impl From<ReservationAnswersRow> for models::ReservationAnswers {
+    fn from(row: ReservationAnswersRow) -> Self {
+        models::ReservationAnswers {
+            reservation_id: Uuid::parse_str(&row.id).expect("Invalid UUID in database"),
+            user_name: row.user_name,
+            user_email: row.user_email,
+            answers: serde_json::from_str(&row.answers).expect("Invalid answers in database"),
+        }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct RosterRow {
+    id: String,
+    user_name: String,
+    user_email: String,
+    party_name: Option<String>,
+    spot_count: u32,
+    status: String,
+}
+
+impl From<RosterRow> for models::RosterEntry {
+    fn from(row: RosterRow) -> Self {
+        models::RosterEntry {
+            reservation_id: Uuid::parse_str(&row.id).expect("Invalid UUID in database"),
+            user_name: row.user_name,
+            user_email: row.user_email,
+            party_name: row.party_name,
+            spot_count: row.spot_count,
+            confirmed: row.status == "confirmed",
+        }
+    }
+}
+
+impl From<EmailLogRow> for models::EmailLogEntry {
+    fn from(row: EmailLogRow) -> Self {
+        models::EmailLogEntry {
+            to: row.recipient,
+            kind: row.kind,
+            status: row.status,
+            error: row.error,
+            at: row.at,
+        }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct ReservationBucketRow {
+    bucket_start: String,
+    spots: u32,
+}
+
+/// Granularity for grouping reservations over time
+#[derive(Debug, Clone, Copy)]
+pub enum TimelineBucket {
+    Hour,
+    Day,
+}
+
+impl TimelineBucket {
+    fn strftime_format(&self) -> &'static str {
+        match self {
+            TimelineBucket::Hour => "%Y-%m-%dT%H:00:00Z",
+            TimelineBucket::Day => "%Y-%m-%dT00:00:00Z",
+        }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct ReservationTokenRow {
     reservation_id: String,
     token: String,
+    label: Option<String>,
+    short_code: Option<String>,
     status: String,
     created_at: OffsetDateTime,
     used_at: Option<OffsetDateTime>,
@@ -100,47 +541,69 @@ impl From<String> for models::VerificationToken {
 }
 
 impl ReservationTokenRow {
-    fn to_active_reservation_token(self) -> models::ActiveReservationToken {
+    fn into_inactive_reservation_token(self) -> models::InactiveReservationToken {
+        models::InactiveReservationToken {
+            token: self.token,
+            reservation_id: Uuid::parse_str(&self.reservation_id).expect("Invalid UUID in database"),
+            label: self.label,
+            short_code: self.short_code,
+            created_at: self.created_at,
+            status: models::Inactive,
+        }
+    }
+
+    fn into_active_reservation_token(self) -> models::ActiveReservationToken {
         models::ActiveReservationToken {
             token: self.token,
             reservation_id: Uuid::parse_str(&self.reservation_id).expect("Invalid UUID in database"),
+            label: self.label,
+            short_code: self.short_code,
             created_at: self.created_at,
             status: models::Active,
         }
     }
 
-    fn to_used_reservation_token(self) -> models::UsedReservationToken {
+    fn into_used_reservation_token(self) -> models::UsedReservationToken {
         models::UsedReservationToken {
             token: self.token,
             reservation_id: Uuid::parse_str(&self.reservation_id).expect("Invalid UUID in database"),
+            label: self.label,
+            short_code: self.short_code,
             created_at: self.created_at,
-            status: models::Used,
+            status: models::Used {
+                used_at: self.used_at.expect("Used token without used_at in database"),
+            },
         }
     }   
 
-    fn to_expired_reservation_token(self) -> models::ExpiredReservationToken {
+    fn into_expired_reservation_token(self) -> models::ExpiredReservationToken {
         models::ExpiredReservationToken {
             token: self.token,
             reservation_id: Uuid::parse_str(&self.reservation_id).expect("Invalid UUID in database"),
+            label: self.label,
+            short_code: self.short_code,
             created_at: self.created_at,
             status: models::Expired,
         }
     }
 
-    fn to_any_reservation_token(self) -> models::AnyReservationToken {
+    fn into_any_reservation_token(self) -> models::AnyReservationToken {
         match self.status.as_str() {
+            "inactive" => {
+                models::AnyReservationToken::Inactive(self.into_inactive_reservation_token())
+            }
             "active" => {
-                models::AnyReservationToken::Active(self.to_active_reservation_token())
+                models::AnyReservationToken::Active(self.into_active_reservation_token())
             }
             "used" => {
-                models::AnyReservationToken::Used(self.to_used_reservation_token())
+                models::AnyReservationToken::Used(self.into_used_reservation_token())
             }
             "expired" => {
-                models::AnyReservationToken::Expired(self.to_expired_reservation_token())
+                models::AnyReservationToken::Expired(self.into_expired_reservation_token())
             }
             _ => {
                 // Handle unknown status by treating as expired
-                models::AnyReservationToken::Expired(self.to_expired_reservation_token())
+                models::AnyReservationToken::Expired(self.into_expired_reservation_token())
             }
         }
     }
@@ -155,7 +618,7 @@ impl ReservationRow {
         }
     }
 
-    async fn to_pending_reservation(self) -> Result<models::PendingReservation, DatabaseError> {
+    async fn into_pending_reservation(self) -> Result<models::PendingReservation, DatabaseError> {
 
         Ok(models::Reservation {
             id: Uuid::parse_str(&self.id).expect("Invalid UUID in database"),
@@ -163,6 +626,9 @@ impl ReservationRow {
             user_name: self.user_name,
             user_email: self.user_email,
             spot_count: self.spot_count,
+            spot_labels: self.spot_labels.map(|labels| serde_json::from_str(&labels).expect("Invalid spot labels in database")),
+            answers: self.answers.map(|answers| serde_json::from_str(&answers).expect("Invalid answers in database")),
+            party_name: self.party_name,
             verification_token: self.verification_token.into(),
             status: models::Pending {
                 created_at: self.created_at,
@@ -171,7 +637,7 @@ impl ReservationRow {
         })
     }
 
-    async fn to_confirmed_reservation(self, db: &Database) -> Result<models::ConfirmedReservation, DatabaseError> {
+    async fn into_confirmed_reservation(self, db: &Database) -> Result<models::ConfirmedReservation, DatabaseError> {
         let reservation_tokens = self.get_reservation_tokens(db).await?;
         
         Ok(models::Reservation {
@@ -180,32 +646,263 @@ impl ReservationRow {
             user_name: self.user_name,
             user_email: self.user_email,
             spot_count: self.spot_count,
+            spot_labels: self.spot_labels.map(|labels| serde_json::from_str(&labels).expect("Invalid spot labels in database")),
+            answers: self.answers.map(|answers| serde_json::from_str(&answers).expect("Invalid answers in database")),
+            party_name: self.party_name,
             verification_token: self.verification_token.into(),
             status: models::Confirmed { 
                 created_at: self.created_at,
                 updated_at: self.updated_at,
                 verified_at: self.verified_at.unwrap(), 
                 reservation_tokens,
+                cancellation_token: self.cancellation_token.expect("Confirmed reservation without cancellation_token"),
             },
         })
     }
+
+    fn into_cancelled_reservation(self) -> models::CancelledReservation {
+        models::Reservation {
+            id: Uuid::parse_str(&self.id).expect("Invalid UUID in database"),
+            event_id: Uuid::parse_str(&self.event_id).expect("Invalid UUID in database"),
+            user_name: self.user_name,
+            user_email: self.user_email,
+            spot_count: self.spot_count,
+            spot_labels: self.spot_labels.map(|labels| serde_json::from_str(&labels).expect("Invalid spot labels in database")),
+            answers: self.answers.map(|answers| serde_json::from_str(&answers).expect("Invalid answers in database")),
+            party_name: self.party_name,
+            verification_token: self.verification_token.into(),
+            status: models::ReservationCancelled {
+                created_at: self.created_at,
+                verified_at: self.verified_at,
+                cancelled_at: self.cancelled_at.expect("Cancelled reservation without cancelled_at"),
+            },
+        }
+    }
+}
+
+/// How many times a colliding reservation token is regenerated before giving up
+const TOKEN_INSERT_ATTEMPTS: usize = 3;
+
+/// How many times a colliding event slug is regenerated before giving up
+const SLUG_INSERT_ATTEMPTS: usize = 3;
+
+/// How many short codes are drawn before giving up on finding one unused within an event
+const SHORT_CODE_ATTEMPTS: usize = 10;
+
+/// Most reservations a support email search returns; narrow the fragment to see past these
+const RESERVATION_SEARCH_LIMIT: i64 = 50;
+
+/// Escape `%`, `_` and the escape character itself so a LIKE pattern (with `ESCAPE '\'`) matches them literally
+fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+/// Insert a new open event, returning its generated id; a colliding slug gets a fresh random suffix
+async fn insert_event(conn: &mut SqliteConnection, new_event: &models::NewEvent) -> Result<Uuid, DatabaseError> {
+    let event_id = Uuid::new_v4();
+
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        let result = sqlx::query(
+            r#"
+            INSERT INTO events (id, slug, name, description, start_time, end_time, capacity, location, max_reservations, max_spots_per_reservation, min_spots_per_reservation, visibility, reservations_open_at, reservations_close_at, skip_verification, require_name_match, token_rotation_minutes, series_id, status)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, 'open')
+            "#
+        )
+        .bind(event_id.to_string())
+        .bind(new_event.generate_slug())
+        .bind(&new_event.name)
+        .bind(&new_event.description)
+        .bind(new_event.start_time)
+        .bind(new_event.end_time)
+        .bind(new_event.capacity)
+        .bind(&new_event.location)
+        .bind(new_event.max_reservations)
+        .bind(new_event.max_spots_per_reservation)
+        .bind(new_event.min_spots_per_reservation)
+        .bind(new_event.visibility.as_str())
+        .bind(new_event.reservations_open_at)
+        .bind(new_event.reservations_close_at)
+        .bind(new_event.skip_verification)
+        .bind(new_event.require_name_match)
+        .bind(new_event.token_rotation_minutes)
+        .bind(new_event.series_id.map(|id| id.to_string()))
+        .execute(&mut *conn)
+        .await;
+
+        match result {
+            Ok(_) => return Ok(event_id),
+            Err(sqlx::Error::Database(db_err)) if db_err.is_unique_violation() && attempts < SLUG_INSERT_ATTEMPTS => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+/// Expire every still-active token belonging to an event's reservations
+async fn expire_active_tokens(conn: &mut SqliteConnection, event_id: &Uuid) -> Result<u64, DatabaseError> {
+    let result = sqlx::query(
+        r#"
+        UPDATE reservation_tokens SET status = 'expired'
+        WHERE status = 'active'
+          AND reservation_id IN (SELECT id FROM reservations WHERE event_id = ?)
+        "#
+    )
+    .bind(event_id.to_string())
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Cancel a reservation that is still in `from_status`, expiring any active or pre-issued tokens so they
+/// can never scan, and recording the cancellation in the audit trail. Returns when it was cancelled; `None` when the
+/// reservation has already moved on.
+async fn cancel_reservation(conn: &mut SqliteConnection, reservation_id: &Uuid, from_status: &str) -> Result<Option<OffsetDateTime>, DatabaseError> {
+    // Stored with second precision, so report exactly what is stored
+    let cancelled_at = OffsetDateTime::from_unix_timestamp(OffsetDateTime::now_utc().unix_timestamp())
+        .expect("Current time is a valid timestamp");
+
+    let result = sqlx::query("UPDATE reservations SET status = 'cancelled', cancelled_at = ? WHERE id = ? AND status = ?")
+        .bind(cancelled_at.unix_timestamp())
+        .bind(reservation_id.to_string())
+        .bind(from_status)
+        .execute(&mut *conn)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Ok(None);
+    }
+
+    // The audit entry is stamped from the column so the two always agree
+    sqlx::query(
+        "INSERT INTO reservation_audit (reservation_id, action, previous_spot_count, at) SELECT id, 'cancel', spot_count, cancelled_at FROM reservations WHERE id = ?"
+    )
+    .bind(reservation_id.to_string())
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        "UPDATE reservation_tokens SET status = 'expired' WHERE reservation_id = ? AND status IN ('active', 'inactive')"
+    )
+    .bind(reservation_id.to_string())
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(Some(cancelled_at))
+}
+
+/// Pick a short code no other token for the event has. The caller's transaction has already
+/// written, so it holds SQLite's write lock and no other writer can take the code in between.
+async fn unused_short_code(tx: &mut Transaction<'_, Sqlite>, event_id: &str) -> Result<String, DatabaseError> {
+    for _ in 0..SHORT_CODE_ATTEMPTS {
+        let code = models::generate_short_code();
+        let taken: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM reservation_tokens t JOIN reservations r ON r.id = t.reservation_id WHERE r.event_id = ? AND t.short_code = ?)"
+        )
+        .bind(event_id)
+        .bind(&code)
+        .fetch_one(&mut **tx)
+        .await?;
+        if !taken {
+            return Ok(code);
+        }
+    }
+
+    Err(DatabaseError::ShortCodesExhausted)
+}
+
+/// Insert newly issued reservation tokens, regenerating any token whose value is already taken
+/// and giving each a short code unique within the reservation's event
+async fn insert_reservation_tokens(
+    tx: &mut Transaction<'_, Sqlite>,
+    reservation_id: &Uuid,
+    tokens: &mut [models::AnyReservationToken],
+) -> Result<(), DatabaseError> {
+    let event_id: String = sqlx::query_scalar("SELECT event_id FROM reservations WHERE id = ?")
+        .bind(reservation_id.to_string())
+        .fetch_one(&mut **tx)
+        .await?;
+
+    for token in tokens.iter_mut() {
+        token.set_short_code(unused_short_code(tx, &event_id).await?);
+
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            let result = sqlx::query(
+                r#"
+                INSERT INTO reservation_tokens (id, reservation_id, token, label, short_code, status)
+                VALUES (?, ?, ?, ?, ?, ?)
+                "#
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(reservation_id.to_string())
+            .bind(token.token())
+            .bind(token.label())
+            .bind(token.short_code())
+            .bind(if token.is_inactive() { "inactive" } else { "active" })
+            .execute(&mut **tx)
+            .await;
+
+            match result {
+                Ok(_) => break,
+                Err(sqlx::Error::Database(db_err)) if db_err.is_unique_violation() && attempts < TOKEN_INSERT_ATTEMPTS => {
+                    match token {
+                        models::AnyReservationToken::Active(active) => *token = models::AnyReservationToken::Active(active.regenerate()),
+                        models::AnyReservationToken::Inactive(inactive) => *token = models::AnyReservationToken::Inactive(inactive.regenerate()),
+                        _ => {}
+                    }
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    Ok(())
 }
 
+/// How many times a write that found the database locked is attempted before giving up
+const BUSY_RETRY_ATTEMPTS: u32 = 4;
+
+/// Wait before retrying a locked write; doubles after each attempt
+const BUSY_RETRY_BACKOFF: std::time::Duration = std::time::Duration::from_millis(25);
+
+/// Whether an error is SQLite reporting the database (`SQLITE_BUSY`) or a table (`SQLITE_LOCKED`) as locked
+fn is_busy(error: &DatabaseError) -> bool {
+    let DatabaseError::SqlxError(sqlx::Error::Database(db_err)) = error else {
+        return false;
+    };
+
+    // Extended result codes keep the primary code in the low byte
+    db_err.code()
+        .and_then(|code| code.parse::<i32>().ok())
+        .is_some_and(|code| matches!(code & 0xff, 5 | 6))
+}
 
-// Helper function to convert from ReservationRow to appropriate type
-pub async fn reservation_from_row(row: ReservationRow, db: &Database) -> Result<Box<dyn std::any::Any>, DatabaseError> {
-    match (row.status.as_str(), row.verified_at.is_some()) {
-        ("pending", _) => {
-            let pending = row.to_pending_reservation().await?;
-            Ok(Box::new(pending))
-        },
-        ("confirmed", true) => {
-            let confirmed = row.to_confirmed_reservation(db).await?;
-            Ok(Box::new(confirmed))
-        },
-        _ => {
-            let pending = row.to_pending_reservation().await?;
-            Ok(Box::new(pending)) // Default to pending
+/// Run a write, retrying with a short backoff while SQLite reports the database as locked. Even with
+/// a busy timeout, contended writes can fail this way; a lock that outlasts every attempt is `Busy`.
+async fn retry_when_busy<T, F, Fut>(mut write: F) -> Result<T, DatabaseError>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<T, DatabaseError>>,
+{
+    let mut backoff = BUSY_RETRY_BACKOFF;
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        match write().await {
+            Err(e) if is_busy(&e) => {
+                if attempts >= BUSY_RETRY_ATTEMPTS {
+                    eprintln!("Giving up on write after {} attempts: {}", attempts, e);
+                    return Err(DatabaseError::Busy);
+                }
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+            }
+            result => return result,
         }
     }
 }
@@ -220,7 +917,12 @@ impl Database {
         let database_url = env::var("DATABASE_URL")
             .unwrap_or_else(|_| "sqlite:quick-res.db".to_string());
         
-        let pool = SqlitePool::connect(&database_url).await?;
+        Self::connect(&database_url).await
+    }
+
+    /// Create a new database connection pool for the given URL
+    pub async fn connect(database_url: &str) -> Result<Self, DatabaseError> {
+        let pool = SqlitePool::connect(database_url).await?;
         
         // Ensure you have run the necessary SQL migrations before launching the application.
         // You can use `sqlx migrate run` or initialize migrations during the application start-up using `sqlx::migrate!()` macro.
@@ -230,7 +932,7 @@ impl Database {
 
     pub async fn get_open_event_by_id(&self, event_id: &Uuid) -> Result<models::OpenEvent, DatabaseError> {
         let event = sqlx::query_as::<_, EventRow>(
-"SELECT id, name, description, start_time, end_time, capacity, location, status, created_at, updated_at FROM events WHERE id = ? AND status = 'open'"
+"SELECT id, slug, name, description, start_time, end_time, capacity, location, max_reservations, max_spots_per_reservation, min_spots_per_reservation, visibility, reservations_open_at, reservations_close_at, skip_verification, require_name_match, token_rotation_minutes, series_id, sales_closed, status, cancelled_at, created_at, updated_at FROM events WHERE id = ? AND status = 'open'"
         )
         .bind(event_id.to_string())
         .fetch_optional(&self.pool)
@@ -240,45 +942,300 @@ impl Database {
         Ok(event.into())
     }
 
-    pub async fn get_all_open_events(&self) -> Result<Vec<models::OpenEvent>, DatabaseError> {
-        let events = sqlx::query_as::<_, EventRow>(
-"SELECT id, name, description, start_time, end_time, capacity, location, status, created_at, updated_at FROM events WHERE status = 'open' ORDER BY start_time ASC"
+    /// Look up an open event by its friendly slug
+    pub async fn get_open_event_by_slug(&self, slug: &str) -> Result<models::OpenEvent, DatabaseError> {
+        let event = sqlx::query_as::<_, EventRow>(
+"SELECT id, slug, name, description, start_time, end_time, capacity, location, max_reservations, max_spots_per_reservation, min_spots_per_reservation, visibility, reservations_open_at, reservations_close_at, skip_verification, require_name_match, token_rotation_minutes, series_id, sales_closed, status, cancelled_at, created_at, updated_at FROM events WHERE slug = ? AND status = 'open'"
         )
-        .fetch_all(&self.pool)
-        .await?;
+        .bind(slug)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(DatabaseError::EventNotFound)?;
 
-        Ok(events.into_iter().map(|e| e.into()).collect())
+        Ok(event.into())
     }
 
-    pub async fn insert_reservation(
-        &self,
-        creating_reservation: models::CreatingReservation,
-    ) -> Result<models::PendingReservation, DatabaseError> {
-        // Insert the reservation (timestamps handled by database)
-        sqlx::query(
-            r#"
-            INSERT INTO reservations (id, event_id, user_name, user_email, spot_count,   status, verification_token, verified_at)
-            VALUES                   ( ?,        ?,         ?,          ?,          ?, 'pending',          ?,        NULL)
-            "#
+    /// Look up an event whatever its status (open, full or finished)
+    pub async fn get_event_by_id(&self, event_id: &Uuid) -> Result<models::AnyEvent, DatabaseError> {
+        let event = sqlx::query_as::<_, EventRow>(
+"SELECT id, slug, name, description, start_time, end_time, capacity, location, max_reservations, max_spots_per_reservation, min_spots_per_reservation, visibility, reservations_open_at, reservations_close_at, skip_verification, require_name_match, token_rotation_minutes, series_id, sales_closed, status, cancelled_at, created_at, updated_at FROM events WHERE id = ?"
         )
-        .bind(creating_reservation.id.to_string())
-        .bind(creating_reservation.event_id.to_string())
-        .bind(&creating_reservation.user_name)
-        .bind(&creating_reservation.user_email)
-        .bind(creating_reservation.spot_count)
-        .bind(creating_reservation.verification_token.0)
-        .execute(&self.pool)
-        .await?;
+        .bind(event_id.to_string())
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(DatabaseError::EventNotFound)?;
 
-        // TODO handle duplicate email error and surface to UI
+        Ok(event.into())
+    }
+
+    pub async fn get_event_status(&self, event_id: &Uuid) -> Result<models::EventStatus, DatabaseError> {
+        Ok(self.get_event_by_id(event_id).await?.status())
+    }
+
+    /// Stop or resume sales for an event, independently of its status and capacity
+    pub async fn set_event_sales_closed(&self, event_id: &Uuid, sales_closed: bool) -> Result<(), DatabaseError> {
+        let result = sqlx::query("UPDATE events SET sales_closed = ? WHERE id = ?")
+            .bind(sales_closed)
+            .bind(event_id.to_string())
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(DatabaseError::EventNotFound);
+        }
+
+        Ok(())
+    }
+
+    /// Persist a manual status change; callers check the transition with `EventStatus::can_transition_to` first
+    pub async fn set_event_status(&self, event_id: &Uuid, status: models::EventStatus) -> Result<(), DatabaseError> {
+        let (db_status, cancelled_at) = match status {
+            models::EventStatus::Open => ("open", None),
+            models::EventStatus::Full => ("full", None),
+            models::EventStatus::Finished => ("closed", None),
+            models::EventStatus::Cancelled => ("closed", Some(OffsetDateTime::now_utc())),
+        };
+
+        let result = sqlx::query("UPDATE events SET status = ?, cancelled_at = ? WHERE id = ?")
+            .bind(db_status)
+            .bind(cancelled_at.map(|at| at.unix_timestamp()))
+            .bind(event_id.to_string())
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(DatabaseError::EventNotFound);
+        }
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(test), expect(dead_code))]
+    pub async fn get_all_open_events(&self) -> Result<Vec<models::OpenEvent>, DatabaseError> {
+        let events = sqlx::query_as::<_, EventRow>(
+"SELECT id, slug, name, description, start_time, end_time, capacity, location, max_reservations, max_spots_per_reservation, min_spots_per_reservation, visibility, reservations_open_at, reservations_close_at, skip_verification, require_name_match, token_rotation_minutes, series_id, sales_closed, status, cancelled_at, created_at, updated_at FROM events WHERE status = 'open' AND visibility = 'public' ORDER BY start_time ASC"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(events.into_iter().map(|e| e.into()).collect())
+    }
+
+    /// Every event whatever its status or visibility, with confirmed reservation totals, optionally limited to one status
+    pub async fn get_all_events(&self, status_filter: Option<models::EventStatus>) -> Result<Vec<models::AdminEventSummary>, DatabaseError> {
+        let mut query = QueryBuilder::<Sqlite>::new(
+            r#"
+            SELECT e.id, e.slug, e.name, e.description, e.start_time, e.end_time, e.capacity, e.location, e.max_reservations, e.max_spots_per_reservation, e.min_spots_per_reservation, e.visibility, e.reservations_open_at, e.reservations_close_at, e.skip_verification, e.require_name_match, e.token_rotation_minutes, e.series_id, e.sales_closed, e.status, e.cancelled_at, e.created_at, e.updated_at,
+                   COUNT(r.id) AS reservation_count, COALESCE(SUM(r.spot_count), 0) AS reserved_spots
+            FROM events e
+            LEFT JOIN reservations r ON r.event_id = e.id AND r.status = 'confirmed'
+            "#
+        );
+
+        // Finished and cancelled events share the 'closed' status and differ by cancelled_at
+        match status_filter {
+            Some(models::EventStatus::Open) => { query.push(" WHERE e.status = 'open'"); }
+            Some(models::EventStatus::Full) => { query.push(" WHERE e.status = 'full'"); }
+            Some(models::EventStatus::Finished) => { query.push(" WHERE e.status = 'closed' AND e.cancelled_at IS NULL"); }
+            Some(models::EventStatus::Cancelled) => { query.push(" WHERE e.status = 'closed' AND e.cancelled_at IS NOT NULL"); }
+            None => {}
+        }
+
+        query.push(" GROUP BY e.id ORDER BY e.start_time ASC");
+
+        let rows = query.build_query_as::<AdminEventRow>()
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// Events starting on a given UTC day, whatever their status, with confirmed reservation totals
+    pub async fn get_events_with_counts_for_day(&self, date: time::Date) -> Result<Vec<models::AdminEventSummary>, DatabaseError> {
+        let day_start = date.midnight().assume_utc();
+        let day_end = day_start + time::Duration::days(1);
+
+        let rows = sqlx::query_as::<_, AdminEventRow>(
+            r#"
+            SELECT e.id, e.slug, e.name, e.description, e.start_time, e.end_time, e.capacity, e.location, e.max_reservations, e.max_spots_per_reservation, e.min_spots_per_reservation, e.visibility, e.reservations_open_at, e.reservations_close_at, e.skip_verification, e.require_name_match, e.token_rotation_minutes, e.series_id, e.sales_closed, e.status, e.cancelled_at, e.created_at, e.updated_at,
+                   COUNT(r.id) AS reservation_count, COALESCE(SUM(r.spot_count), 0) AS reserved_spots
+            FROM events e
+            LEFT JOIN reservations r ON r.event_id = e.id AND r.status = 'confirmed'
+            WHERE unixepoch(e.start_time, 'auto') >= ? AND unixepoch(e.start_time, 'auto') < ?
+            GROUP BY e.id ORDER BY e.start_time ASC
+            "#
+        )
+        .bind(day_start.unix_timestamp())
+        .bind(day_end.unix_timestamp())
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// Search open, public events; each provided filter narrows the results further
+    /// `only_available` uses the same arithmetic as `reserve`: confirmed spots, pending ones when
+    /// `include_pending` is set, and spots held at `now`
+    pub async fn search_events_filtered(&self, search: &models::EventSearch, include_pending: bool, now: OffsetDateTime) -> Result<Vec<models::OpenEvent>, DatabaseError> {
+        let mut query = QueryBuilder::<Sqlite>::new(
+            "SELECT id, slug, name, description, start_time, end_time, capacity, location, max_reservations, max_spots_per_reservation, min_spots_per_reservation, visibility, reservations_open_at, reservations_close_at, skip_verification, require_name_match, token_rotation_minutes, series_id, sales_closed, status, cancelled_at, created_at, updated_at FROM events WHERE status = 'open' AND visibility = 'public'"
+        );
+
+        if let Some(name_query) = &search.name_query {
+            query.push(" AND name LIKE ").push_bind(format!("%{}%", escape_like(name_query))).push(" ESCAPE '\\'");
+        }
+        // Start times may be stored as RFC3339 text or Unix seconds, so normalise before comparing
+        if let Some(from) = search.from {
+            query.push(" AND unixepoch(start_time, 'auto') >= ").push_bind(from.unix_timestamp());
+        }
+        if let Some(to) = search.to {
+            query.push(" AND unixepoch(start_time, 'auto') <= ").push_bind(to.unix_timestamp());
+        }
+        if let Some(location) = &search.location {
+            query.push(" AND location LIKE ").push_bind(format!("%{}%", escape_like(location))).push(" ESCAPE '\\'");
+        }
+        if search.only_available {
+            query.push(" AND capacity > (SELECT COALESCE(SUM(spot_count), 0) FROM reservations r WHERE r.event_id = events.id AND (r.status = 'confirmed' OR (")
+                .push_bind(include_pending)
+                .push(" AND r.status = 'pending'))) + (SELECT COALESCE(SUM(spot_count), 0) FROM reservation_holds h WHERE h.event_id = events.id AND h.expires_at > ")
+                .push_bind(now.unix_timestamp())
+                .push(")");
+        }
+        query.push(" ORDER BY start_time ASC");
+
+        let events = query
+            .build_query_as::<EventRow>()
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(events.into_iter().map(|e| e.into()).collect())
+    }
+
+    #[tracing::instrument(skip_all, fields(reservation_id = %creating_reservation.id, event_id = %creating_reservation.event_id))]
+    pub async fn insert_reservation(
+        &self,
+        creating_reservation: models::CreatingReservation,
+    ) -> Result<models::PendingReservation, DatabaseError> {
+        retry_when_busy(|| self.try_insert_reservation(&creating_reservation)).await
+    }
+
+    async fn try_insert_reservation(
+        &self,
+        creating_reservation: &models::CreatingReservation,
+    ) -> Result<models::PendingReservation, DatabaseError> {
+        // Insert the reservation (timestamps handled by database). The email is stored normalized so the
+        // (event_id, user_email) unique constraint treats case variants as the same address.
+        sqlx::query(
+            r#"
+            INSERT INTO reservations (id, event_id, user_name, user_email, user_email_original, spot_count, spot_labels, answers, party_name,   status, verification_token, verified_at)
+            VALUES                   ( ?,        ?,         ?,          ?,                   ?,          ?,           ?,       ?,          ?, 'pending',          ?,        NULL)
+            "#
+        )
+        .bind(creating_reservation.id.to_string())
+        .bind(creating_reservation.event_id.to_string())
+        .bind(&creating_reservation.user_name)
+        .bind(crate::email::normalize_email(&creating_reservation.user_email))
+        .bind(&creating_reservation.user_email)
+        .bind(creating_reservation.spot_count)
+        .bind(creating_reservation.spot_labels.as_ref().map(|labels| serde_json::to_string(labels).expect("Failed to serialize spot labels")))
+        .bind(creating_reservation.answers.as_ref().map(|answers| answers.to_string()))
+        .bind(&creating_reservation.party_name)
+        .bind(&creating_reservation.verification_token.0)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db_err) if db_err.is_unique_violation() => DatabaseError::DuplicateReservation,
+            e => DatabaseError::SqlxError(e),
+        })?;
 
         // Fetch the inserted reservation
         self.get_pending_reservation_by_id(&creating_reservation.id).await
     }
 
-    pub async fn count_event_reservations(&self, event_id: &Uuid) -> Result<u32, DatabaseError> {
+    /// Sum of spots held by confirmed reservations for an event, plus pending ones when `include_pending` is set
+    pub async fn count_event_reservations(&self, event_id: &Uuid, include_pending: bool) -> Result<u32, DatabaseError> {
+        let count: u32 = sqlx::query_scalar(
+            "SELECT COALESCE(SUM(spot_count), 0) FROM reservations WHERE event_id = ? AND (status = 'confirmed' OR (? AND status = 'pending'))"
+        )
+        .bind(event_id.to_string())
+        .bind(include_pending)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count)
+    }
+
+    /// Spots currently set aside by unexpired holds for an event
+    pub async fn count_held_spots(&self, event_id: &Uuid, now: OffsetDateTime) -> Result<u32, DatabaseError> {
+        let count: u32 = sqlx::query_scalar(
+            "SELECT COALESCE(SUM(spot_count), 0) FROM reservation_holds WHERE event_id = ? AND expires_at > ?"
+        )
+        .bind(event_id.to_string())
+        .bind(now.unix_timestamp())
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count)
+    }
+
+    pub async fn insert_hold(&self, hold: models::HoldingReservation) -> Result<models::HoldingReservation, DatabaseError> {
+        // Timestamps are bound as Unix seconds so expiry comparisons stay numeric
+        sqlx::query(
+            r#"
+            INSERT INTO reservation_holds (id, event_id, spot_count, hold_token, created_at, expires_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#
+        )
+        .bind(hold.id.to_string())
+        .bind(hold.event_id.to_string())
+        .bind(hold.spot_count)
+        .bind(&hold.status.hold_token)
+        .bind(hold.status.created_at.unix_timestamp())
+        .bind(hold.status.expires_at.unix_timestamp())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(hold)
+    }
+
+    /// Look up a hold by its token, ignoring holds that have already lapsed
+    pub async fn get_active_hold_by_token(&self, hold_token: &str, now: OffsetDateTime) -> Result<models::HoldingReservation, DatabaseError> {
+        let row = sqlx::query_as::<_, HoldRow>(
+            "SELECT id, event_id, spot_count, hold_token, created_at, expires_at FROM reservation_holds WHERE hold_token = ? AND expires_at > ?"
+        )
+        .bind(hold_token)
+        .bind(now.unix_timestamp())
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(DatabaseError::HoldNotFound)?;
+
+        Ok(row.into())
+    }
+
+    /// Drop a hold once its reservation has been created
+    pub async fn release_hold(&self, hold: models::HoldingReservation) -> Result<(), DatabaseError> {
+        sqlx::query("DELETE FROM reservation_holds WHERE id = ?")
+            .bind(hold.id.to_string())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Remove lapsed holds, returning how many were cleared
+    pub async fn delete_expired_holds(&self, now: OffsetDateTime) -> Result<u64, DatabaseError> {
+        let result = sqlx::query("DELETE FROM reservation_holds WHERE expires_at <= ?")
+            .bind(now.unix_timestamp())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Number of pending or confirmed reservations (parties) for an event, regardless of spot count
+    pub async fn count_event_parties(&self, event_id: &Uuid) -> Result<u32, DatabaseError> {
         let count: u32 = sqlx::query_scalar(
-            "SELECT COUNT(*) FROM reservations WHERE event_id = ? AND status = 'confirmed'"
+            "SELECT COUNT(*) FROM reservations WHERE event_id = ? AND status IN ('pending', 'confirmed')"
         )
         .bind(event_id.to_string())
         .fetch_one(&self.pool)
@@ -287,53 +1244,282 @@ impl Database {
         Ok(count)
     }
 
+    /// Confirmed spots for an event grouped into hour or day buckets by verification time
+    pub async fn get_reservation_buckets(&self, event_id: &Uuid, bucket: TimelineBucket) -> Result<Vec<models::ReservationBucket>, DatabaseError> {
+        let rows = sqlx::query_as::<_, ReservationBucketRow>(
+            r#"
+            SELECT strftime(?, verified_at) AS bucket_start, SUM(spot_count) AS spots
+            FROM reservations
+            WHERE event_id = ? AND status = 'confirmed' AND verified_at IS NOT NULL
+            GROUP BY bucket_start
+            ORDER BY bucket_start ASC
+            "#
+        )
+        .bind(bucket.strftime_format())
+        .bind(event_id.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|row| models::ReservationBucket {
+            bucket_start: OffsetDateTime::parse(&row.bucket_start, &Rfc3339).expect("Invalid bucket timestamp from database"),
+            spots: row.spots,
+        }).collect())
+    }
+
+    /// Fill rate, busiest confirmation hour and average party size over an event's confirmed reservations
+    pub async fn get_event_analytics(&self, event_id: &Uuid) -> Result<models::EventAnalytics, DatabaseError> {
+        let (capacity, confirmed_spots, average_party_size): (u32, u32, Option<f64>) = sqlx::query_as(
+            r#"
+            SELECT e.capacity, COALESCE(SUM(r.spot_count), 0), AVG(r.spot_count)
+            FROM events e
+            LEFT JOIN reservations r ON r.event_id = e.id AND r.status = 'confirmed'
+            WHERE e.id = ?
+            GROUP BY e.id
+            "#
+        )
+        .bind(event_id.to_string())
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(DatabaseError::EventNotFound)?;
+
+        let peak_hour: Option<String> = sqlx::query_scalar(
+            r#"
+            SELECT strftime('%Y-%m-%dT%H:00:00Z', verified_at) AS hour
+            FROM reservations
+            WHERE event_id = ? AND status = 'confirmed' AND verified_at IS NOT NULL
+            GROUP BY hour
+            ORDER BY COUNT(*) DESC, hour ASC
+            LIMIT 1
+            "#
+        )
+        .bind(event_id.to_string())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(models::EventAnalytics {
+            event_id: *event_id,
+            capacity,
+            confirmed_spots,
+            fill_rate: confirmed_spots as f64 / capacity as f64,
+            peak_hour: peak_hour.map(|hour| OffsetDateTime::parse(&hour, &Rfc3339).expect("Invalid peak hour from database")),
+            average_party_size,
+        })
+    }
+
     pub async fn get_pending_reservation_by_id(&self, reservation_id: &Uuid) -> Result<models::PendingReservation, DatabaseError> {
         let row = sqlx::query_as::<_, ReservationRow>(
-        "SELECT id, event_id, user_name, user_email, spot_count, status, verification_token, created_at, updated_at, verified_at FROM reservations WHERE id = ? AND status = 'pending'"
+        "SELECT id, event_id, user_name, user_email, spot_count, spot_labels, answers, party_name, cancellation_token, refund_status, cancelled_at, status, verification_token, created_at, updated_at, verified_at FROM reservations WHERE id = ? AND status = 'pending'"
         )
         .bind(reservation_id.to_string())
         .fetch_optional(&self.pool)
         .await?
         .ok_or(DatabaseError::ReservationNotFound)?;
 
-        row.to_pending_reservation().await
+        row.into_pending_reservation().await
     }
 
     pub async fn get_confirmed_reservation_by_id(&self, reservation_id: &Uuid) -> Result<models::ConfirmedReservation, DatabaseError> {
         let row = sqlx::query_as::<_, ReservationRow>(
-        "SELECT id, event_id, user_name, user_email, spot_count, status, verification_token, created_at, updated_at, verified_at FROM reservations WHERE id = ? AND status = 'confirmed'"
+        "SELECT id, event_id, user_name, user_email, spot_count, spot_labels, answers, party_name, cancellation_token, refund_status, cancelled_at, status, verification_token, created_at, updated_at, verified_at FROM reservations WHERE id = ? AND status = 'confirmed'"
+        )
+        .bind(reservation_id.to_string())
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(DatabaseError::ReservationNotFound)?;
+
+        row.into_confirmed_reservation(self).await
+    }
+
+    pub async fn get_cancelled_reservation_by_id(&self, reservation_id: &Uuid) -> Result<models::CancelledReservation, DatabaseError> {
+        let row = sqlx::query_as::<_, ReservationRow>(
+            "SELECT id, event_id, user_name, user_email, spot_count, spot_labels, answers, party_name, cancellation_token, refund_status, cancelled_at, status, verification_token, created_at, updated_at, verified_at FROM reservations WHERE id = ? AND status = 'cancelled'"
+        )
+        .bind(reservation_id.to_string())
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(DatabaseError::ReservationNotFound)?;
+
+        Ok(row.into_cancelled_reservation())
+    }
+
+    /// A cancelled reservation by any token its holder was sent: one of its (now expired) reservation
+    /// tokens, or its verification token if it was cancelled before being confirmed
+    pub async fn get_cancelled_reservation_by_token(&self, token: &str) -> Result<models::CancelledReservation, DatabaseError> {
+        let row = sqlx::query_as::<_, ReservationRow>(
+            "SELECT id, event_id, user_name, user_email, spot_count, spot_labels, answers, party_name, cancellation_token, refund_status, cancelled_at, status, verification_token, created_at, updated_at, verified_at FROM reservations WHERE status = 'cancelled' AND (verification_token = ? OR id IN (SELECT reservation_id FROM reservation_tokens WHERE token = ?))"
+        )
+        .bind(token)
+        .bind(token)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(DatabaseError::ReservationNotFound)?;
+
+        Ok(row.into_cancelled_reservation())
+    }
+
+    /// Fetch a confirmed reservation together with its event in one query, whatever the event's status
+    pub async fn get_confirmed_reservation_with_event(&self, reservation_id: &Uuid) -> Result<(models::ConfirmedReservation, models::AnyEvent), DatabaseError> {
+        let row = sqlx::query_as::<_, ReservationWithEventRow>(
+            r#"
+            SELECT r.id, r.event_id, r.user_name, r.user_email, r.spot_count, r.spot_labels, r.answers, r.party_name, r.cancellation_token, r.refund_status, r.cancelled_at, r.status, r.verification_token, r.created_at, r.updated_at, r.verified_at,
+                   e.slug AS event_slug, e.name AS event_name, e.description AS event_description, e.start_time AS event_start_time, e.end_time AS event_end_time,
+                   e.capacity AS event_capacity, e.location AS event_location, e.max_reservations AS event_max_reservations,
+                   e.max_spots_per_reservation AS event_max_spots_per_reservation, e.min_spots_per_reservation AS event_min_spots_per_reservation, e.visibility AS event_visibility,
+                   e.reservations_open_at AS event_reservations_open_at, e.reservations_close_at AS event_reservations_close_at,
+                   e.skip_verification AS event_skip_verification, e.require_name_match AS event_require_name_match, e.token_rotation_minutes AS event_token_rotation_minutes, e.series_id AS event_series_id, e.sales_closed AS event_sales_closed,
+                   e.status AS event_status, e.cancelled_at AS event_cancelled_at, e.created_at AS event_created_at, e.updated_at AS event_updated_at
+            FROM reservations r
+            JOIN events e ON e.id = r.event_id
+            WHERE r.id = ? AND r.status = 'confirmed'
+            "#
         )
         .bind(reservation_id.to_string())
         .fetch_optional(&self.pool)
         .await?
         .ok_or(DatabaseError::ReservationNotFound)?;
 
-        row.to_confirmed_reservation(&self).await
+        let (reservation, event) = row.split();
+        Ok((reservation.into_confirmed_reservation(self).await?, event.into()))
+    }
+
+    /// Resolve an active reservation token to the reservation it belongs to
+    pub async fn get_reservation_id_by_active_token(&self, token: &str) -> Result<Uuid, DatabaseError> {
+        let reservation_id: String = sqlx::query_scalar(
+            "SELECT reservation_id FROM reservation_tokens WHERE token = ? AND status = 'active'"
+        )
+        .bind(token)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(DatabaseError::ReservationNotFound)?;
+
+        Ok(Uuid::parse_str(&reservation_id).expect("Invalid UUID in database"))
+    }
+
+    /// Resolve a cancellation token from a confirmation email to its confirmed reservation
+    pub async fn get_reservation_id_by_cancellation_token(&self, token: &str) -> Result<Uuid, DatabaseError> {
+        let reservation_id: String = sqlx::query_scalar(
+            "SELECT id FROM reservations WHERE cancellation_token = ? AND status = 'confirmed'"
+        )
+        .bind(token)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(DatabaseError::ReservationNotFound)?;
+
+        Ok(Uuid::parse_str(&reservation_id).expect("Invalid UUID in database"))
+    }
+
+    /// All pending reservations for an event, oldest first
+    pub async fn get_pending_reservations_for_event(&self, event_id: &Uuid) -> Result<Vec<models::PendingReservation>, DatabaseError> {
+        let rows = sqlx::query_as::<_, ReservationRow>(
+            "SELECT id, event_id, user_name, user_email, spot_count, spot_labels, answers, party_name, cancellation_token, refund_status, cancelled_at, status, verification_token, created_at, updated_at, verified_at FROM reservations WHERE event_id = ? AND status = 'pending' ORDER BY created_at, rowid"
+        )
+        .bind(event_id.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut reservations = Vec::with_capacity(rows.len());
+        for row in rows {
+            reservations.push(row.into_pending_reservation().await?);
+        }
+
+        Ok(reservations)
+    }
+
+    /// Pending reservations for an event that share an email (compared case-insensitively), oldest first within each group
+    pub async fn find_duplicate_pending(&self, event_id: &Uuid) -> Result<Vec<Vec<models::PendingReservation>>, DatabaseError> {
+        let rows = sqlx::query_as::<_, ReservationRow>(
+            r#"
+            SELECT id, event_id, user_name, user_email, spot_count, spot_labels, answers, party_name, cancellation_token, refund_status, cancelled_at, status, verification_token, created_at, updated_at, verified_at
+            FROM reservations
+            WHERE event_id = ? AND status = 'pending'
+              AND LOWER(user_email) IN (
+                  SELECT LOWER(user_email) FROM reservations
+                  WHERE event_id = ? AND status = 'pending'
+                  GROUP BY LOWER(user_email) HAVING COUNT(*) > 1
+              )
+            ORDER BY LOWER(user_email), created_at, rowid
+            "#
+        )
+        .bind(event_id.to_string())
+        .bind(event_id.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut groups: Vec<Vec<models::PendingReservation>> = Vec::new();
+        for row in rows {
+            let pending = row.into_pending_reservation().await?;
+            match groups.last_mut() {
+                Some(group) if group[0].user_email.eq_ignore_ascii_case(&pending.user_email) => group.push(pending),
+                _ => groups.push(vec![pending]),
+            }
+        }
+
+        Ok(groups)
+    }
+
+    /// Status of the reservation an email holds for an event, with when it was cancelled if it only has a cancelled one
+    pub async fn get_reservation_status_for_email(&self, event_id: &Uuid, user_email: &str) -> Result<Option<(String, Option<OffsetDateTime>)>, DatabaseError> {
+        // Confirmed beats pending beats cancelled; among cancelled ones, the latest cancellation
+        let status: Option<(String, Option<OffsetDateTime>)> = sqlx::query_as(
+            "SELECT status, cancelled_at FROM reservations WHERE event_id = ? AND user_email = ? ORDER BY status = 'confirmed' DESC, status = 'pending' DESC, cancelled_at DESC LIMIT 1"
+        )
+        .bind(event_id.to_string())
+        .bind(crate::email::normalize_email(user_email))
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(status)
+    }
+
+    /// Cancel a pending reservation, e.g. a duplicate
+    pub async fn cancel_pending_reservation(&self, pending: models::PendingReservation) -> Result<(), DatabaseError> {
+        let mut tx = self.pool.begin().await?;
+
+        if cancel_reservation(&mut tx, &pending.id, "pending").await?.is_none() {
+            return Err(DatabaseError::ReservationNotFound);
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    pub async fn get_pending_reservation_by_email(&self, event_id: &Uuid, user_email: &str) -> Result<models::PendingReservation, DatabaseError> {
+        let row = sqlx::query_as::<_, ReservationRow>(
+            "SELECT id, event_id, user_name, user_email, spot_count, spot_labels, answers, party_name, cancellation_token, refund_status, cancelled_at, status, verification_token, created_at, updated_at, verified_at FROM reservations WHERE event_id = ? AND user_email = ? AND status = 'pending' ORDER BY created_at, rowid"
+        )
+        .bind(event_id.to_string())
+        .bind(crate::email::normalize_email(user_email))
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(DatabaseError::ReservationNotFound)?;
+
+        row.into_pending_reservation().await
     }
 
     pub async fn get_pending_reservation_by_verification_token(&self, token: &str) -> Result<models::PendingReservation, DatabaseError> {
         println!("Getting pending reservation by verification token: {}", token);
         let row = sqlx::query_as::<_, ReservationRow>(
-            "SELECT id, event_id, user_name, user_email, spot_count, status, verification_token, created_at, updated_at, verified_at FROM reservations WHERE verification_token = ? AND status = 'pending'"
+            "SELECT id, event_id, user_name, user_email, spot_count, spot_labels, answers, party_name, cancellation_token, refund_status, cancelled_at, status, verification_token, created_at, updated_at, verified_at FROM reservations WHERE verification_token = ? AND status = 'pending'"
         )
         .bind(token)
         .fetch_optional(&self.pool)
         .await?
         .ok_or(DatabaseError::ReservationNotFound)?;
 
-        row.to_pending_reservation().await
+        row.into_pending_reservation().await
     }
 
     pub async fn get_confirmed_reservation_by_verification_token(&self, token: &str) -> Result<models::ConfirmedReservation, DatabaseError> {
         let row = sqlx::query_as::<_, ReservationRow>(
-            "SELECT id, event_id, user_name, user_email, spot_count, status, verification_token, created_at, updated_at, verified_at FROM reservations WHERE verification_token = ? AND status = 'confirmed'"
+            "SELECT id, event_id, user_name, user_email, spot_count, spot_labels, answers, party_name, cancellation_token, refund_status, cancelled_at, status, verification_token, created_at, updated_at, verified_at FROM reservations WHERE verification_token = ? AND status = 'confirmed'"
         )
         .bind(token)
         .fetch_optional(&self.pool)
         .await?
         .ok_or(DatabaseError::ReservationNotFound)?;
 
-        row.to_confirmed_reservation(&self).await
+        row.into_confirmed_reservation(self).await
     }
 
     pub async fn get_pending_reservation_by_reservation_token(&self, token: &str) -> Result<models::PendingReservation, DatabaseError> {
@@ -348,179 +1534,1032 @@ impl Database {
         .await?
         .ok_or(DatabaseError::ReservationNotFound)?;
 
-        // Then get the reservation
-        let row = sqlx::query_as::<_, ReservationRow>(
-            "SELECT id, event_id, user_name, user_email, status, verification_token, created_at, updated_at, verified_at FROM reservations WHERE id = ? AND status = 'pending'"
-        )
-        .bind(&reservation_id)
-        .fetch_optional(&self.pool)
-        .await?
-        .ok_or(DatabaseError::ReservationNotFound)?;
+        // Then get the reservation
+        let row = sqlx::query_as::<_, ReservationRow>(
+            "SELECT id, event_id, user_name, user_email, spot_count, spot_labels, answers, party_name, cancellation_token, refund_status, cancelled_at, status, verification_token, created_at, updated_at, verified_at FROM reservations WHERE id = ? AND status = 'pending'"
+        )
+        .bind(&reservation_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(DatabaseError::ReservationNotFound)?;
+
+        row.into_pending_reservation().await
+    }
+
+    async fn get_reservation_tokens_by_reservation_id(&self, reservation_id: &str) -> Result<Vec<models::AnyReservationToken>, DatabaseError> {
+        let token_rows = sqlx::query_as::<_, ReservationTokenRow>(
+            "SELECT id, reservation_id, token, label, short_code, status, created_at, used_at FROM reservation_tokens WHERE reservation_id = ?"
+        )
+        .bind(reservation_id)
+        .fetch_all(&self.pool)
+        .await?;
+    
+        Ok(token_rows.into_iter().map(ReservationTokenRow::into_any_reservation_token).collect())
+    }
+
+    /// Active tokens of an event's confirmed reservations, grouped by guest in name order
+    pub async fn get_qr_sheet_tickets(&self, event_id: &Uuid) -> Result<Vec<models::QrSheetTicket>, DatabaseError> {
+        let rows = sqlx::query_as::<_, QrSheetTicketRow>(
+            r#"
+            SELECT t.token, t.label, t.short_code, r.user_name, r.party_name
+            FROM reservation_tokens t
+            JOIN reservations r ON r.id = t.reservation_id
+            WHERE r.event_id = ? AND r.status = 'confirmed' AND t.status = 'active'
+            ORDER BY COALESCE(r.party_name, r.user_name) COLLATE NOCASE, r.created_at, t.rowid
+            "#
+        )
+        .bind(event_id.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// Get every reservation token issued for an event, optionally filtered by token status
+    pub async fn get_tokens_for_event(&self, event_id: &Uuid, status: Option<&str>) -> Result<Vec<models::AnyReservationToken>, DatabaseError> {
+        let token_rows = sqlx::query_as::<_, ReservationTokenRow>(
+            r#"
+            SELECT t.id, t.reservation_id, t.token, t.label, t.short_code, t.status, t.created_at, t.used_at
+            FROM reservation_tokens t
+            JOIN reservations r ON r.id = t.reservation_id
+            WHERE r.event_id = ? AND (? IS NULL OR t.status = ?)
+            ORDER BY t.created_at ASC, t.token ASC
+            "#
+        )
+        .bind(event_id.to_string())
+        .bind(status)
+        .bind(status)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(token_rows.into_iter().map(ReservationTokenRow::into_any_reservation_token).collect())
+    }
+
+    /// Expire every still-active token for an event (e.g. once it has finished), returning how many changed
+    pub async fn expire_active_tokens_for_event(&self, event_id: &Uuid) -> Result<u64, DatabaseError> {
+        let mut conn = self.pool.acquire().await?;
+        expire_active_tokens(&mut conn, event_id).await
+    }
+
+    /// Mark every open or full event whose end time has passed as finished, expiring its leftover active tokens
+    pub async fn finish_past_events(&self) -> Result<Vec<models::FinishedEvent>, DatabaseError> {
+        let mut tx = self.pool.begin().await?;
+
+        // End times may be stored as RFC3339 text or Unix seconds, so normalise before comparing
+        let rows = sqlx::query_as::<_, EventRow>(
+"SELECT id, slug, name, description, start_time, end_time, capacity, location, max_reservations, max_spots_per_reservation, min_spots_per_reservation, visibility, reservations_open_at, reservations_close_at, skip_verification, require_name_match, token_rotation_minutes, series_id, sales_closed, status, cancelled_at, created_at, updated_at FROM events WHERE status IN ('open', 'full') AND unixepoch(end_time, 'auto') < unixepoch()"
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut finished = Vec::with_capacity(rows.len());
+        for row in rows {
+            let event: models::FinishedEvent = match row.status.as_str() {
+                "open" => models::OpenEvent::from(row).finish(),
+                _ => row.into(),
+            };
+
+            // Finished events are stored with the schema's 'closed' status
+            sqlx::query("UPDATE events SET status = 'closed' WHERE id = ?")
+                .bind(event.id.to_string())
+                .execute(&mut *tx)
+                .await?;
+            expire_active_tokens(&mut tx, &event.id).await?;
+
+            finished.push(event);
+        }
+
+        tx.commit().await?;
+
+        Ok(finished)
+    }
+
+    /// Recompute an open or full event's confirmed spots and correct anything that has drifted from
+    /// them: the event's open/full status, and tokens whose state doesn't match their reservation's
+    pub async fn reconcile_event(&self, event_id: &Uuid) -> Result<models::EventReconciliation, DatabaseError> {
+        let mut tx = self.pool.begin().await?;
+
+        let (status, capacity): (String, u32) = sqlx::query_as("SELECT status, capacity FROM events WHERE id = ? AND status IN ('open', 'full')")
+            .bind(event_id.to_string())
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(DatabaseError::EventNotFound)?;
+        let status_before = if status == "full" { models::EventStatus::Full } else { models::EventStatus::Open };
+
+        let confirmed_spots: u32 = sqlx::query_scalar(
+            "SELECT COALESCE(SUM(spot_count), 0) FROM reservations WHERE event_id = ? AND status = 'confirmed'"
+        )
+        .bind(event_id.to_string())
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let status_after = models::EventReconciliation::expected_status(capacity, confirmed_spots);
+        if status_after != status_before {
+            sqlx::query("UPDATE events SET status = ? WHERE id = ?")
+                .bind(status_after.to_string())
+                .bind(event_id.to_string())
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        // Token states should follow the reservation: scannable only once confirmed, dead once cancelled
+        let relabel = |reservation_status: &'static str, from: &'static str, to: &'static str| {
+            sqlx::query(
+                "UPDATE reservation_tokens SET status = ? WHERE status = ? AND reservation_id IN (SELECT id FROM reservations WHERE event_id = ? AND status = ?)"
+            )
+            .bind(to)
+            .bind(from)
+            .bind(event_id.to_string())
+            .bind(reservation_status)
+        };
+        let tokens_expired = relabel("cancelled", "active", "expired").execute(&mut *tx).await?.rows_affected()
+            + relabel("cancelled", "inactive", "expired").execute(&mut *tx).await?.rows_affected();
+        let tokens_activated = relabel("confirmed", "inactive", "active").execute(&mut *tx).await?.rows_affected();
+        let tokens_deactivated = relabel("pending", "active", "inactive").execute(&mut *tx).await?.rows_affected();
+
+        tx.commit().await?;
+
+        Ok(models::EventReconciliation {
+            event_id: *event_id,
+            capacity,
+            confirmed_spots,
+            status_before,
+            status_after,
+            tokens_expired,
+            tokens_activated,
+            tokens_deactivated,
+        })
+    }
+
+    #[cfg_attr(not(test), expect(dead_code))]
+    pub async fn get_finished_event_by_id(&self, event_id: &Uuid) -> Result<models::FinishedEvent, DatabaseError> {
+        let event = sqlx::query_as::<_, EventRow>(
+"SELECT id, slug, name, description, start_time, end_time, capacity, location, max_reservations, max_spots_per_reservation, min_spots_per_reservation, visibility, reservations_open_at, reservations_close_at, skip_verification, require_name_match, token_rotation_minutes, series_id, sales_closed, status, cancelled_at, created_at, updated_at FROM events WHERE id = ? AND status = 'closed' AND cancelled_at IS NULL"
+        )
+        .bind(event_id.to_string())
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(DatabaseError::EventNotFound)?;
+
+        Ok(event.into())
+    }
+
+    /// Record the outcome of an email send attempt
+    pub async fn record_email_attempt(&self, to: &str, kind: EmailKind, error: Option<&EmailError>) -> Result<(), DatabaseError> {
+        let status = if error.is_some() { "failed" } else { "sent" };
+
+        sqlx::query("INSERT INTO email_log (recipient, kind, status, error) VALUES (?, ?, ?, ?)")
+            .bind(to)
+            .bind(kind.as_str())
+            .bind(status)
+            .bind(error.map(|e| e.to_string()))
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Emails of one kind successfully sent to a recipient (compared case-insensitively) since the given time
+    pub async fn count_emails_sent_since(&self, to: &str, kind: EmailKind, since: OffsetDateTime) -> Result<u32, DatabaseError> {
+        let count: u32 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM email_log WHERE LOWER(recipient) = LOWER(?) AND kind = ? AND status = 'sent' AND at >= ?"
+        )
+        .bind(to)
+        .bind(kind.as_str())
+        .bind(since.unix_timestamp())
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count)
+    }
+
+    /// Email send attempts, newest first, optionally limited to one recipient
+    pub async fn get_email_log(&self, email: Option<&str>) -> Result<Vec<models::EmailLogEntry>, DatabaseError> {
+        let rows = sqlx::query_as::<_, EmailLogRow>(
+            "SELECT recipient, kind, status, error, at FROM email_log WHERE (? IS NULL OR recipient = ?) ORDER BY at DESC, id DESC"
+        )
+        .bind(email)
+        .bind(email)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// An event's custom questions in display order
+    pub async fn get_event_questions(&self, event_id: &Uuid) -> Result<Vec<models::EventQuestion>, DatabaseError> {
+        let rows = sqlx::query_as::<_, EventQuestionRow>(
+            "SELECT key, prompt, required FROM event_questions WHERE event_id = ? ORDER BY position, id"
+        )
+        .bind(event_id.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// Replace an event's custom questions, keeping the given order
+    pub async fn set_event_questions(&self, event_id: &Uuid, questions: &[models::EventQuestion]) -> Result<(), DatabaseError> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM event_questions WHERE event_id = ?")
+            .bind(event_id.to_string())
+            .execute(&mut *tx)
+            .await?;
+
+        for (position, question) in questions.iter().enumerate() {
+            sqlx::query("INSERT INTO event_questions (event_id, key, prompt, required, position) VALUES (?, ?, ?, ?, ?)")
+                .bind(event_id.to_string())
+                .bind(&question.key)
+                .bind(&question.prompt)
+                .bind(question.required)
+                .bind(position as i64)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Answers from an event's pending and confirmed reservations, oldest first
+    pub async fn get_event_answers(&self, event_id: &Uuid) -> Result<Vec<models::ReservationAnswers>, DatabaseError> {
+        let rows = sqlx::query_as::<_, ReservationAnswersRow>(
+            r#"
+            SELECT id, user_name, user_email, answers
+            FROM reservations
+            WHERE event_id = ? AND status IN ('pending', 'confirmed') AND answers IS NOT NULL
+            ORDER BY created_at, id
+            "#
+        )
+        .bind(event_id.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// Pending and confirmed reservations for an event, oldest first
+    pub async fn get_event_roster(&self, event_id: &Uuid) -> Result<Vec<models::RosterEntry>, DatabaseError> {
+        let rows = sqlx::query_as::<_, RosterRow>(
+            r#"
+            SELECT id, user_name, user_email, party_name, spot_count, status
+            FROM reservations
+            WHERE event_id = ? AND status IN ('pending', 'confirmed')
+            ORDER BY created_at, rowid
+            "#
+        )
+        .bind(event_id.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// Version of the most recently applied migration, if any have run
+    pub async fn latest_migration_version(&self) -> Result<Option<i64>, DatabaseError> {
+        let version: Option<i64> = sqlx::query_scalar("SELECT MAX(version) FROM _sqlx_migrations WHERE success = 1")
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(version)
+    }
+
+    /// Compare the applied migrations against the ones embedded in this build, the same checks
+    /// `Migrator::validate` makes: every embedded migration applied, unchanged, and nothing applied we don't know
+    pub async fn migration_drift(&self) -> Result<Vec<models::MigrationDrift>, DatabaseError> {
+        let has_migrations_table: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations')"
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        let applied: Vec<(i64, String, Vec<u8>)> = if has_migrations_table {
+            sqlx::query_as("SELECT version, description, checksum FROM _sqlx_migrations WHERE success = 1 ORDER BY version")
+                .fetch_all(&self.pool)
+                .await?
+        } else {
+            Vec::new()
+        };
+
+        let migrator = sqlx::migrate!("./migrations");
+        let mut drift = Vec::new();
+
+        for migration in migrator.iter().filter(|migration| !migration.migration_type.is_down_migration()) {
+            let reason = match applied.iter().find(|(version, _, _)| *version == migration.version) {
+                None => models::MigrationDriftReason::NotApplied,
+                Some((_, _, checksum)) if checksum.as_slice() != &*migration.checksum => models::MigrationDriftReason::ChecksumMismatch,
+                Some(_) => continue,
+            };
+            drift.push(models::MigrationDrift {
+                version: migration.version,
+                description: migration.description.to_string(),
+                reason,
+            });
+        }
+
+        for (version, description, _) in applied {
+            if !migrator.iter().any(|migration| migration.version == version) {
+                drift.push(models::MigrationDrift {
+                    version,
+                    description,
+                    reason: models::MigrationDriftReason::UnknownToBinary,
+                });
+            }
+        }
+
+        Ok(drift)
+    }
+
+    /// Whether an event exists, whatever its status
+    pub async fn event_exists(&self, event_id: &Uuid) -> Result<bool, DatabaseError> {
+        let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM events WHERE id = ?)")
+            .bind(event_id.to_string())
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(exists)
+    }
+
+    /// Mark an active reservation token as used (type-safe state transition). Events with
+    /// `token_rotation_minutes` only accept the rotated code shown at the door, passed in `presented`
+    /// with the secret it was signed with, and only during its window.
+    #[tracing::instrument(skip_all, fields(reservation_id = %token.reservation_id))]
+    pub async fn mark_reservation_token_used(
+        &self,
+        token: models::ActiveReservationToken,
+        presented: Option<(&models::RotatedToken, &str)>,
+    ) -> Result<models::UsedReservationToken, DatabaseError> {
+        if let Some(rotation_minutes) = self.get_token_rotation_minutes(&token.token).await? {
+            let Some((rotated, secret)) = presented.filter(|(rotated, _)| rotated.token == token.token) else {
+                return Err(DatabaseError::TicketCodeRequired);
+            };
+            if !rotated.is_current(secret, rotation_minutes, OffsetDateTime::now_utc()) {
+                return Err(DatabaseError::TicketCodeExpired);
+            }
+        }
+
+        retry_when_busy(|| self.try_mark_reservation_token_used(token.clone())).await
+    }
+
+    async fn try_mark_reservation_token_used(&self, token: models::ActiveReservationToken) -> Result<models::UsedReservationToken, DatabaseError> {
+        let used = token.mark_used(OffsetDateTime::now_utc());
+
+        let result = sqlx::query(
+            "UPDATE reservation_tokens SET status = 'used', used_at = ? WHERE token = ? AND status = 'active'"
+        )
+        .bind(used.status.used_at)
+        .bind(&used.token)
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(DatabaseError::ReservationTokenNotFound);
+        }
+
+        Ok(used)
+    }
+
+    /// Revert a used reservation token to active and record the correction (type-safe state transition)
+    pub async fn unmark_reservation_token_used(&self, token: models::UsedReservationToken) -> Result<models::ActiveReservationToken, DatabaseError> {
+        let previous_used_at = token.status.used_at;
+        let active = token.unmark_used();
+
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query(
+            "UPDATE reservation_tokens SET status = 'active', used_at = NULL WHERE token = ? AND status = 'used'"
+        )
+        .bind(&active.token)
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(DatabaseError::ReservationTokenNotFound);
+        }
+
+        sqlx::query("INSERT INTO reservation_token_audit (token, action, previous_used_at) VALUES (?, 'unscan', ?)")
+            .bind(&active.token)
+            .bind(previous_used_at)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(active)
+    }
+
+    /// Rotation period of the event a reservation token belongs to, if that event rotates its ticket codes
+    pub async fn get_token_rotation_minutes(&self, token: &str) -> Result<Option<u32>, DatabaseError> {
+        let rotation_minutes: Option<Option<u32>> = sqlx::query_scalar(
+            r#"
+            SELECT e.token_rotation_minutes
+            FROM reservation_tokens t
+            JOIN reservations r ON r.id = t.reservation_id
+            JOIN events e ON e.id = r.event_id
+            WHERE t.token = ?
+            "#
+        )
+        .bind(token)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        rotation_minutes.ok_or(DatabaseError::ReservationTokenNotFound)
+    }
+
+    /// The reservation's name when the token's event requires door staff to match it at check-in
+    pub async fn get_token_required_name(&self, token: &str) -> Result<Option<String>, DatabaseError> {
+        let required_name: Option<Option<String>> = sqlx::query_scalar(
+            r#"
+            SELECT CASE WHEN e.require_name_match THEN r.user_name END
+            FROM reservation_tokens t
+            JOIN reservations r ON r.id = t.reservation_id
+            JOIN events e ON e.id = r.event_id
+            WHERE t.token = ?
+            "#
+        )
+        .bind(token)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        required_name.ok_or(DatabaseError::ReservationTokenNotFound)
+    }
+
+    /// Look up a reservation token by its value, whatever its state
+    pub async fn get_reservation_token(&self, token: &str) -> Result<models::AnyReservationToken, DatabaseError> {
+        let token_row = sqlx::query_as::<_, ReservationTokenRow>(
+            "SELECT id, reservation_id, token, label, short_code, status, created_at, used_at FROM reservation_tokens WHERE token = ?"
+        )
+        .bind(token)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(DatabaseError::ReservationTokenNotFound)?;
+
+        Ok(token_row.into_any_reservation_token())
+    }
+
+    /// The stored record for any reservation token, active, used or expired, with its reservation's event
+    pub async fn get_token_row(&self, token: &str) -> Result<models::TokenRecord, DatabaseError> {
+        let row = sqlx::query_as::<_, TokenRecordRow>(
+            r#"
+            SELECT t.token, t.status, t.label, t.created_at, t.used_at, t.reservation_id,
+                   r.status AS reservation_status, r.cancelled_at AS reservation_cancelled_at, r.event_id
+            FROM reservation_tokens t
+            JOIN reservations r ON r.id = t.reservation_id
+            WHERE t.token = ?
+            "#
+        )
+        .bind(token)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(DatabaseError::ReservationTokenNotFound)?;
+
+        Ok(row.into())
+    }
+
+    /// Every active token across an email's confirmed reservations, soonest event first
+    pub async fn get_active_tokens_by_email(&self, user_email: &str) -> Result<Vec<models::WalletToken>, DatabaseError> {
+        let rows = sqlx::query_as::<_, WalletTokenRow>(
+            r#"
+            SELECT t.token, t.label, t.reservation_id, e.id AS event_id, e.name AS event_name,
+                   e.start_time AS event_start_time, e.end_time AS event_end_time, e.location AS event_location
+            FROM reservation_tokens t
+            JOIN reservations r ON r.id = t.reservation_id
+            JOIN events e ON e.id = r.event_id
+            WHERE r.user_email = ? AND r.status = 'confirmed' AND t.status = 'active'
+            ORDER BY unixepoch(e.start_time, 'auto') ASC, t.created_at ASC, t.token ASC
+            "#
+        )
+        .bind(crate::email::normalize_email(user_email))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(models::WalletToken::from).collect())
+    }
+
+    /// Reservations whose email contains `fragment`, newest first. `%` and `_` in the fragment
+    /// match literally rather than as wildcards.
+    pub async fn search_reservations_by_email_fragment(&self, fragment: &str) -> Result<Vec<models::ReservationSearchResult>, DatabaseError> {
+        let escaped = escape_like(&crate::email::normalize_email(fragment));
+
+        let rows = sqlx::query_as::<_, ReservationSearchRow>(
+            r#"
+            SELECT r.id, r.user_email, r.user_name, r.status, r.spot_count, r.event_id, e.name AS event_name, r.created_at
+            FROM reservations r
+            JOIN events e ON e.id = r.event_id
+            WHERE r.user_email LIKE ? ESCAPE '\'
+            ORDER BY r.created_at DESC, r.rowid DESC
+            LIMIT ?
+            "#
+        )
+        .bind(format!("%{}%", escaped))
+        .bind(RESERVATION_SEARCH_LIMIT)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// The full token behind a short code typed in at the door for an event
+    pub async fn get_token_by_short_code(&self, event_id: &Uuid, short_code: &str) -> Result<String, DatabaseError> {
+        sqlx::query_scalar(
+            "SELECT t.token FROM reservation_tokens t JOIN reservations r ON r.id = t.reservation_id WHERE r.event_id = ? AND t.short_code = ?"
+        )
+        .bind(event_id.to_string())
+        .bind(models::normalize_short_code(short_code))
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(DatabaseError::ReservationTokenNotFound)
+    }
+
+    /// Store a token granting access to an email's tickets until `expires_at`
+    pub async fn insert_email_access_token(&self, user_email: &str, token: &str, created_at: OffsetDateTime, expires_at: OffsetDateTime) -> Result<(), DatabaseError> {
+        sqlx::query("INSERT INTO email_access_tokens (token, email, created_at, expires_at) VALUES (?, ?, ?, ?)")
+            .bind(token)
+            .bind(crate::email::normalize_email(user_email))
+            .bind(created_at.unix_timestamp())
+            .bind(expires_at.unix_timestamp())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Whether an access token was issued for this email and hasn't lapsed
+    pub async fn email_access_token_is_valid(&self, user_email: &str, token: &str, now: OffsetDateTime) -> Result<bool, DatabaseError> {
+        let found: Option<i64> = sqlx::query_scalar(
+            "SELECT 1 FROM email_access_tokens WHERE token = ? AND email = ? AND expires_at > ?"
+        )
+        .bind(token)
+        .bind(crate::email::normalize_email(user_email))
+        .bind(now.unix_timestamp())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(found.is_some())
+    }
+
+    #[cfg_attr(not(test), expect(dead_code))]
+    pub async fn check_open_event_capacity(&self, event_id: &Uuid) -> Result<bool, DatabaseError> {
+        let event = self.get_open_event_by_id(event_id).await?;
+        let current_reservations = self.count_event_reservations(event_id, false).await?;
+        
+        Ok(current_reservations < event.capacity)
+    }
+
+    pub async fn create_event(&self, new_event: models::NewEvent) -> Result<models::OpenEvent, DatabaseError> {
+        let mut conn = self.pool.acquire().await?;
+        let event_id = insert_event(&mut conn, &new_event).await?;
+
+        self.get_open_event_by_id(&event_id).await
+    }
+
+    /// Copy an event to a new start time. With `attach_to_series` the copy joins the source's series,
+    /// which starts from the source (using its id) if it isn't part of one yet.
+    pub async fn duplicate_event(&self, source: &models::AnyEvent, start_time: OffsetDateTime, attach_to_series: bool) -> Result<models::OpenEvent, DatabaseError> {
+        let series_id = attach_to_series.then(|| source.series_id().unwrap_or(source.id()));
+        let new_event = source.duplicate(start_time, series_id);
+
+        let mut tx = self.pool.begin().await?;
+
+        if attach_to_series && source.series_id().is_none() {
+            sqlx::query("UPDATE events SET series_id = id WHERE id = ?")
+                .bind(source.id().to_string())
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        let event_id = insert_event(&mut tx, &new_event).await?;
+
+        tx.commit().await?;
+
+        self.get_open_event_by_id(&event_id).await
+    }
+
+    /// Every event in a series, whatever its status, earliest first
+    pub async fn get_events_in_series(&self, series_id: &Uuid) -> Result<Vec<models::AnyEvent>, DatabaseError> {
+        let rows = sqlx::query_as::<_, EventRow>(
+"SELECT id, slug, name, description, start_time, end_time, capacity, location, max_reservations, max_spots_per_reservation, min_spots_per_reservation, visibility, reservations_open_at, reservations_close_at, skip_verification, require_name_match, token_rotation_minutes, series_id, sales_closed, status, cancelled_at, created_at, updated_at FROM events WHERE series_id = ? ORDER BY start_time ASC"
+        )
+        .bind(series_id.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// Create several events at once; either all of them are created or none are
+    pub async fn create_events(&self, new_events: &[models::NewEvent]) -> Result<Vec<Uuid>, DatabaseError> {
+        let mut tx = self.pool.begin().await?;
+
+        let mut event_ids = Vec::with_capacity(new_events.len());
+        for new_event in new_events {
+            event_ids.push(insert_event(&mut tx, new_event).await?);
+        }
+
+        tx.commit().await?;
+
+        Ok(event_ids)
+    }
+
+    /// Everything stored for an event as one backup document: the event, its questions, and each
+    /// reservation with its tokens
+    pub async fn export_event_backup(&self, event_id: &Uuid) -> Result<backup::EventBackup, DatabaseError> {
+        let event = sqlx::query_as::<_, EventRow>(
+"SELECT id, slug, name, description, start_time, end_time, capacity, location, max_reservations, max_spots_per_reservation, min_spots_per_reservation, visibility, reservations_open_at, reservations_close_at, skip_verification, require_name_match, token_rotation_minutes, series_id, sales_closed, status, cancelled_at, created_at, updated_at FROM events WHERE id = ?"
+        )
+        .bind(event_id.to_string())
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(DatabaseError::EventNotFound)?;
+
+        let questions = self.get_event_questions(event_id).await?
+            .into_iter()
+            .map(|question| backup::BackedUpQuestion {
+                key: question.key,
+                prompt: question.prompt,
+                required: question.required,
+            })
+            .collect();
+
+        let reservation_rows = sqlx::query_as::<_, ReservationRow>(
+            "SELECT id, event_id, user_name, user_email, spot_count, spot_labels, answers, party_name, cancellation_token, refund_status, cancelled_at, status, verification_token, created_at, updated_at, verified_at FROM reservations WHERE event_id = ? ORDER BY created_at, rowid"
+        )
+        .bind(event_id.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut reservations = Vec::with_capacity(reservation_rows.len());
+        for row in reservation_rows {
+            let tokens = sqlx::query_as::<_, ReservationTokenRow>(
+                "SELECT id, reservation_id, token, label, short_code, status, created_at, used_at FROM reservation_tokens WHERE reservation_id = ? ORDER BY rowid"
+            )
+            .bind(&row.id)
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(|token| backup::BackedUpToken {
+                token: token.token,
+                label: token.label,
+                short_code: token.short_code,
+                status: token.status,
+                created_at: token.created_at,
+                used_at: token.used_at,
+            })
+            .collect();
+
+            reservations.push(backup::BackedUpReservation {
+                user_name: row.user_name,
+                user_email: row.user_email,
+                spot_count: row.spot_count,
+                spot_labels: row.spot_labels.map(|labels| serde_json::from_str(&labels).expect("Invalid spot labels in database")),
+                answers: row.answers.map(|answers| serde_json::from_str(&answers).expect("Invalid answers in database")),
+                party_name: row.party_name,
+                status: row.status,
+                verification_token: row.verification_token,
+                cancellation_token: row.cancellation_token,
+                refund_status: row.refund_status,
+                cancelled_at: row.cancelled_at,
+                created_at: row.created_at,
+                verified_at: row.verified_at,
+                tokens,
+            });
+        }
+
+        Ok(backup::EventBackup {
+            version: backup::BACKUP_VERSION,
+            event: backup::BackedUpEvent {
+                name: event.name,
+                description: event.description,
+                start_time: event.start_time,
+                end_time: event.end_time,
+                capacity: event.capacity,
+                location: event.location,
+                max_reservations: event.max_reservations,
+                max_spots_per_reservation: event.max_spots_per_reservation,
+                min_spots_per_reservation: event.min_spots_per_reservation,
+                visibility: event.visibility,
+                reservations_open_at: event.reservations_open_at,
+                reservations_close_at: event.reservations_close_at,
+                skip_verification: event.skip_verification,
+                require_name_match: event.require_name_match,
+                token_rotation_minutes: event.token_rotation_minutes,
+                status: event.status,
+                cancelled_at: event.cancelled_at,
+            },
+            questions,
+            reservations,
+        })
+    }
+
+    /// Recreate an event from a backup under new ids, all in one transaction; the event gets a fresh slug
+    pub async fn import_event_backup(&self, event_backup: &backup::EventBackup) -> Result<Uuid, DatabaseError> {
+        let event = &event_backup.event;
+        let new_event = models::NewEvent {
+            name: event.name.clone(),
+            description: event.description.clone(),
+            start_time: event.start_time,
+            end_time: event.end_time,
+            capacity: event.capacity,
+            location: event.location.clone(),
+            max_reservations: event.max_reservations,
+            max_spots_per_reservation: event.max_spots_per_reservation,
+            min_spots_per_reservation: event.min_spots_per_reservation,
+            visibility: models::EventVisibility::from_db(&event.visibility),
+            reservations_open_at: event.reservations_open_at,
+            reservations_close_at: event.reservations_close_at,
+            skip_verification: event.skip_verification,
+            require_name_match: event.require_name_match,
+            token_rotation_minutes: event.token_rotation_minutes,
+            series_id: None,
+        };
+
+        let mut tx = self.pool.begin().await?;
+
+        let event_id = insert_event(&mut tx, &new_event).await?;
+        sqlx::query("UPDATE events SET status = ?, cancelled_at = ? WHERE id = ?")
+            .bind(&event.status)
+            .bind(event.cancelled_at.map(|at| at.unix_timestamp()))
+            .bind(event_id.to_string())
+            .execute(&mut *tx)
+            .await?;
+
+        for (position, question) in event_backup.questions.iter().enumerate() {
+            sqlx::query("INSERT INTO event_questions (event_id, key, prompt, required, position) VALUES (?, ?, ?, ?, ?)")
+                .bind(event_id.to_string())
+                .bind(&question.key)
+                .bind(&question.prompt)
+                .bind(question.required)
+                .bind(position as i64)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        // Token values are kept, so restoring over data that still has them is a conflict
+        let map_unique_violation = |e: sqlx::Error| match e {
+            sqlx::Error::Database(db_err) if db_err.is_unique_violation() => DatabaseError::BackupConflict,
+            e => DatabaseError::SqlxError(e),
+        };
+
+        for reservation in &event_backup.reservations {
+            let reservation_id = Uuid::new_v4();
+            sqlx::query(
+                r#"
+                INSERT INTO reservations (id, event_id, user_name, user_email, spot_count, spot_labels, answers, party_name, status, verification_token, cancellation_token, refund_status, cancelled_at, created_at, verified_at)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#
+            )
+            .bind(reservation_id.to_string())
+            .bind(event_id.to_string())
+            .bind(&reservation.user_name)
+            .bind(&reservation.user_email)
+            .bind(reservation.spot_count)
+            .bind(reservation.spot_labels.as_ref().map(|labels| serde_json::to_string(labels).expect("Failed to serialize spot labels")))
+            .bind(reservation.answers.as_ref().map(|answers| answers.to_string()))
+            .bind(&reservation.party_name)
+            .bind(&reservation.status)
+            .bind(&reservation.verification_token)
+            .bind(&reservation.cancellation_token)
+            .bind(&reservation.refund_status)
+            .bind(reservation.cancelled_at.map(OffsetDateTime::unix_timestamp))
+            .bind(reservation.created_at.unix_timestamp())
+            .bind(reservation.verified_at)
+            .execute(&mut *tx)
+            .await
+            .map_err(map_unique_violation)?;
+
+            for token in &reservation.tokens {
+                sqlx::query(
+                    "INSERT INTO reservation_tokens (id, reservation_id, token, label, short_code, status, created_at, used_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)"
+                )
+                .bind(Uuid::new_v4().to_string())
+                .bind(reservation_id.to_string())
+                .bind(&token.token)
+                .bind(&token.label)
+                .bind(&token.short_code)
+                .bind(&token.status)
+                .bind(token.created_at.unix_timestamp())
+                .bind(token.used_at)
+                .execute(&mut *tx)
+                .await
+                .map_err(map_unique_violation)?;
+            }
+        }
+
+        tx.commit().await?;
 
-        row.to_pending_reservation().await
+        Ok(event_id)
     }
 
-    pub async fn get_confirmed_reservation_by_reservation_token(&self, token: &str) -> Result<models::ConfirmedReservation, DatabaseError> {
-        // First find the reservation_id for this token
-        let reservation_id: String = sqlx::query_scalar(
-            "SELECT reservation_id FROM reservation_tokens WHERE token = ? AND status = 'active'"
-        )
-        .bind(token)
-        .fetch_optional(&self.pool)
-        .await?
-        .ok_or(DatabaseError::ReservationNotFound)?;
+    /// Confirm a pending reservation (type-safe state transition)
+    #[tracing::instrument(skip_all, fields(reservation_id = %pending.id, event_id = %pending.event_id))]
+    pub async fn confirm_reservation(&self, pending: models::PendingReservation) -> Result<models::ConfirmedReservation, DatabaseError> {
+        retry_when_busy(|| self.try_confirm_reservation(pending.clone())).await
+    }
 
-        // Then get the reservation
-        let row = sqlx::query_as::<_, ReservationRow>(
-            "SELECT id, event_id, user_name, user_email, status, verification_token, created_at, updated_at, verified_at FROM reservations WHERE id = ? AND status = 'confirmed'"
-        )
-        .bind(&reservation_id)
-        .fetch_optional(&self.pool)
-        .await?
-        .ok_or(DatabaseError::ReservationNotFound)?;
+    async fn try_confirm_reservation(&self, pending: models::PendingReservation) -> Result<models::ConfirmedReservation, DatabaseError> {
+        let pre_issued = self.get_inactive_reservation_tokens(&pending.id).await?;
+        let confirmed = if pre_issued.is_empty() {
+            pending.confirm(OffsetDateTime::now_utc())
+        } else {
+            pending.confirm_pre_issued(OffsetDateTime::now_utc(), pre_issued)
+        };
+        self.store_confirmed_reservation(confirmed).await
+    }
+
+    /// Store inactive tokens for a pending reservation so confirming it only has to activate them
+    pub async fn pre_issue_reservation_tokens(&self, pending: &models::PendingReservation) -> Result<Vec<models::AnyReservationToken>, DatabaseError> {
+        let mut tokens = pending.pre_issue_tokens(OffsetDateTime::now_utc());
 
-        row.to_confirmed_reservation(&self).await
+        let mut tx = self.pool.begin().await?;
+        insert_reservation_tokens(&mut tx, &pending.id, &mut tokens).await?;
+        tx.commit().await?;
+
+        Ok(tokens)
     }
 
-    async fn get_reservation_tokens_by_reservation_id(&self, reservation_id: &str) -> Result<Vec<models::AnyReservationToken>, DatabaseError> {
+    /// A pending reservation's pre-issued tokens, in spot order
+    async fn get_inactive_reservation_tokens(&self, reservation_id: &Uuid) -> Result<Vec<models::InactiveReservationToken>, DatabaseError> {
         let token_rows = sqlx::query_as::<_, ReservationTokenRow>(
-            "SELECT id, reservation_id, token, status, created_at, used_at FROM reservation_tokens WHERE reservation_id = ?"
+            "SELECT id, reservation_id, token, label, short_code, status, created_at, used_at FROM reservation_tokens WHERE reservation_id = ? AND status = 'inactive' ORDER BY rowid"
         )
-        .bind(reservation_id)
+        .bind(reservation_id.to_string())
         .fetch_all(&self.pool)
         .await?;
-    
-        Ok(token_rows.into_iter().map(|row| {
-            let reservation_id = Uuid::parse_str(&row.reservation_id).expect("Invalid reservation UUID in database");
-            
-            // Map database status to appropriate AnyReservationToken variant
-            match row.status.as_str() {
-                "active" => {
-                    let active_token = models::ReservationToken {
-                        token: row.token,
-                        reservation_id,
-                        created_at: row.created_at,
-                        status: models::Active,
-                    };
-                    models::AnyReservationToken::Active(active_token)
-                }
-                "used" => {
-                    let used_token = models::ReservationToken {
-                        token: row.token,
-                        reservation_id,
-                        created_at: row.created_at,
-                        status: models::Used,
-                    };
-                    models::AnyReservationToken::Used(used_token)
-                }
-                "expired" => {
-                    let expired_token = models::ReservationToken {
-                        token: row.token,
-                        reservation_id,
-                        created_at: row.created_at,
-                        status: models::Expired,
-                    };
-                    models::AnyReservationToken::Expired(expired_token)
-                }
-                _ => {
-                    // Handle unknown status by treating as expired
-                    let expired_token = models::ReservationToken {
-                        token: row.token,
-                        reservation_id,
-                        created_at: row.created_at,
-                        status: models::Expired,
-                    };
-                    models::AnyReservationToken::Expired(expired_token)
-                }
-            }
-        }).collect())
+
+        Ok(token_rows.into_iter().map(ReservationTokenRow::into_inactive_reservation_token).collect())
     }
 
-    pub async fn check_open_event_capacity(&self, event_id: &Uuid) -> Result<bool, DatabaseError> {
-        let event = self.get_open_event_by_id(event_id).await?;
-        let current_reservations = self.count_event_reservations(event_id).await?;
-        
-        Ok(current_reservations < event.capacity)
+    /// Persist a confirmation: status change and token inserts happen in one transaction
+    async fn store_confirmed_reservation(&self, mut confirmed: models::ConfirmedReservation) -> Result<models::ConfirmedReservation, DatabaseError> {
+        let mut tx = self.pool.begin().await?;
+
+        // Update the reservation status and set verified_at timestamp
+        // Note: updated_at is handled by database trigger, verified_at is set by application
+        let updated = sqlx::query(
+            "UPDATE reservations SET status = 'confirmed', verified_at = ?, cancellation_token = ? WHERE id = ? AND status = 'pending'"
+        )
+        .bind(confirmed.status.verified_at)
+        .bind(&confirmed.status.cancellation_token)
+        .bind(confirmed.id.to_string())
+        .execute(&mut *tx)
+        .await?;
+
+        // Someone else confirmed (or cancelled) it first; leave their cancellation token and tokens alone
+        if updated.rows_affected() == 0 {
+            return Err(DatabaseError::ReservationNotFound);
+        }
+
+        // Pre-issued tokens only need activating; otherwise insert the freshly issued ones
+        let activated = sqlx::query(
+            "UPDATE reservation_tokens SET status = 'active' WHERE reservation_id = ? AND status = 'inactive'"
+        )
+        .bind(confirmed.id.to_string())
+        .execute(&mut *tx)
+        .await?;
+
+        if activated.rows_affected() == 0 {
+            insert_reservation_tokens(&mut tx, &confirmed.id, &mut confirmed.status.reservation_tokens).await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(confirmed)
     }
 
-    pub async fn create_event(
-        &self,
-        name: &str,
-        description: Option<&str>,
-        start_time: OffsetDateTime,
-        end_time: OffsetDateTime,
-        capacity: i32,
-        location: Option<&str>,
-    ) -> Result<models::OpenEvent, DatabaseError> {
-        let event_id = Uuid::new_v4();
+    /// Move a confirmed reservation to another event (type-safe state transition)
+    /// Expires the reservation's active tokens and issues new ones, all in one transaction
+    pub async fn move_reservation(&self, confirmed: models::ConfirmedReservation, target_event_id: &Uuid) -> Result<models::ConfirmedReservation, DatabaseError> {
+        let mut moved = confirmed.move_to(*target_event_id, OffsetDateTime::now_utc());
+
+        let mut tx = self.pool.begin().await?;
 
         sqlx::query(
-            r#"
-            INSERT INTO events (id, name, description, start_time, end_time, capacity, location, status)
-            VALUES (?, ?, ?, ?, ?, ?, ?, 'open')
-            "#
+            "UPDATE reservations SET event_id = ? WHERE id = ? AND status = 'confirmed'"
         )
-        .bind(event_id.to_string())
-        .bind(name)
-        .bind(description)
-        .bind(start_time)
-        .bind(end_time)
-        .bind(capacity)
-        .bind(location)
-        .execute(&self.pool)
+        .bind(moved.event_id.to_string())
+        .bind(moved.id.to_string())
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db_err) if db_err.is_unique_violation() => DatabaseError::DuplicateReservation,
+            e => DatabaseError::SqlxError(e),
+        })?;
+
+        sqlx::query(
+            "UPDATE reservation_tokens SET status = 'expired' WHERE reservation_id = ? AND status = 'active'"
+        )
+        .bind(moved.id.to_string())
+        .execute(&mut *tx)
         .await?;
 
-        self.get_open_event_by_id(&event_id).await
-    }
+        insert_reservation_tokens(&mut tx, &moved.id, &mut moved.status.reservation_tokens).await?;
 
-    // Helper methods for API compatibility (string IDs)
-    
-    /// Look up an event by string ID (converts to UUID)
-    pub async fn get_open_event_by_string_id(&self, event_id: &str) -> Result<models::OpenEvent, DatabaseError> {
-        let uuid = Uuid::parse_str(event_id)
-            .map_err(|_| DatabaseError::EventNotFound)?;
-        self.get_open_event_by_id(&uuid).await
-    }
+        tx.commit().await?;
 
-    pub async fn check_event_capacity_with_string_id(&self, event_id: &str) -> Result<bool, DatabaseError> {
-        let uuid = Uuid::parse_str(event_id)
-            .map_err(|_| DatabaseError::EventNotFound)?;
-        self.check_open_event_capacity(&uuid).await
+        Ok(moved)
     }
 
-    /// Confirm a pending reservation (type-safe state transition)
-    pub async fn confirm_reservation(&self, pending: models::PendingReservation) -> Result<models::ConfirmedReservation, DatabaseError> {
-        let confirmed = pending.confirm(OffsetDateTime::now_utc());
-        
-        // Update the reservation status and set verified_at timestamp
-        // Note: updated_at is handled by database trigger, verified_at is set by application
-        sqlx::query(
-            "UPDATE reservations SET status = 'confirmed', verified_at = ? WHERE id = ?"
+    /// Change a confirmed reservation's spot count in one transaction: issue tokens for added spots,
+    /// expire tokens for removed ones, and record the change in the reservation audit trail
+    pub async fn adjust_reservation_spots(&self, confirmed: models::ConfirmedReservation, spot_count: u32) -> Result<models::ConfirmedReservation, DatabaseError> {
+        let previous_spot_count = confirmed.spot_count;
+        let (mut adjusted, expired) = confirmed.adjust_spots(spot_count, OffsetDateTime::now_utc());
+
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query(
+            "UPDATE reservations SET spot_count = ?, spot_labels = ?, updated_at = ? WHERE id = ? AND status = 'confirmed'"
         )
-        .bind(confirmed.status.verified_at)
-        .bind(confirmed.id.to_string())
-        .execute(&self.pool)
+        .bind(adjusted.spot_count)
+        .bind(adjusted.spot_labels.as_ref().map(|labels| serde_json::to_string(labels).expect("Failed to serialize spot labels")))
+        .bind(adjusted.status.updated_at)
+        .bind(adjusted.id.to_string())
+        .execute(&mut *tx)
         .await?;
 
-        // Insert the reservation tokens
-        for token in &confirmed.status.reservation_tokens {
-            let token_id = Uuid::new_v4();
-            sqlx::query(
-                r#"
-                INSERT INTO reservation_tokens (id, reservation_id, token, status)
-                VALUES (?, ?, ?, 'active')
-                "#
-            )
-            .bind(token_id.to_string())
+        if result.rows_affected() == 0 {
+            return Err(DatabaseError::ReservationNotFound);
+        }
+
+        for token in &expired {
+            sqlx::query("UPDATE reservation_tokens SET status = 'expired' WHERE token = ? AND status = 'active'")
+                .bind(&token.token)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        // Added spots' tokens were appended to the end of the list
+        let issued = spot_count.saturating_sub(previous_spot_count) as usize;
+        let first_issued = adjusted.status.reservation_tokens.len() - issued;
+        insert_reservation_tokens(&mut tx, &adjusted.id, &mut adjusted.status.reservation_tokens[first_issued..]).await?;
+
+        sqlx::query("INSERT INTO reservation_audit (reservation_id, action, previous_spot_count, spot_count) VALUES (?, 'adjust_spots', ?, ?)")
+            .bind(adjusted.id.to_string())
+            .bind(previous_spot_count)
+            .bind(spot_count)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(adjusted)
+    }
+
+    /// Cancel a confirmed reservation, expiring its active tokens so they no longer scan,
+    /// and record the refund the cancellation policy grants. Returns when it was cancelled.
+    #[tracing::instrument(skip_all, fields(reservation_id = %confirmed.id, event_id = %confirmed.event_id))]
+    pub async fn cancel_confirmed_reservation(&self, confirmed: models::ConfirmedReservation, refund_status: models::RefundStatus) -> Result<OffsetDateTime, DatabaseError> {
+        let mut tx = self.pool.begin().await?;
+
+        let Some(cancelled_at) = cancel_reservation(&mut tx, &confirmed.id, "confirmed").await? else {
+            return Err(DatabaseError::ReservationNotFound);
+        };
+
+        sqlx::query("UPDATE reservations SET refund_status = ? WHERE id = ?")
+            .bind(refund_status.as_str())
             .bind(confirmed.id.to_string())
-            .bind(&token.token())
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await?;
-        }
 
-        Ok(confirmed)
+        tx.commit().await?;
+
+        Ok(cancelled_at)
     }
 
+    /// Cancel every pending and/or confirmed reservation for an event in one transaction,
+    /// returning the reservations that were cancelled, oldest first
+    pub async fn cancel_event_reservations(&self, event_id: &Uuid, include_pending: bool, include_confirmed: bool) -> Result<Vec<models::RosterEntry>, DatabaseError> {
+        let mut tx = self.pool.begin().await?;
+
+        let rows = sqlx::query_as::<_, RosterRow>(
+            r#"
+            SELECT id, user_name, user_email, party_name, spot_count, status
+            FROM reservations
+            WHERE event_id = ? AND ((? AND status = 'pending') OR (? AND status = 'confirmed'))
+            ORDER BY created_at, rowid
+            "#
+        )
+        .bind(event_id.to_string())
+        .bind(include_pending)
+        .bind(include_confirmed)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut cancelled = Vec::with_capacity(rows.len());
+        for row in rows {
+            let entry = models::RosterEntry::from(row);
+            let from_status = if entry.confirmed { "confirmed" } else { "pending" };
+            if cancel_reservation(&mut tx, &entry.reservation_id, from_status).await?.is_some() {
+                cancelled.push(entry);
+            }
+        }
+
+        tx.commit().await?;
+
+        Ok(cancelled)
+    }
 
     // /// Cancel a pending reservation (type-safe state transition)
     // pub async fn cancel_pending_reservation(&self, pending: models::PendingReservation) -> Result<models::CancelledReservation, DatabaseError> {
@@ -571,31 +2610,100 @@ mod tests {
     use super::*;
     use time::Duration;
 
-    #[tokio::test]
-    async fn test_database_operations() {
-        // Set test database URL
-        env::set_var("DATABASE_URL", "sqlite::memory:");
-        
-        let db = Database::new().await.unwrap();
-        
+    async fn setup_test_db() -> Database {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+
         // Run migrations for test database
         sqlx::migrate!("./migrations")
             .run(&db.pool)
             .await
             .expect("Failed to run migrations");
-        
-        // Test event creation
+
+        db
+    }
+
+    fn test_new_event(capacity: u32) -> models::NewEvent {
         let start_time = OffsetDateTime::now_utc() + Duration::hours(1);
         let end_time = start_time + Duration::hours(2);
-        
-        let event = db.create_event(
-            "Test Event",
-            Some("A test event"),
+
+        models::NewEvent {
+            name: "Test Event".to_string(),
+            description: Some("A test event".to_string()),
             start_time,
             end_time,
-            50,
-            Some("Test Location"),
+            capacity,
+            location: Some("Test Location".to_string()),
+            max_reservations: None,
+            max_spots_per_reservation: None,
+            min_spots_per_reservation: None,
+            visibility: models::EventVisibility::Public,
+            reservations_open_at: None,
+            reservations_close_at: None,
+            skip_verification: false,
+            require_name_match: false,
+            token_rotation_minutes: None,
+            series_id: None,
+        }
+    }
+
+    async fn create_test_event(db: &Database, capacity: u32) -> models::OpenEvent {
+        db.create_event(test_new_event(capacity)).await.unwrap()
+    }
+
+    async fn create_confirmed_reservation(db: &Database, event_id: Uuid, user_email: &str, spot_count: u32) -> models::ConfirmedReservation {
+        let pending = db.insert_reservation(
+            models::CreatingReservation::prepare(event_id, "Test User".to_string(), user_email.to_string(), spot_count, None)
+        ).await.unwrap();
+
+        db.confirm_reservation(pending).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_count_event_reservations_confirmed_only() {
+        let db = setup_test_db().await;
+        let event = create_test_event(&db, 50).await;
+
+        create_confirmed_reservation(&db, event.id, "john@example.com", 2).await;
+        db.insert_reservation(
+            models::CreatingReservation::prepare(event.id, "Jane".to_string(), "jane@example.com".to_string(), 3, None)
+        ).await.unwrap();
+
+        assert_eq!(db.count_event_reservations(&event.id, false).await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_count_event_reservations_including_pending() {
+        let db = setup_test_db().await;
+        let event = create_test_event(&db, 50).await;
+
+        create_confirmed_reservation(&db, event.id, "john@example.com", 2).await;
+        let pending = db.insert_reservation(
+            models::CreatingReservation::prepare(event.id, "Jane".to_string(), "jane@example.com".to_string(), 3, None)
         ).await.unwrap();
+        let cancelled = db.insert_reservation(
+            models::CreatingReservation::prepare(event.id, "Jim".to_string(), "jim@example.com".to_string(), 4, None)
+        ).await.unwrap();
+        db.cancel_pending_reservation(cancelled).await.unwrap();
+
+        assert_eq!(db.count_event_reservations(&event.id, true).await.unwrap(), 5);
+
+        db.confirm_reservation(pending).await.unwrap();
+        assert_eq!(db.count_event_reservations(&event.id, true).await.unwrap(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_database_operations() {
+        // Connect directly rather than through DATABASE_URL, which the config tests change concurrently
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        
+        // Run migrations for test database
+        sqlx::migrate!("./migrations")
+            .run(&db.pool)
+            .await
+            .expect("Failed to run migrations");
+        
+        // Test event creation
+        let event = db.create_event(test_new_event(50)).await.unwrap();
         
         assert_eq!(event.name, "Test Event");
         assert_eq!(event.capacity, 50);
@@ -607,7 +2715,10 @@ mod tests {
             user_name: "John Doe".to_string(),
             user_email: "john@example.com".to_string(),
             spot_count: 1,
-            verification_token: models::VerificationToken::new(),
+            spot_labels: None,
+            answers: None,
+            party_name: None,
+            verification_token: models::VerificationToken("verification-token-123".to_string()),
             status: models::Creating,
         };
         
@@ -624,4 +2735,470 @@ mod tests {
         let found = db.get_pending_reservation_by_verification_token("verification-token-123").await.unwrap();
         assert_eq!(found.id, reservation.id);
     }
+
+    #[tokio::test]
+    async fn test_mark_used_checks_rotation_window() {
+        let db = setup_test_db().await;
+        let event = db.create_event(models::NewEvent {
+            token_rotation_minutes: Some(5),
+            ..test_new_event(50)
+        }).await.unwrap();
+        let confirmed = create_confirmed_reservation(&db, event.id, "john@example.com", 1).await;
+        let token = confirmed.get_active_reservation_tokens().remove(0);
+        let secret = "test-secret";
+
+        let result = db.mark_reservation_token_used(token.clone(), None).await;
+        assert!(matches!(result, Err(DatabaseError::TicketCodeRequired)));
+
+        let stale = models::RotatedToken::issue(&token.token, secret, 5, OffsetDateTime::now_utc() - Duration::minutes(10));
+        let result = db.mark_reservation_token_used(token.clone(), Some((&stale, secret))).await;
+        assert!(matches!(result, Err(DatabaseError::TicketCodeExpired)));
+        assert!(db.get_reservation_token(&token.token).await.unwrap().is_active());
+
+        let current = models::RotatedToken::issue(&token.token, secret, 5, OffsetDateTime::now_utc());
+        let used = db.mark_reservation_token_used(token, Some((&current, secret))).await.unwrap();
+        assert!(db.get_reservation_token(&used.token).await.unwrap().is_used());
+    }
+
+    #[tokio::test]
+    async fn test_get_tokens_for_event() {
+        let db = setup_test_db().await;
+        let event = create_test_event(&db, 50).await;
+
+        let confirmed = create_confirmed_reservation(&db, event.id, "john@example.com", 2).await;
+        let mut active_tokens = confirmed.get_active_reservation_tokens();
+        let used = db.mark_reservation_token_used(active_tokens.remove(0), None).await.unwrap();
+        let still_active = active_tokens.remove(0);
+
+        let tokens = db.get_tokens_for_event(&event.id, None).await.unwrap();
+        assert_eq!(tokens.len(), 2);
+
+        let used_token = tokens.iter().find(|t| t.token() == used.token).unwrap();
+        assert!(used_token.is_used());
+        assert!(used_token.used_at().is_some());
+
+        let active_token = tokens.iter().find(|t| t.token() == still_active.token).unwrap();
+        assert!(active_token.is_active());
+        assert!(active_token.used_at().is_none());
+
+        let used_only = db.get_tokens_for_event(&event.id, Some("used")).await.unwrap();
+        assert_eq!(used_only.len(), 1);
+        assert_eq!(used_only[0].token(), used.token);
+    }
+
+    #[tokio::test]
+    async fn test_expire_active_tokens_for_event() {
+        let db = setup_test_db().await;
+        let event = create_test_event(&db, 50).await;
+        let other_event = create_test_event(&db, 50).await;
+
+        let confirmed = create_confirmed_reservation(&db, event.id, "john@example.com", 3).await;
+        let other = create_confirmed_reservation(&db, other_event.id, "jane@example.com", 1).await;
+        let used = db.mark_reservation_token_used(confirmed.get_active_reservation_tokens().remove(0), None).await.unwrap();
+
+        let expired_count = db.expire_active_tokens_for_event(&event.id).await.unwrap();
+        assert_eq!(expired_count, 2);
+
+        let tokens = db.get_tokens_for_event(&event.id, None).await.unwrap();
+        for token in &tokens {
+            if token.token() == used.token {
+                assert!(token.is_used());
+            } else {
+                assert!(token.is_expired());
+            }
+        }
+
+        // Other events are untouched
+        let other_tokens = db.get_tokens_for_event(&other_event.id, None).await.unwrap();
+        assert_eq!(other_tokens.len(), other.spot_count as usize);
+        assert!(other_tokens.iter().all(|t| t.is_active()));
+    }
+
+    #[tokio::test]
+    async fn test_search_events_filtered() {
+        let db = setup_test_db().await;
+        let soon = OffsetDateTime::now_utc() + Duration::days(1);
+        let later = OffsetDateTime::now_utc() + Duration::days(30);
+
+        let jazz_soon = db.create_event(models::NewEvent {
+            name: "Jazz Night".to_string(),
+            start_time: soon,
+            end_time: soon + Duration::hours(2),
+            ..test_new_event(2)
+        }).await.unwrap();
+        let jazz_soon_full = db.create_event(models::NewEvent {
+            name: "Late Jazz Session".to_string(),
+            start_time: soon + Duration::hours(3),
+            end_time: soon + Duration::hours(5),
+            ..test_new_event(2)
+        }).await.unwrap();
+        create_confirmed_reservation(&db, jazz_soon_full.id, "john@example.com", 2).await;
+        let jazz_later = db.create_event(models::NewEvent {
+            name: "Jazz Brunch".to_string(),
+            start_time: later,
+            end_time: later + Duration::hours(2),
+            ..test_new_event(2)
+        }).await.unwrap();
+        db.create_event(models::NewEvent {
+            name: "Poetry Reading".to_string(),
+            start_time: soon,
+            end_time: soon + Duration::hours(2),
+            ..test_new_event(2)
+        }).await.unwrap();
+
+        let ids = |events: Vec<models::OpenEvent>| events.into_iter().map(|e| e.id).collect::<Vec<_>>();
+
+        let by_name = db.search_events_filtered(&models::EventSearch {
+            name_query: Some("jazz".to_string()),
+            ..Default::default()
+        }, false, OffsetDateTime::now_utc()).await.unwrap();
+        assert_eq!(ids(by_name), vec![jazz_soon.id, jazz_soon_full.id, jazz_later.id]);
+
+        let by_name_and_dates = db.search_events_filtered(&models::EventSearch {
+            name_query: Some("jazz".to_string()),
+            from: Some(soon - Duration::hours(1)),
+            to: Some(soon + Duration::days(7)),
+            ..Default::default()
+        }, false, OffsetDateTime::now_utc()).await.unwrap();
+        assert_eq!(ids(by_name_and_dates), vec![jazz_soon.id, jazz_soon_full.id]);
+
+        let available = db.search_events_filtered(&models::EventSearch {
+            name_query: Some("jazz".to_string()),
+            from: Some(soon - Duration::hours(1)),
+            to: Some(soon + Duration::days(7)),
+            only_available: true,
+            ..Default::default()
+        }, false, OffsetDateTime::now_utc()).await.unwrap();
+        assert_eq!(ids(available), vec![jazz_soon.id]);
+    }
+
+    #[tokio::test]
+    async fn test_search_events_filtered_matches_wildcards_literally_and_counts_like_reserve() {
+        let db = setup_test_db().await;
+        let discounted = db.create_event(models::NewEvent {
+            name: "50% Off Night".to_string(),
+            location: Some("Hall_A".to_string()),
+            ..test_new_event(2)
+        }).await.unwrap();
+        let pending_only = db.create_event(models::NewEvent { name: "Pending Night".to_string(), ..test_new_event(2) }).await.unwrap();
+        db.insert_reservation(
+            models::CreatingReservation::prepare(pending_only.id, "Jane".to_string(), "jane@example.com".to_string(), 2, None)
+        ).await.unwrap();
+        let held = db.create_event(models::NewEvent { name: "Held Night".to_string(), ..test_new_event(2) }).await.unwrap();
+        let now = OffsetDateTime::now_utc();
+        db.insert_hold(models::HoldingReservation::place(held.id, 2, now, Duration::minutes(5))).await.unwrap();
+
+        let ids = |events: Vec<models::OpenEvent>| events.into_iter().map(|e| e.id).collect::<Vec<_>>();
+
+        // `%` and `_` only match themselves
+        let percent = db.search_events_filtered(&models::EventSearch { name_query: Some("%".to_string()), ..Default::default() }, false, now).await.unwrap();
+        assert_eq!(ids(percent), vec![discounted.id]);
+        let underscore = db.search_events_filtered(&models::EventSearch { location: Some("_".to_string()), ..Default::default() }, false, now).await.unwrap();
+        assert_eq!(ids(underscore), vec![discounted.id]);
+
+        // Held spots always count; pending ones only when they count toward capacity
+        let available = db.search_events_filtered(&models::EventSearch { only_available: true, ..Default::default() }, false, now).await.unwrap();
+        assert_eq!(ids(available), vec![discounted.id, pending_only.id]);
+        let available = db.search_events_filtered(&models::EventSearch { only_available: true, ..Default::default() }, true, now).await.unwrap();
+        assert_eq!(ids(available), vec![discounted.id]);
+    }
+
+    #[tokio::test]
+    async fn test_finish_past_events() {
+        let db = setup_test_db().await;
+        let upcoming = create_test_event(&db, 50).await;
+
+        let start_time = OffsetDateTime::now_utc() - Duration::hours(3);
+        let past = db.create_event(models::NewEvent {
+            start_time,
+            end_time: start_time + Duration::hours(2),
+            ..test_new_event(50)
+        }).await.unwrap();
+        create_confirmed_reservation(&db, past.id, "john@example.com", 2).await;
+
+        let finished = db.finish_past_events().await.unwrap();
+        assert_eq!(finished.len(), 1);
+        assert_eq!(finished[0].id, past.id);
+
+        let stored = db.get_finished_event_by_id(&past.id).await.unwrap();
+        assert_eq!(stored.id, past.id);
+        assert!(matches!(db.get_open_event_by_id(&past.id).await, Err(DatabaseError::EventNotFound)));
+        assert!(db.get_tokens_for_event(&past.id, None).await.unwrap().iter().all(|t| t.is_expired()));
+
+        // Upcoming events stay open and a second run is a no-op
+        assert!(db.get_open_event_by_id(&upcoming.id).await.is_ok());
+        assert!(db.finish_past_events().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_event_analytics() {
+        let db = setup_test_db().await;
+        let event = create_test_event(&db, 20).await;
+
+        let base = time::macros::datetime!(2026-03-01 09:15:00 UTC);
+        for (email, spots, verified_at) in [
+            ("a@example.com", 1, base),
+            ("b@example.com", 2, base + Duration::hours(1)),
+            ("c@example.com", 3, base + Duration::hours(1) + Duration::minutes(20)),
+            ("d@example.com", 6, base + Duration::hours(3)),
+        ] {
+            let confirmed = create_confirmed_reservation(&db, event.id, email, spots).await;
+            sqlx::query("UPDATE reservations SET verified_at = ? WHERE id = ?")
+                .bind(verified_at)
+                .bind(confirmed.id.to_string())
+                .execute(&db.pool)
+                .await
+                .unwrap();
+        }
+        // Pending reservations don't count
+        db.insert_reservation(
+            models::CreatingReservation::prepare(event.id, "Test User".to_string(), "e@example.com".to_string(), 5, None)
+        ).await.unwrap();
+
+        let analytics = db.get_event_analytics(&event.id).await.unwrap();
+        assert_eq!(analytics.confirmed_spots, 12);
+        assert!((analytics.fill_rate - 0.6).abs() < f64::EPSILON);
+        assert_eq!(analytics.average_party_size, Some(3.0));
+        assert_eq!(analytics.peak_hour, Some(time::macros::datetime!(2026-03-01 10:00:00 UTC)));
+
+        // No confirmations yet: nothing to average
+        let empty = create_test_event(&db, 10).await;
+        let analytics = db.get_event_analytics(&empty.id).await.unwrap();
+        assert_eq!(analytics.confirmed_spots, 0);
+        assert_eq!(analytics.average_party_size, None);
+        assert_eq!(analytics.peak_hour, None);
+    }
+
+    #[tokio::test]
+    async fn test_confirm_reservation_advances_updated_at() {
+        let db = setup_test_db().await;
+        let event = create_test_event(&db, 50).await;
+
+        let pending = db.insert_reservation(
+            models::CreatingReservation::prepare(event.id, "Test User".to_string(), "john@example.com".to_string(), 1, None)
+        ).await.unwrap();
+        let created_at = pending.status.created_at;
+        let updated_at = pending.status.updated_at;
+
+        // Timestamps are stored with second precision
+        tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+
+        let confirmed = db.confirm_reservation(pending).await.unwrap();
+        let stored = db.get_confirmed_reservation_by_id(&confirmed.id).await.unwrap();
+
+        assert_eq!(stored.status.created_at, created_at);
+        assert!(stored.status.updated_at > updated_at);
+    }
+
+    /// Collects each span opened while it is the default subscriber, as `name field=value ...`
+    #[derive(Default)]
+    struct SpanRecorder {
+        spans: std::sync::Mutex<Vec<String>>,
+    }
+
+    struct SpanFields<'a>(&'a mut String);
+
+    impl tracing::field::Visit for SpanFields<'_> {
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+            self.0.push_str(&format!(" {}={:?}", field.name(), value));
+        }
+    }
+
+    impl tracing::Subscriber for SpanRecorder {
+        fn enabled(&self, _metadata: &tracing::Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+            let mut recorded = span.metadata().name().to_string();
+            span.record(&mut SpanFields(&mut recorded));
+            let mut spans = self.spans.lock().unwrap();
+            spans.push(recorded);
+            tracing::span::Id::from_u64(spans.len() as u64)
+        }
+
+        fn record(&self, _span: &tracing::span::Id, _values: &tracing::span::Record<'_>) {}
+        fn record_follows_from(&self, _span: &tracing::span::Id, _follows: &tracing::span::Id) {}
+        fn event(&self, _event: &tracing::Event<'_>) {}
+        fn enter(&self, _span: &tracing::span::Id) {}
+        fn exit(&self, _span: &tracing::span::Id) {}
+    }
+
+    #[tokio::test]
+    async fn test_lifecycle_spans_carry_reservation_ids() {
+        let recorder = std::sync::Arc::new(SpanRecorder::default());
+        let _guard = tracing::subscriber::set_default(recorder.clone());
+
+        let db = setup_test_db().await;
+        let event = create_test_event(&db, 50).await;
+        let confirmed = create_confirmed_reservation(&db, event.id, "john@example.com", 2).await;
+
+        // Instrumenting doesn't change what the methods do
+        assert_eq!(confirmed.spot_count, 2);
+        assert_eq!(db.get_confirmed_reservation_by_id(&confirmed.id).await.unwrap().status.reservation_tokens.len(), 2);
+
+        let ids = format!("reservation_id={} event_id={}", confirmed.id, event.id);
+        let spans = recorder.spans.lock().unwrap();
+        assert!(spans.contains(&format!("insert_reservation {}", ids)), "{:?}", spans);
+        assert!(spans.contains(&format!("confirm_reservation {}", ids)), "{:?}", spans);
+    }
+
+    #[tokio::test]
+    async fn test_confirm_regenerates_colliding_token() {
+        let db = setup_test_db().await;
+        let event = create_test_event(&db, 50).await;
+        let existing = create_confirmed_reservation(&db, event.id, "jane@example.com", 1).await;
+        let existing_token = existing.status.reservation_tokens[0].token().to_string();
+
+        let pending = db.insert_reservation(
+            models::CreatingReservation::prepare(event.id, "Test User".to_string(), "john@example.com".to_string(), 2, None)
+        ).await.unwrap();
+        let mut confirmed = pending.confirm(OffsetDateTime::now_utc());
+
+        // Force the first new token to collide with one already in the database
+        if let models::AnyReservationToken::Active(token) = &mut confirmed.status.reservation_tokens[0] {
+            token.token = existing_token.clone();
+        }
+
+        let confirmed = db.store_confirmed_reservation(confirmed).await.unwrap();
+        assert_ne!(confirmed.status.reservation_tokens[0].token(), existing_token);
+
+        let stored = db.get_confirmed_reservation_by_id(&confirmed.id).await.unwrap();
+        assert_eq!(stored.status.reservation_tokens.len(), 2);
+        assert!(stored.status.reservation_tokens.iter().all(|t| t.token() != existing_token));
+    }
+
+    #[tokio::test]
+    async fn test_confirm_activates_pre_issued_tokens() {
+        let db = setup_test_db().await;
+        let event = create_test_event(&db, 50).await;
+        let labels = vec!["Seat 1".to_string(), "Seat 2".to_string()];
+
+        let pending = db.insert_reservation(
+            models::CreatingReservation::prepare(event.id, "Test User".to_string(), "john@example.com".to_string(), 2, Some(labels))
+        ).await.unwrap();
+        let pre_issued = db.pre_issue_reservation_tokens(&pending).await.unwrap();
+        assert!(pre_issued.iter().all(models::AnyReservationToken::is_inactive));
+
+        // Pre-issued tokens don't scan or count as active before confirmation
+        assert!(db.get_tokens_for_event(&event.id, Some("active")).await.unwrap().is_empty());
+        assert!(db.get_reservation_token(pre_issued[0].token()).await.unwrap().is_inactive());
+
+        let confirmed = db.confirm_reservation(pending).await.unwrap();
+        let confirmed_tokens: Vec<&str> = confirmed.status.reservation_tokens.iter().map(|t| t.token()).collect();
+        let pre_issued_tokens: Vec<&str> = pre_issued.iter().map(|t| t.token()).collect();
+        assert_eq!(confirmed_tokens, pre_issued_tokens);
+        assert_eq!(confirmed.status.reservation_tokens[1].label(), Some("Seat 2"));
+
+        // Activated in place: no extra rows were inserted
+        let all_tokens = db.get_tokens_for_event(&event.id, None).await.unwrap();
+        assert_eq!(all_tokens.len(), 2);
+        assert!(all_tokens.iter().all(models::AnyReservationToken::is_active));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_pending_expires_pre_issued_tokens() {
+        let db = setup_test_db().await;
+        let event = create_test_event(&db, 50).await;
+
+        let pending = db.insert_reservation(
+            models::CreatingReservation::prepare(event.id, "Test User".to_string(), "john@example.com".to_string(), 2, None)
+        ).await.unwrap();
+        db.pre_issue_reservation_tokens(&pending).await.unwrap();
+
+        db.cancel_pending_reservation(pending).await.unwrap();
+
+        assert!(db.get_tokens_for_event(&event.id, Some("inactive")).await.unwrap().is_empty());
+        assert_eq!(db.get_tokens_for_event(&event.id, Some("expired")).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_confirm_twice_keeps_first_confirmation() {
+        let db = setup_test_db().await;
+        let event = create_test_event(&db, 50).await;
+
+        let pending = db.insert_reservation(
+            models::CreatingReservation::prepare(event.id, "Test User".to_string(), "john@example.com".to_string(), 2, None)
+        ).await.unwrap();
+
+        // Two verifications racing on the same link both loaded the pending reservation
+        let first = db.confirm_reservation(pending.clone()).await.unwrap();
+        let second = db.confirm_reservation(pending).await;
+        assert!(matches!(second, Err(DatabaseError::ReservationNotFound)));
+
+        let stored = db.get_confirmed_reservation_by_id(&first.id).await.unwrap();
+        assert_eq!(stored.status.cancellation_token, first.status.cancellation_token);
+        assert_eq!(db.get_tokens_for_event(&event.id, None).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_get_reservation_buckets() {
+        let db = setup_test_db().await;
+        let event = create_test_event(&db, 50).await;
+
+        let base = time::macros::datetime!(2026-03-01 09:15:00 UTC);
+        for (email, spots, verified_at) in [
+            ("a@example.com", 2, base),
+            ("b@example.com", 1, base + Duration::minutes(30)),
+            ("c@example.com", 3, base + Duration::hours(2)),
+            ("d@example.com", 4, base + Duration::days(1)),
+        ] {
+            let confirmed = create_confirmed_reservation(&db, event.id, email, spots).await;
+            sqlx::query("UPDATE reservations SET verified_at = ? WHERE id = ?")
+                .bind(verified_at)
+                .bind(confirmed.id.to_string())
+                .execute(&db.pool)
+                .await
+                .unwrap();
+        }
+
+        let hourly = db.get_reservation_buckets(&event.id, TimelineBucket::Hour).await.unwrap();
+        let hourly: Vec<(OffsetDateTime, u32)> = hourly.into_iter().map(|b| (b.bucket_start, b.spots)).collect();
+        assert_eq!(hourly, vec![
+            (time::macros::datetime!(2026-03-01 09:00:00 UTC), 3),
+            (time::macros::datetime!(2026-03-01 11:00:00 UTC), 3),
+            (time::macros::datetime!(2026-03-02 09:00:00 UTC), 4),
+        ]);
+
+        let daily = db.get_reservation_buckets(&event.id, TimelineBucket::Day).await.unwrap();
+        let daily: Vec<(OffsetDateTime, u32)> = daily.into_iter().map(|b| (b.bucket_start, b.spots)).collect();
+        assert_eq!(daily, vec![
+            (time::macros::datetime!(2026-03-01 00:00:00 UTC), 6),
+            (time::macros::datetime!(2026-03-02 00:00:00 UTC), 4),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_locked_writes_are_retried() {
+        use sqlx::sqlite::SqliteConnectOptions;
+        use std::str::FromStr;
+
+        // A file database, since in-memory ones don't lock the same way; no busy timeout, so a
+        // locked write fails straight away and only the retry can get it through
+        let path = std::env::temp_dir().join(format!("quick-res-busy-{}.db", Uuid::new_v4()));
+        let options = SqliteConnectOptions::from_str(&format!("sqlite:{}", path.display())).unwrap()
+            .create_if_missing(true)
+            .busy_timeout(std::time::Duration::ZERO);
+        let db = Database { pool: SqlitePool::connect_with(options).await.unwrap() };
+        sqlx::migrate!("./migrations").run(&db.pool).await.unwrap();
+        let event = create_test_event(&db, 10).await;
+        let prepare = |email: &str| models::CreatingReservation::prepare(event.id, "Test User".to_string(), email.to_string(), 1, None);
+
+        // Another writer holds the lock for longer than every retry: the write gives up as busy
+        let mut holder = db.pool.acquire().await.unwrap();
+        sqlx::query("BEGIN IMMEDIATE").execute(&mut *holder).await.unwrap();
+        assert!(matches!(db.insert_reservation(prepare("john@example.com")).await, Err(DatabaseError::Busy)));
+
+        // The lock is released part-way through the retries: the write succeeds
+        let release = tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(40)).await;
+            sqlx::query("COMMIT").execute(&mut *holder).await.unwrap();
+        });
+        let pending = db.insert_reservation(prepare("john@example.com")).await.unwrap();
+        release.await.unwrap();
+        assert_eq!(pending.user_email, "john@example.com");
+
+        db.pool.close().await;
+        let _ = std::fs::remove_file(&path);
+    }
 }
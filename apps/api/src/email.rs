@@ -1,6 +1,10 @@
-
+use async_trait::async_trait;
+use serde_json::json;
 use thiserror::Error;
 use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+use crate::config::Config;
 use crate::models;
 
 #[derive(Debug, Error)]
@@ -9,83 +13,360 @@ pub enum EmailError {
     SendFailure(String),
     #[error("Invalid email address: {0}")]
     InvalidEmail(String),
+    #[error("Email configuration error: {0}")]
+    Configuration(String),
 }
 
-/// Send a verification email with a token
-/// Currently logs to stdout, but designed to be pluggable for real email providers
-pub async fn send_verification(email: &str, token: &str) -> Result<(), EmailError> {
-    // Validate email format (basic validation)
-    if !is_valid_email(email) {
-        return Err(EmailError::InvalidEmail(email.to_string()));
+/// Which kind of email was sent, as recorded in the delivery log
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmailKind {
+    Verification,
+    Confirmation,
+    Cancellation,
+    Access,
+}
+
+impl EmailKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EmailKind::Verification => "verification",
+            EmailKind::Confirmation => "confirmation",
+            EmailKind::Cancellation => "cancellation",
+            EmailKind::Access => "access",
+        }
+    }
+}
+
+/// A fully rendered email, ready to be handed to a provider
+#[derive(Debug, Clone)]
+pub struct EmailMessage {
+    pub from_name: String,
+    pub from_email: String,
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Delivery backend for rendered emails (console, SMTP, SendGrid, ...)
+#[async_trait]
+pub trait EmailProvider: Send + Sync + std::fmt::Debug {
+    async fn send(&self, message: &EmailMessage) -> Result<(), EmailError>;
+}
+
+/// Logs emails to stdout instead of delivering them
+#[derive(Debug, Clone, Default)]
+pub struct ConsoleProvider;
+
+#[async_trait]
+impl EmailProvider for ConsoleProvider {
+    async fn send(&self, message: &EmailMessage) -> Result<(), EmailError> {
+        println!("=== EMAIL ===");
+        println!("From: {} <{}>", message.from_name, message.from_email);
+        println!("To: {}", message.to);
+        println!("Subject: {}", message.subject);
+        println!("Body:");
+        println!("{}", message.body);
+        println!("=============");
+        Ok(())
+    }
+}
+
+const SENDGRID_SEND_URL: &str = "https://api.sendgrid.com/v3/mail/send";
+
+/// Delivers emails through the SendGrid v3 mail/send API
+#[derive(Debug, Clone)]
+pub struct SendGridProvider {
+    api_key: String,
+    client: reqwest::Client,
+}
+
+impl SendGridProvider {
+    pub fn new(api_key: Option<String>) -> Result<Self, EmailError> {
+        let api_key = api_key
+            .filter(|key| !key.trim().is_empty())
+            .ok_or_else(|| EmailError::Configuration("SENDGRID_API_KEY is required when EMAIL_PROVIDER=sendgrid".to_string()))?;
+
+        Ok(Self {
+            api_key,
+            client: reqwest::Client::new(),
+        })
+    }
+}
+
+#[async_trait]
+impl EmailProvider for SendGridProvider {
+    async fn send(&self, message: &EmailMessage) -> Result<(), EmailError> {
+        let payload = json!({
+            "personalizations": [{ "to": [{ "email": message.to }] }],
+            "from": { "email": message.from_email, "name": message.from_name },
+            "subject": message.subject,
+            "content": [{ "type": "text/plain", "value": message.body }],
+        });
+
+        let response = self.client
+            .post(SENDGRID_SEND_URL)
+            .bearer_auth(&self.api_key)
+            .json(&payload)
+            .send()
+            .await
+            .map_err(|e| EmailError::SendFailure(e.to_string()))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(EmailError::SendFailure(format!("SendGrid responded with {}: {}", status, body)));
+        }
+
+        Ok(())
+    }
+}
+
+/// Gives up on a send that takes longer than `timeout`, so a hung provider connection can't hold a request open
+#[derive(Debug)]
+pub struct TimeoutProvider {
+    inner: Arc<dyn EmailProvider>,
+    timeout: Duration,
+}
+
+impl TimeoutProvider {
+    pub fn new(inner: Arc<dyn EmailProvider>, timeout: Duration) -> Self {
+        Self { inner, timeout }
     }
+}
 
+#[async_trait]
+impl EmailProvider for TimeoutProvider {
+    async fn send(&self, message: &EmailMessage) -> Result<(), EmailError> {
+        tokio::time::timeout(self.timeout, self.inner.send(message))
+            .await
+            .map_err(|_| EmailError::SendFailure("timeout".to_string()))?
+    }
+}
+
+/// Pick the delivery backend for the configured `EMAIL_PROVIDER`; providers without an implementation log to the console.
+/// Every provider is bounded by `EMAIL_SEND_TIMEOUT_SECONDS`.
+pub fn provider_from_config(config: &Config) -> Result<Arc<dyn EmailProvider>, EmailError> {
+    let provider: Arc<dyn EmailProvider> = match config.email_provider.to_lowercase().as_str() {
+        "sendgrid" => Arc::new(SendGridProvider::new(config.sendgrid_api_key())?),
+        _ => Arc::new(ConsoleProvider),
+    };
+
+    Ok(Arc::new(TimeoutProvider::new(provider, Duration::from_secs(config.email_send_timeout_seconds))))
+}
+
+/// Sender address: the given override, or `EMAIL_FROM`
+fn from_address(from_email: Option<&str>) -> String {
+    from_email
+        .map(str::to_string)
+        .unwrap_or_else(|| env::var("EMAIL_FROM").unwrap_or_else(|_| "noreply@quick-res.example.com".to_string()))
+}
+
+/// Link a guest follows to verify a pending reservation
+pub fn verification_url(app_url: &str, token: &str) -> String {
+    format!("{}/verify/{}", app_url, token)
+}
+
+/// Build the verification email containing the verification link, sent from `from_email` when given
+pub fn build_verification_email(email: &str, token: &str, from_email: Option<&str>) -> EmailMessage {
     // Get configuration from environment variables
     let app_url = env::var("APP_URL").unwrap_or_else(|_| "http://localhost:5173".to_string());
-    let email_from = env::var("EMAIL_FROM").unwrap_or_else(|_| "noreply@quick-res.example.com".to_string());
+    let email_from = from_address(from_email);
     let email_from_name = env::var("EMAIL_FROM_NAME").unwrap_or_else(|_| "Quick Reservations".to_string());
     let app_name = env::var("APP_NAME").unwrap_or_else(|_| "Quick Reservations".to_string());
     
-    // Build verification URL
-    let verification_url = format!("{}/verify/{}", app_url, token);
-
-    // For now, log to stdout - this will be replaced with actual email provider integration
-    println!("=== EMAIL VERIFICATION ===");
-    println!("From: {} <{}>", email_from_name, email_from);
-    println!("To: {}", email);
-    println!("Subject: Verify your email address for {}", app_name);
-    println!("Body:");
-    println!("Please verify your email address by clicking the following link:");
-    println!("{}", verification_url);
-    println!("If you did not request this verification, please ignore this email.");
-    println!("This link will expire in 24 hours for security reasons.");
-    println!("========================");
-
-    // Simulate potential email sending failure for testing
-    // In a real implementation, this would handle actual SMTP errors, API failures, etc.
-    Ok(())
-}
+    let verification_url = verification_url(&app_url, token);
 
-/// Send a confirmation email for a reservation
-/// Currently logs to stdout, but designed to be pluggable for real email providers
-pub async fn send_confirmation(email: &str, reservation: &models::ConfirmedReservation) -> Result<(), EmailError> {
-    // Validate email format (basic validation)
-    if !is_valid_email(email) {
-        return Err(EmailError::InvalidEmail(email.to_string()));
+    let body = [
+        "Please verify your email address by clicking the following link:".to_string(),
+        verification_url,
+        "If you did not request this verification, please ignore this email.".to_string(),
+        "This link will expire in 24 hours for security reasons.".to_string(),
+    ].join("\n");
+
+    EmailMessage {
+        from_name: email_from_name,
+        from_email: email_from,
+        to: email.to_string(),
+        subject: format!("Verify your email address for {}", app_name),
+        body,
     }
+}
 
+/// Build the confirmation email containing the reservation details and magic link, sent from `from_email` when given
+pub fn build_confirmation_email(email: &str, reservation: &models::ConfirmedReservation, from_email: Option<&str>) -> EmailMessage {
     // Get configuration from environment variables
     let app_url = env::var("APP_URL").unwrap_or_else(|_| "http://localhost:5173".to_string());
-    let email_from = env::var("EMAIL_FROM").unwrap_or_else(|_| "noreply@quick-res.example.com".to_string());
+    let base_url = env::var("BASE_URL").unwrap_or_else(|_| "http://localhost:8000".to_string());
+    let email_from = from_address(from_email);
     let email_from_name = env::var("EMAIL_FROM_NAME").unwrap_or_else(|_| "Quick Reservations".to_string());
     let app_name = env::var("APP_NAME").unwrap_or_else(|_| "Quick Reservations".to_string());
     
     // Build magic link URL
     let magic_link_url = format!("{}/retrieve/{}", app_url, reservation.id);
+    let cancellation_url = format!("{}/cancel/{}", app_url, reservation.status.cancellation_token);
+    // The pass is served by the API itself rather than the frontend
+    let wallet_pass_url = format!("{}/reservations/{}/pass.pkpass", base_url, reservation.id);
+
+    let body = [
+        format!("Dear {},", reservation.user_name),
+        String::new(),
+        "Your reservation has been confirmed!".to_string(),
+        format!("Reservation for {}", reservation.display_name()),
+        "Reservation Details:".to_string(),
+        format!("- Reservation ID: {}", reservation.id),
+        format!("- Event ID: {}", reservation.event_id),
+        format!("- Status: {}", reservation.status),
+        format!("- Created: {}", reservation.status.created_at),
+        String::new(),
+        "Access your reservation details at:".to_string(),
+        magic_link_url,
+        String::new(),
+        "Add your ticket to your wallet:".to_string(),
+        wallet_pass_url,
+        String::new(),
+        "Can't make it? Cancel your reservation at:".to_string(),
+        cancellation_url,
+        String::new(),
+        format!("Thank you for using {}!", app_name),
+    ].join("\n");
+
+    EmailMessage {
+        from_name: email_from_name,
+        from_email: email_from,
+        to: email.to_string(),
+        subject: format!("Reservation Confirmed - {}", app_name),
+        body,
+    }
+}
+
+/// Build the email telling someone the organizer cancelled their reservation
+pub fn build_cancellation_email(email: &str, user_name: &str, event_name: &str) -> EmailMessage {
+    let email_from = from_address(None);
+    let email_from_name = env::var("EMAIL_FROM_NAME").unwrap_or_else(|_| "Quick Reservations".to_string());
+    let app_name = env::var("APP_NAME").unwrap_or_else(|_| "Quick Reservations".to_string());
 
-    // For now, log to stdout - this will be replaced with actual email provider integration
-    println!("=== RESERVATION CONFIRMATION ===");
-    println!("From: {} <{}>", email_from_name, email_from);
-    println!("To: {}", email);
-    println!("Subject: Reservation Confirmed - {}", app_name);
-    println!("Body:");
-    println!("Dear {},", reservation.user_name);
-    println!("");
-    println!("Your reservation has been confirmed!");
-    println!("Reservation Details:");
-    println!("- Reservation ID: {}", reservation.id);
-    println!("- Event ID: {}", reservation.event_id);
-    println!("- Status: {}", reservation.status);
-    println!("- Created: {}", reservation.status.created_at);
-    println!("");
-    println!("Access your reservation details at:");
-    println!("{}", magic_link_url);
-    println!("");
-    println!("Thank you for using {}!", app_name);
-    println!("==============================");
-
-    // Simulate potential email sending failure for testing
-    // In a real implementation, this would handle actual SMTP errors, API failures, etc.
-    Ok(())
+    let body = [
+        format!("Dear {},", user_name),
+        String::new(),
+        format!("Your reservation for {} has been cancelled by the organizer.", event_name),
+        "Any tickets you were sent will no longer be accepted.".to_string(),
+        String::new(),
+        format!("Thank you for using {}!", app_name),
+    ].join("\n");
+
+    EmailMessage {
+        from_name: email_from_name,
+        from_email: email_from,
+        to: email.to_string(),
+        subject: format!("Reservation Cancelled - {}", event_name),
+        body,
+    }
+}
+
+/// Build the email carrying a short-lived link to every ticket held by the address
+pub fn build_access_email(email: &str, access_token: &str, valid_for_minutes: u64) -> EmailMessage {
+    let app_url = env::var("APP_URL").unwrap_or_else(|_| "http://localhost:5173".to_string());
+    let email_from = from_address(None);
+    let email_from_name = env::var("EMAIL_FROM_NAME").unwrap_or_else(|_| "Quick Reservations".to_string());
+    let app_name = env::var("APP_NAME").unwrap_or_else(|_| "Quick Reservations".to_string());
+
+    let tickets_url = format!("{}/my/tokens?access_token={}", app_url, access_token);
+
+    let body = [
+        "View all of your tickets at the following link:".to_string(),
+        tickets_url,
+        "If you did not request this link, please ignore this email.".to_string(),
+        format!("This link will expire in {} minutes for security reasons.", valid_for_minutes),
+    ].join("\n");
+
+    EmailMessage {
+        from_name: email_from_name,
+        from_email: email_from,
+        to: email.to_string(),
+        subject: format!("Your tickets - {}", app_name),
+        body,
+    }
+}
+
+/// Send a verification email with a token
+/// Logs to stdout; use `send_verification_via` to deliver through another provider
+#[cfg_attr(not(test), expect(dead_code))]
+pub async fn send_verification(email: &str, token: &str) -> Result<(), EmailError> {
+    send_verification_via(&ConsoleProvider, email, token, None).await
+}
+
+/// Send a verification email with a token through the given provider, from `from_email` when given
+pub async fn send_verification_via(provider: &dyn EmailProvider, email: &str, token: &str, from_email: Option<&str>) -> Result<(), EmailError> {
+    // Validate email format (basic validation)
+    if !is_valid_email(email) {
+        return Err(EmailError::InvalidEmail(email.to_string()));
+    }
+
+    provider.send(&build_verification_email(email, token, from_email)).await
+}
+
+/// Send a confirmation email for a reservation
+/// Logs to stdout; use `send_confirmation_via` to deliver through another provider
+#[cfg_attr(not(test), expect(dead_code))]
+pub async fn send_confirmation(email: &str, reservation: &models::ConfirmedReservation) -> Result<(), EmailError> {
+    send_confirmation_via(&ConsoleProvider, email, reservation, None).await
+}
+
+/// Send a confirmation email for a reservation through the given provider, from `from_email` when given
+pub async fn send_confirmation_via(provider: &dyn EmailProvider, email: &str, reservation: &models::ConfirmedReservation, from_email: Option<&str>) -> Result<(), EmailError> {
+    // Validate email format (basic validation)
+    if !is_valid_email(email) {
+        return Err(EmailError::InvalidEmail(email.to_string()));
+    }
+
+    provider.send(&build_confirmation_email(email, reservation, from_email)).await
+}
+
+/// Send a cancellation notice through the given provider
+pub async fn send_cancellation_via(provider: &dyn EmailProvider, email: &str, user_name: &str, event_name: &str) -> Result<(), EmailError> {
+    if !is_valid_email(email) {
+        return Err(EmailError::InvalidEmail(email.to_string()));
+    }
+
+    provider.send(&build_cancellation_email(email, user_name, event_name)).await
+}
+
+/// Send a ticket access link through the given provider
+pub async fn send_access_via(provider: &dyn EmailProvider, email: &str, access_token: &str, valid_for_minutes: u64) -> Result<(), EmailError> {
+    if !is_valid_email(email) {
+        return Err(EmailError::InvalidEmail(email.to_string()));
+    }
+
+    provider.send(&build_access_email(email, access_token, valid_for_minutes)).await
+}
+
+/// Domain part of an email address (after the `@`), as checked by email validation
+fn email_domain(email: &str) -> Option<&str> {
+    email.split_once('@').map(|(_, domain)| domain)
+}
+
+/// Form reservation emails are stored and looked up in, so addresses differing only by case match
+pub fn normalize_email(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+/// Hide all but the first character of the local part, e.g. `j***@example.com`, so support can
+/// recognise an address without the full address being shown
+pub fn mask_email(email: &str) -> String {
+    match email.split_once('@') {
+        Some((local, domain)) => {
+            let first: String = local.chars().take(1).collect();
+            format!("{}***@{}", first, domain)
+        }
+        None => "***".to_string(),
+    }
+}
+
+/// Whether an email's domain is one of the blocked domains (case-insensitive)
+pub fn is_blocked_domain(email: &str, blocked_domains: &[String]) -> bool {
+    email_domain(email).is_some_and(|domain| {
+        blocked_domains.iter().any(|blocked| blocked.eq_ignore_ascii_case(domain))
+    })
 }
 
 /// Basic email validation
@@ -120,6 +401,38 @@ mod tests {
     use time::OffsetDateTime;
     use uuid::Uuid;
 
+    #[test]
+    fn test_sendgrid_provider_requires_api_key() {
+        match SendGridProvider::new(None) {
+            Err(EmailError::Configuration(message)) => assert!(message.contains("SENDGRID_API_KEY")),
+            other => panic!("Expected configuration error, got {:?}", other),
+        }
+        assert!(matches!(SendGridProvider::new(Some("  ".to_string())), Err(EmailError::Configuration(_))));
+        assert!(SendGridProvider::new(Some("SG.key".to_string())).is_ok());
+    }
+
+    #[derive(Debug)]
+    struct HangingProvider;
+
+    #[async_trait]
+    impl EmailProvider for HangingProvider {
+        async fn send(&self, _message: &EmailMessage) -> Result<(), EmailError> {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_timeout_provider_gives_up_on_hung_send() {
+        let provider = TimeoutProvider::new(Arc::new(HangingProvider), Duration::from_millis(20));
+
+        let result = provider.send(&build_verification_email("test@example.com", "abc123", None)).await;
+        match result {
+            Err(EmailError::SendFailure(message)) => assert_eq!(message, "timeout"),
+            other => panic!("Expected timeout, got {:?}", other),
+        }
+    }
+
     #[tokio::test]
     async fn test_send_verification_valid_email() {
         let result = send_verification("test@example.com", "abc123").await;
@@ -145,11 +458,15 @@ mod tests {
             user_email: "john@example.com".to_string(),
             verification_token: models::VerificationToken::new(),
             spot_count: 1,
+            spot_labels: None,
+            answers: None,
+            party_name: None,
             status: models::Confirmed { 
                 verified_at: OffsetDateTime::now_utc(),
                 created_at: OffsetDateTime::now_utc(),
                 updated_at: OffsetDateTime::now_utc(),
                 reservation_tokens: vec![models::AnyReservationToken::from_active(models::ReservationToken::new(Uuid::new_v4(), OffsetDateTime::now_utc()))],
+                cancellation_token: models::VerificationToken::new().0,
             },
         };
 
@@ -166,11 +483,15 @@ mod tests {
             user_email: "john@example.com".to_string(),
             verification_token: models::VerificationToken::new(),
             spot_count: 1,
+            spot_labels: None,
+            answers: None,
+            party_name: None,
             status: models::Confirmed { 
                 verified_at: OffsetDateTime::now_utc(),
                 created_at: OffsetDateTime::now_utc(),
                 updated_at: OffsetDateTime::now_utc(),
                 reservation_tokens: vec![models::AnyReservationToken::from_active(models::ReservationToken::new(Uuid::new_v4(), OffsetDateTime::now_utc()))],
+                cancellation_token: models::VerificationToken::new().0,
             },
         };
 
@@ -13,6 +13,8 @@ pub enum AppError {
     Email(#[from] crate::email::EmailError),
     #[error("Validation error: {0}")]
     Validation(String),
+    #[error("Capacity exceeded: {0}")]
+    CapacityExceeded(String),
     #[error("Invalid UUID format: {0}")]
     InvalidUuid(#[from] uuid::Error),
     #[error("JSON parsing error: {0}")]
@@ -25,6 +27,10 @@ pub enum AppError {
     Forbidden,
     #[error("Conflict: {0}")]
     Conflict(String),
+    #[error("Too many requests: {0}")]
+    TooManyRequests(String),
+    #[error("Request timed out")]
+    Timeout,
     #[error("Internal server error")]
     InternalServerError,
 }
@@ -56,6 +62,7 @@ impl From<ValidationErrors> for AppError {
 // Implement IntoResponse for AppError
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
+        let code = self.code();
         let (status, error_message) = match self {
             // Database errors - map specific database errors to appropriate HTTP status codes
             AppError::Database(crate::db::DatabaseError::EventNotFound) => {
@@ -64,6 +71,27 @@ impl IntoResponse for AppError {
             AppError::Database(crate::db::DatabaseError::ReservationNotFound) => {
                 (StatusCode::NOT_FOUND, "Reservation not found".to_string())
             }
+            AppError::Database(crate::db::DatabaseError::ReservationTokenNotFound) => {
+                (StatusCode::NOT_FOUND, "Reservation token not found".to_string())
+            }
+            AppError::Database(crate::db::DatabaseError::DuplicateReservation) => {
+                (StatusCode::CONFLICT, "A reservation already exists for this email and event".to_string())
+            }
+            AppError::Database(crate::db::DatabaseError::HoldNotFound) => {
+                (StatusCode::NOT_FOUND, "Hold not found or expired".to_string())
+            }
+            AppError::Database(crate::db::DatabaseError::BackupConflict) => {
+                (StatusCode::CONFLICT, "Backup contains tokens that already exist".to_string())
+            }
+            AppError::Database(crate::db::DatabaseError::Busy) => {
+                (StatusCode::SERVICE_UNAVAILABLE, "The server is busy, please try again".to_string())
+            }
+            AppError::Database(crate::db::DatabaseError::TicketCodeRequired) => {
+                (StatusCode::CONFLICT, "This event only accepts scanned ticket codes".to_string())
+            }
+            AppError::Database(crate::db::DatabaseError::TicketCodeExpired) => {
+                (StatusCode::CONFLICT, "Ticket code has expired; refresh it and scan again".to_string())
+            }
             AppError::Database(_) => {
                 // Log the actual error but don't expose internal details to the client
                 eprintln!("Database error: {}", self);
@@ -81,6 +109,7 @@ impl IntoResponse for AppError {
             
             // Validation errors - client errors
             AppError::Validation(msg) => (StatusCode::BAD_REQUEST, msg),
+            AppError::CapacityExceeded(msg) => (StatusCode::BAD_REQUEST, msg),
             
             // Parsing errors - client errors
             AppError::InvalidUuid(_) => {
@@ -95,6 +124,8 @@ impl IntoResponse for AppError {
             AppError::Unauthorized => (StatusCode::UNAUTHORIZED, "Unauthorized".to_string()),
             AppError::Forbidden => (StatusCode::FORBIDDEN, "Forbidden".to_string()),
             AppError::Conflict(msg) => (StatusCode::CONFLICT, msg),
+            AppError::TooManyRequests(msg) => (StatusCode::TOO_MANY_REQUESTS, msg),
+            AppError::Timeout => (StatusCode::GATEWAY_TIMEOUT, "Request timed out".to_string()),
             AppError::InternalServerError => {
                 (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error".to_string())
             }
@@ -102,6 +133,7 @@ impl IntoResponse for AppError {
 
         let body = json!({
             "error": error_message,
+            "code": code,
             "status": status.as_u16()
         });
 
@@ -109,14 +141,47 @@ impl IntoResponse for AppError {
     }
 }
 
+impl AppError {
+    /// Stable machine-readable code for the error, so clients don't have to match on messages
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::Database(crate::db::DatabaseError::EventNotFound) => "event_not_found",
+            AppError::Database(crate::db::DatabaseError::ReservationNotFound) => "reservation_not_found",
+            AppError::Database(crate::db::DatabaseError::ReservationTokenNotFound) => "reservation_token_not_found",
+            AppError::Database(crate::db::DatabaseError::DuplicateReservation) => "duplicate_reservation",
+            AppError::Database(crate::db::DatabaseError::HoldNotFound) => "hold_not_found",
+            AppError::Database(crate::db::DatabaseError::BackupConflict) => "backup_conflict",
+            AppError::Database(crate::db::DatabaseError::Busy) => "database_busy",
+            AppError::Database(crate::db::DatabaseError::TicketCodeRequired) => "ticket_code_required",
+            AppError::Database(crate::db::DatabaseError::TicketCodeExpired) => "ticket_code_expired",
+            AppError::Database(_) => "internal_error",
+            AppError::Email(crate::email::EmailError::InvalidEmail(_)) => "invalid_email",
+            AppError::Email(_) => "email_send_failed",
+            AppError::Validation(_) => "validation_failed",
+            AppError::CapacityExceeded(_) => "capacity_exceeded",
+            AppError::InvalidUuid(_) => "invalid_uuid",
+            AppError::JsonParsing(_) => "invalid_json",
+            AppError::NotFound => "not_found",
+            AppError::Unauthorized => "unauthorized",
+            AppError::Forbidden => "forbidden",
+            AppError::Conflict(_) => "conflict",
+            AppError::TooManyRequests(_) => "too_many_requests",
+            AppError::Timeout => "timeout",
+            AppError::InternalServerError => "internal_error",
+        }
+    }
+}
+
 // Helper methods for creating common errors
 impl AppError {
     /// Create a validation error from a message
+    #[cfg_attr(not(test), expect(dead_code))]
     pub fn validation<T: Into<String>>(msg: T) -> Self {
         AppError::Validation(msg.into())
     }
     
     /// Create a conflict error with a message
+    #[cfg_attr(not(test), expect(dead_code))]
     pub fn conflict<T: Into<String>>(msg: T) -> Self {
         AppError::Conflict(msg.into())
     }
@@ -127,16 +192,19 @@ impl AppError {
     }
     
     /// Create an unauthorized error
+    #[cfg_attr(not(test), expect(dead_code))]
     pub fn unauthorized() -> Self {
         AppError::Unauthorized
     }
     
     /// Create a forbidden error
+    #[cfg_attr(not(test), expect(dead_code))]
     pub fn forbidden() -> Self {
         AppError::Forbidden
     }
     
     /// Create an internal server error
+    #[cfg_attr(not(test), expect(dead_code))]
     pub fn internal_server_error() -> Self {
         AppError::InternalServerError
     }
@@ -147,8 +215,7 @@ mod tests {
     use super::*;
     use axum::http::StatusCode;
     use axum::response::IntoResponse;
-    use validator::{Validate, ValidationErrors};
-    use serde_json::json;
+    use validator::Validate;
     
     #[derive(Debug, Validate)]
     struct TestStruct {
@@ -260,4 +327,18 @@ mod tests {
             _ => panic!("Expected Conflict error"),
         }
     }
+
+    #[tokio::test]
+    async fn test_error_body_includes_code() {
+        use http_body_util::BodyExt;
+
+        let response = AppError::from(crate::db::DatabaseError::EventNotFound).into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["code"], "event_not_found");
+        assert_eq!(body["error"], "Event not found");
+        assert_eq!(body["status"], 404);
+    }
 }
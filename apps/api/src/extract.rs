@@ -0,0 +1,40 @@
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{FromRequest, Request};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+
+use crate::error::AppError;
+
+/// `Json` extractor that reports unparseable timestamps as a validation error naming the field,
+/// rather than serde's opaque deserialization message
+#[derive(Debug, Clone)]
+pub struct AppJson<T>(pub T);
+
+impl<T, S> FromRequest<S> for AppJson<T>
+where
+    Json<T>: FromRequest<S, Rejection = JsonRejection>,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        match Json::<T>::from_request(req, state).await {
+            Ok(Json(value)) => Ok(AppJson(value)),
+            Err(JsonRejection::JsonDataError(error)) => match timestamp_field(&error.body_text()) {
+                Some(field) => Err(AppError::Validation(format!("{} must be ISO8601", field)).into_response()),
+                None => Err(error.into_response()),
+            },
+            Err(rejection) => Err(rejection.into_response()),
+        }
+    }
+}
+
+/// The field a data error points at, when it is one of our timestamp fields (`*_time` / `*_at`).
+/// The message reads "Failed to deserialize ...: <path>: <serde error>", e.g. `event.start_time: ...`.
+fn timestamp_field(message: &str) -> Option<String> {
+    let (_, detail) = message.split_once(": ")?;
+    let (path, _) = detail.split_once(": ")?;
+    let field = path.rsplit('.').next()?;
+
+    (field.ends_with("_time") || field.ends_with("_at")).then(|| field.to_string())
+}
@@ -0,0 +1,85 @@
+use time::{OffsetDateTime, UtcOffset};
+
+/// Content type calendar apps expect for `.ics` downloads and subscriptions
+pub const CALENDAR_CONTENT_TYPE: &str = "text/calendar; charset=utf-8";
+
+/// Lines longer than this many octets are folded onto continuation lines (RFC 5545 §3.1)
+const MAX_LINE_OCTETS: usize = 75;
+
+/// What goes into a calendar VEVENT
+#[derive(Debug)]
+pub struct CalendarEvent<'a> {
+    /// Stable across downloads so calendar apps update the entry rather than adding a new one
+    pub uid: String,
+    pub summary: &'a str,
+    pub description: Option<&'a str>,
+    pub location: Option<&'a str>,
+    pub start_time: OffsetDateTime,
+    pub end_time: OffsetDateTime,
+    /// Set when the event has been cancelled, so subscribers see it struck through
+    pub cancelled: bool,
+}
+
+/// Build a VCALENDAR holding a single VEVENT
+pub fn build_calendar(product_name: &str, event: &CalendarEvent, generated_at: OffsetDateTime) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:-//{}//EN", escape_text(product_name)),
+        "CALSCALE:GREGORIAN".to_string(),
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}", event.uid),
+        format!("DTSTAMP:{}", format_utc(generated_at)),
+        format!("DTSTART:{}", format_utc(event.start_time)),
+        format!("DTEND:{}", format_utc(event.end_time)),
+        format!("SUMMARY:{}", escape_text(event.summary)),
+    ];
+    if let Some(description) = event.description {
+        lines.push(format!("DESCRIPTION:{}", escape_text(description)));
+    }
+    if let Some(location) = event.location {
+        lines.push(format!("LOCATION:{}", escape_text(location)));
+    }
+    lines.push(format!("STATUS:{}", if event.cancelled { "CANCELLED" } else { "CONFIRMED" }));
+    lines.push("END:VEVENT".to_string());
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|line| fold_line(line) + "\r\n").collect()
+}
+
+/// UTC "form #2" date-time, e.g. `20250101T180000Z`
+fn format_utc(at: OffsetDateTime) -> String {
+    let at = at.to_offset(UtcOffset::UTC);
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+        at.year(), at.month() as u8, at.day(), at.hour(), at.minute(), at.second()
+    )
+}
+
+/// Escape the characters TEXT values can't contain verbatim
+fn escape_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Split a content line into 75-octet chunks joined by CRLF + space, never splitting a UTF-8 character
+fn fold_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut line_octets = 0;
+
+    for ch in line.chars() {
+        if line_octets + ch.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            // The leading space counts toward the continuation line's length
+            line_octets = 1;
+        }
+        folded.push(ch);
+        line_octets += ch.len_utf8();
+    }
+
+    folded
+}
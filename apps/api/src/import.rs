@@ -0,0 +1,122 @@
+use serde::Deserialize;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+use crate::models;
+
+/// One row of an events CSV; times are RFC 3339 and blank optional cells mean "not set"
+#[derive(Debug, Deserialize)]
+struct EventCsvRow {
+    name: String,
+    description: Option<String>,
+    location: Option<String>,
+    start_time: String,
+    end_time: String,
+    capacity: String,
+    max_spots_per_reservation: Option<String>,
+    min_spots_per_reservation: Option<String>,
+}
+
+/// Parse and validate an events CSV with a header row, collecting every bad row as
+/// `Line N: problem` so organizers can fix the whole sheet in one pass
+pub fn parse_events_csv(body: &str, max_capacity: u32) -> Result<Vec<models::NewEvent>, Vec<String>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(body.as_bytes());
+
+    let headers = reader.headers()
+        .map_err(|e| vec![format!("Line 1: {}", e)])?
+        .clone();
+
+    let mut events = Vec::new();
+    let mut errors = Vec::new();
+
+    for record in reader.records() {
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                let line = e.position().map_or(0, |position| position.line());
+                errors.push(format!("Line {}: {}", line, e));
+                continue;
+            }
+        };
+        let line = record.position().map_or(0, |position| position.line());
+
+        let parsed = record
+            .deserialize::<EventCsvRow>(Some(&headers))
+            .map_err(|e| e.to_string())
+            .and_then(|row| new_event_from_row(row, max_capacity));
+
+        match parsed {
+            Ok(event) => events.push(event),
+            Err(problem) => errors.push(format!("Line {}: {}", line, problem)),
+        }
+    }
+
+    if events.is_empty() && errors.is_empty() {
+        errors.push("CSV contains no events".to_string());
+    }
+
+    if errors.is_empty() {
+        Ok(events)
+    } else {
+        Err(errors)
+    }
+}
+
+fn new_event_from_row(row: EventCsvRow, max_capacity: u32) -> Result<models::NewEvent, String> {
+    if row.name.is_empty() {
+        return Err("name is required".to_string());
+    }
+
+    let start_time = OffsetDateTime::parse(&row.start_time, &Rfc3339)
+        .map_err(|_| format!("start_time must be an RFC 3339 timestamp, got {:?}", row.start_time))?;
+    let end_time = OffsetDateTime::parse(&row.end_time, &Rfc3339)
+        .map_err(|_| format!("end_time must be an RFC 3339 timestamp, got {:?}", row.end_time))?;
+    if end_time <= start_time {
+        return Err("end_time must be after start_time".to_string());
+    }
+
+    let capacity: u32 = row.capacity.parse()
+        .ok()
+        .filter(|capacity| *capacity > 0)
+        .ok_or_else(|| format!("capacity must be a positive whole number, got {:?}", row.capacity))?;
+
+    let max_spots_per_reservation = parse_optional_spots(row.max_spots_per_reservation, "max_spots_per_reservation")?;
+    let min_spots_per_reservation = parse_optional_spots(row.min_spots_per_reservation, "min_spots_per_reservation")?;
+
+    let new_event = models::NewEvent {
+        name: row.name,
+        description: row.description.filter(|cell| !cell.is_empty()),
+        start_time,
+        end_time,
+        capacity,
+        location: row.location.filter(|cell| !cell.is_empty()),
+        max_reservations: None,
+        max_spots_per_reservation,
+        min_spots_per_reservation,
+        visibility: models::EventVisibility::Public,
+        reservations_open_at: None,
+        reservations_close_at: None,
+        skip_verification: false,
+        require_name_match: false,
+        token_rotation_minutes: None,
+        series_id: None,
+    };
+    new_event.validate_capacity(max_capacity)?;
+    new_event.validate_spot_limits()?;
+
+    Ok(new_event)
+}
+
+/// A blank cell is unset; anything else must be a positive whole number of spots
+fn parse_optional_spots(cell: Option<String>, column: &str) -> Result<Option<u32>, String> {
+    match cell.filter(|cell| !cell.is_empty()) {
+        Some(cell) => cell.parse::<u32>()
+            .ok()
+            .filter(|spots| *spots > 0)
+            .map(Some)
+            .ok_or_else(|| format!("{} must be a positive whole number, got {:?}", column, cell)),
+        None => Ok(None),
+    }
+}
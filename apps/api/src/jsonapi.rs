@@ -0,0 +1,85 @@
+use axum::http::{header, HeaderMap};
+use serde_json::{json, Map, Value};
+
+use crate::api;
+
+/// Media type clients send in `Accept` to ask for a JSON:API compound document
+pub const JSON_API_CONTENT_TYPE: &str = "application/vnd.api+json";
+
+/// Whether the request's `Accept` header asks for JSON:API
+pub fn accepts_json_api(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|media_type| media_type.split(';').next().unwrap_or_default().trim().eq_ignore_ascii_case(JSON_API_CONTENT_TYPE))
+}
+
+/// A retrieved reservation as a compound document: the reservation is `data`, and its event and
+/// tokens are separate resources in `included`, linked from the reservation's `relationships`
+pub fn reservation_document(result: &api::RetrieveReservationResult) -> Value {
+    let mut reservation = attributes(result);
+    let reservation_id = take_id(&mut reservation, "reservation_id");
+
+    let mut event = reservation.remove("event").and_then(into_object).unwrap_or_default();
+    let event_id = take_id(&mut event, "id");
+
+    let mut relationships = Map::new();
+    relationships.insert("event".to_string(), json!({ "data": { "type": "event", "id": event_id } }));
+    let mut included = vec![resource("event", &event_id, event, None)];
+
+    // Pending summaries carry no tokens, so there's nothing to link
+    if let Some(tokens) = reservation.remove("reservation_tokens").and_then(into_array) {
+        let mut linkage = Vec::with_capacity(tokens.len());
+        for token in tokens {
+            let mut token = into_object(token).unwrap_or_default();
+            let token_id = take_id(&mut token, "token");
+            token.remove("reservation_id");
+            linkage.push(json!({ "type": "reservation_token", "id": token_id }));
+            included.push(resource("reservation_token", &token_id, token, None));
+        }
+        relationships.insert("reservation_tokens".to_string(), json!({ "data": linkage }));
+    }
+
+    json!({
+        "data": resource("reservation", &reservation_id, reservation, Some(relationships)),
+        "included": included,
+    })
+}
+
+/// A resource object; its identifying field has already been taken out of `attributes`
+fn resource(kind: &str, id: &str, attributes: Map<String, Value>, relationships: Option<Map<String, Value>>) -> Value {
+    let mut resource = json!({ "type": kind, "id": id, "attributes": attributes });
+    if let Some(relationships) = relationships {
+        resource["relationships"] = Value::Object(relationships);
+    }
+    resource
+}
+
+fn attributes(result: &api::RetrieveReservationResult) -> Map<String, Value> {
+    serde_json::to_value(result).ok().and_then(into_object).unwrap_or_default()
+}
+
+/// Remove the field holding a resource's id, returning it as the string JSON:API ids must be
+fn take_id(attributes: &mut Map<String, Value>, field: &str) -> String {
+    match attributes.remove(field) {
+        Some(Value::String(id)) => id,
+        Some(other) => other.to_string(),
+        None => String::new(),
+    }
+}
+
+fn into_object(value: Value) -> Option<Map<String, Value>> {
+    match value {
+        Value::Object(object) => Some(object),
+        _ => None,
+    }
+}
+
+fn into_array(value: Value) -> Option<Vec<Value>> {
+    match value {
+        Value::Array(array) => Some(array),
+        _ => None,
+    }
+}
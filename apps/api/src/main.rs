@@ -1,13 +1,16 @@
 use axum::{
-    extract::{Path, State},
-    response::Json,
-    routing::{get, post},
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, HeaderName, HeaderValue, Method, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Json, Response},
+    routing::{get, patch, post},
     Router,
 };
 use tower_http::{
     cors::CorsLayer,
     trace::TraceLayer,
 };
+use std::sync::Arc;
 use uuid::Uuid;
 use validator::Validate;
 use time::{Duration, OffsetDateTime};
@@ -18,32 +21,124 @@ mod email;
 mod error;
 mod models;
 mod api;
+mod auth;
+mod import;
+mod backup;
+mod rate_limit;
+mod extract;
+mod pass;
+mod qr;
+mod qr_sheet;
+mod ics;
+mod jsonapi;
 
+use auth::AdminAuth;
+use config::Config;
 use db::{Database, DatabaseError};
-use email::{EmailError};
+use email::{EmailError, EmailProvider};
 use error::AppError;
+use extract::AppJson;
+
+/// How many verification emails a bulk resend sends concurrently
+const VERIFICATION_RESEND_BATCH_SIZE: usize = 5;
 
 // Email sender component
 #[derive(Clone, Debug)]
 struct EmailSender {
-    // In a real implementation, this would contain SMTP configuration,
-    // API keys for email services, etc.
-    sender_name: String,
+    provider: Arc<dyn EmailProvider>,
+    log_pool: Option<sqlx::Pool<sqlx::Sqlite>>,
+    verification_from: Option<String>,
+    confirmation_from: Option<String>,
 }
 
 impl EmailSender {
-    fn new() -> Self {
+    fn with_provider(provider: Arc<dyn EmailProvider>) -> Self {
         EmailSender {
-            sender_name: "Quick Reservations".to_string(),
+            provider,
+            log_pool: None,
+            verification_from: None,
+            confirmation_from: None,
+        }
+    }
+
+    /// Send verification and confirmation emails from their configured addresses
+    fn with_from_addresses(self, config: &Config) -> Self {
+        Self {
+            verification_from: Some(config.verification_email_from().to_string()),
+            confirmation_from: Some(config.confirmation_email_from().to_string()),
+            ..self
         }
     }
+
+    /// Record every send attempt in the `email_log` table
+    fn with_log(self, pool: sqlx::Pool<sqlx::Sqlite>) -> Self {
+        Self { log_pool: Some(pool), ..self }
+    }
     
     async fn send_verification(&self, email: &str, token: &str) -> Result<(), EmailError> {
-        email::send_verification(email, token).await
+        let result = email::send_verification_via(self.provider.as_ref(), email, token, self.verification_from.as_deref()).await;
+        self.log_attempt(email, email::EmailKind::Verification, &result).await;
+        result
     }
     
+    /// Re-send verification emails for many reservations, `VERIFICATION_RESEND_BATCH_SIZE` at a time so a
+    /// large event doesn't open hundreds of provider connections at once. Returns `(sent, failed)`.
+    async fn send_verifications(&self, reservations: Vec<models::PendingReservation>) -> (usize, usize) {
+        let (mut sent, mut failed) = (0, 0);
+
+        for batch in reservations.chunks(VERIFICATION_RESEND_BATCH_SIZE) {
+            let mut sends = tokio::task::JoinSet::new();
+            for reservation in batch {
+                let sender = self.clone();
+                let email = reservation.user_email.clone();
+                let token = reservation.verification_token.0.clone();
+                sends.spawn(async move { sender.send_verification(&email, &token).await });
+            }
+
+            while let Some(result) = sends.join_next().await {
+                match result {
+                    Ok(Ok(())) => sent += 1,
+                    Ok(Err(e)) => {
+                        eprintln!("Failed to resend verification email: {}", e);
+                        failed += 1;
+                    }
+                    Err(e) => {
+                        eprintln!("Verification resend task failed: {}", e);
+                        failed += 1;
+                    }
+                }
+            }
+        }
+
+        (sent, failed)
+    }
+
     async fn send_confirmation(&self, email: &str, reservation: &models::ConfirmedReservation) -> Result<(), EmailError> {
-        email::send_confirmation(email, reservation).await
+        let result = email::send_confirmation_via(self.provider.as_ref(), email, reservation, self.confirmation_from.as_deref()).await;
+        self.log_attempt(email, email::EmailKind::Confirmation, &result).await;
+        result
+    }
+
+    async fn send_cancellation(&self, email: &str, user_name: &str, event_name: &str) -> Result<(), EmailError> {
+        let result = email::send_cancellation_via(self.provider.as_ref(), email, user_name, event_name).await;
+        self.log_attempt(email, email::EmailKind::Cancellation, &result).await;
+        result
+    }
+
+    async fn send_access(&self, email: &str, access_token: &str, valid_for_minutes: u64) -> Result<(), EmailError> {
+        let result = email::send_access_via(self.provider.as_ref(), email, access_token, valid_for_minutes).await;
+        self.log_attempt(email, email::EmailKind::Access, &result).await;
+        result
+    }
+
+    async fn log_attempt(&self, email: &str, kind: email::EmailKind, result: &Result<(), EmailError>) {
+        let Some(pool) = &self.log_pool else { return };
+
+        // A logging failure shouldn't change the outcome of the send itself
+        let db = Database { pool: pool.clone() };
+        if let Err(e) = db.record_email_attempt(email, kind, result.as_ref().err()).await {
+            eprintln!("Failed to record email attempt: {}", e);
+        }
     }
 }
 
@@ -52,82 +147,475 @@ impl EmailSender {
 struct AppState {
     pool: sqlx::Pool<sqlx::Sqlite>,
     email_sender: EmailSender,
+    config: Config,
+    /// Throttles lookups that could be used to enumerate who has reserved
+    lookup_limiter: rate_limit::RateLimiter,
+}
+
+/// Spots still available on an event, sent with event lookups so clients can disable reserving before it fails
+const SPOTS_REMAINING_HEADER: &str = "x-spots-remaining";
+
+/// Event JSON plus the `X-Spots-Remaining` header, counted the same way reserve counts toward capacity
+async fn open_event_response(state: &AppState, event: models::OpenEvent) -> Result<Response, AppError> {
+    let db = Database { pool: state.pool.clone() };
+    let reserved = db.count_event_reservations(&event.id, state.config.count_pending_toward_capacity).await?;
+    let spots_remaining = event.capacity.saturating_sub(reserved);
+
+    Ok(([(SPOTS_REMAINING_HEADER, spots_remaining.to_string())], Json(api::OpenEventResponse::from(event))).into_response())
 }
 
 // Route handlers
 async fn get_event_by_id(
     Path(event_id): Path<String>,
     State(state): State<AppState>,
-) -> Result<Json<api::OpenEventResponse>, AppError> {
-    let event_id = Uuid::parse_str(&event_id).map_err(|_| AppError::not_found())?;
+    admin: Option<AdminAuth>,
+) -> Result<Response, AppError> {
+    // The router can't match a `{id}.ics` segment on its own, so the calendar feed is served from here
+    if let Some(event_id) = event_id.strip_suffix(".ics") {
+        return get_event_calendar(event_id, &state, admin).await;
+    }
+
+    let event_id = parse_path_id(&event_id)?;
 
     let db = Database { pool: state.pool.clone() };
     let event = db.get_open_event_by_id(&event_id).await?;
 
-    let response = api::OpenEventResponse {
-        id: event.id,
-        name: event.name,
-        description: event.description,
-        start_time: event.start_time,
-        end_time: event.end_time,
-        capacity: event.capacity,
-        location: event.location,
-        created_at: event.created_at,
-        updated_at: event.updated_at,
-        status: api::EventStatus::Open,
+    // Unlisted events are reachable by direct link; private events need an authenticated caller
+    if event.visibility == models::EventVisibility::Private && admin.is_none() {
+        return Err(AppError::Unauthorized);
+    }
+
+    open_event_response(&state, event).await
+}
+
+/// The event as a single-VEVENT calendar organizers can subscribe to. Unlike the JSON lookup this
+/// serves events in any state, so subscribers see a cancellation rather than the entry disappearing.
+async fn get_event_calendar(event_id: &str, state: &AppState, admin: Option<AdminAuth>) -> Result<Response, AppError> {
+    let event_id = parse_path_id(event_id)?;
+
+    let db = Database { pool: state.pool.clone() };
+    let event = db.get_event_by_id(&event_id).await?;
+
+    if event.visibility() == models::EventVisibility::Private && admin.is_none() {
+        return Err(AppError::Unauthorized);
+    }
+
+    let calendar = ics::build_calendar(&state.config.app_name, &ics::CalendarEvent {
+        uid: format!("{}@quick-res", event.id()),
+        summary: event.name(),
+        description: event.description(),
+        location: event.location(),
+        start_time: event.start_time(),
+        end_time: event.end_time(),
+        cancelled: event.status() == models::EventStatus::Cancelled,
+    }, OffsetDateTime::now_utc());
+
+    Ok(([(header::CONTENT_TYPE, ics::CALENDAR_CONTENT_TYPE)], calendar).into_response())
+}
+
+async fn get_event_by_slug(
+    Path(slug): Path<String>,
+    State(state): State<AppState>,
+    admin: Option<AdminAuth>,
+) -> Result<Response, AppError> {
+    let db = Database { pool: state.pool.clone() };
+    let event = db.get_open_event_by_slug(&slug).await?;
+
+    // Same visibility rules as lookups by id
+    if event.visibility == models::EventVisibility::Private && admin.is_none() {
+        return Err(AppError::Unauthorized);
+    }
+
+    open_event_response(&state, event).await
+}
+
+async fn update_event_status(
+    Path(event_id): Path<String>,
+    State(state): State<AppState>,
+    _admin: AdminAuth,
+    Json(payload): Json<api::UpdateEventStatusRequest>,
+) -> Result<Json<api::EventStatusResponse>, AppError> {
+    let event_id = parse_path_id(&event_id)?;
+
+    let db = Database { pool: state.pool.clone() };
+    let current = db.get_event_status(&event_id).await?;
+    let next: models::EventStatus = payload.status.into();
+
+    if !current.can_transition_to(next) {
+        return Err(AppError::Conflict(format!("Cannot change event status from {} to {}", current, next)));
+    }
+    if current != next {
+        db.set_event_status(&event_id, next).await?;
+    }
+
+    Ok(Json(api::EventStatusResponse { event_id, status: next.into() }))
+}
+
+/// Stop taking reservations before the event is full, e.g. to hold seats back
+async fn close_event_sales(
+    Path(event_id): Path<String>,
+    State(state): State<AppState>,
+    _admin: AdminAuth,
+) -> Result<Json<api::SalesStatusResponse>, AppError> {
+    set_event_sales_closed(&event_id, &state, true).await
+}
+
+async fn reopen_event_sales(
+    Path(event_id): Path<String>,
+    State(state): State<AppState>,
+    _admin: AdminAuth,
+) -> Result<Json<api::SalesStatusResponse>, AppError> {
+    set_event_sales_closed(&event_id, &state, false).await
+}
+
+async fn set_event_sales_closed(event_id: &str, state: &AppState, sales_closed: bool) -> Result<Json<api::SalesStatusResponse>, AppError> {
+    let event_id = parse_path_id(event_id)?;
+
+    let db = Database { pool: state.pool.clone() };
+    db.set_event_sales_closed(&event_id, sales_closed).await?;
+
+    Ok(Json(api::SalesStatusResponse { event_id, sales_closed }))
+}
+
+async fn search_events(
+    Query(query): Query<api::EventSearchQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<api::OpenEventResponse>>, AppError> {
+    let db = Database { pool: state.pool.clone() };
+
+    let search = models::EventSearch {
+        name_query: query.q.filter(|q| !q.trim().is_empty()),
+        from: query.from,
+        to: query.to,
+        location: query.location.filter(|location| !location.trim().is_empty()),
+        only_available: query.only_available,
     };
-    
-    Ok(Json(response))
+    let events = db.search_events_filtered(&search, state.config.count_pending_toward_capacity, OffsetDateTime::now_utc()).await?;
+
+    Ok(Json(events.into_iter().map(Into::into).collect()))
 }
 
+#[tracing::instrument(skip_all, fields(event_id = %payload.event_id, reservation_id = tracing::field::Empty))]
 async fn reserve(
     State(state): State<AppState>,
+    admin: Option<AdminAuth>,
     Json(payload): Json<api::ReserveRequest>,
 ) -> Result<Json<api::ReserveResponse>, AppError> {
     // Validate payload using the From<ValidationErrors> implementation
     payload.validate()?;
+
+    if email::is_blocked_domain(&payload.user_email, &state.config.blocked_email_domains) {
+        return Err(AppError::Validation("Email domain not allowed".to_string()));
+    }
     
     let db = Database { pool: state.pool.clone() };
     
     // Check if event exists and has capacity
     let event = db.get_open_event_by_id(&payload.event_id).await?;
-    let current_count = db.count_event_reservations(&event.id).await?;
+
+    // Private events only take reservations from authenticated callers, as with looking them up
+    if event.visibility == models::EventVisibility::Private && admin.is_none() {
+        return Err(AppError::Unauthorized);
+    }
+
+    if event.sales_closed {
+        return Err(AppError::Validation("Sales closed".to_string()));
+    }
+
+    let now = OffsetDateTime::now_utc();
+    if event.reservations_open_at.is_some_and(|open_at| now < open_at) {
+        return Err(AppError::Validation("Reservations not yet open".to_string()));
+    }
+    if event.reservations_close_at.is_some_and(|close_at| now >= close_at) {
+        return Err(AppError::Validation("Reservations closed".to_string()));
+    }
+
+    // Some venues cap the number of parties independently of the total spots
+    if let Some(max_reservations) = event.max_reservations {
+        let party_count = db.count_event_parties(&event.id).await?;
+        if party_count >= max_reservations {
+            return Err(AppError::CapacityExceeded("Event has reached its maximum number of reservations".to_string()));
+        }
+    }
+
+    if let Some(max_spots) = event.max_spots_per_reservation {
+        if payload.spot_count > max_spots {
+            return Err(AppError::Validation(format!("Cannot reserve more than {} spots per reservation for this event", max_spots)));
+        }
+    }
+
+    // Group-only events set a minimum party size
+    if let Some(min_spots) = event.min_spots_per_reservation {
+        if payload.spot_count < min_spots {
+            return Err(AppError::Validation(format!("Must reserve at least {} spots per reservation for this event", min_spots)));
+        }
+    }
+
+    if let Some(labels) = &payload.labels {
+        if labels.len() != payload.spot_count as usize {
+            return Err(AppError::Validation("Number of labels must match spot count".to_string()));
+        }
+    }
+
+    let questions = db.get_event_questions(&event.id).await?;
+    models::EventQuestion::validate_answers(&questions, payload.answers.as_ref())
+        .map_err(AppError::Validation)?;
+
+    // Completing a hold: its spots are already set aside, so don't count them against us
+    let hold = match &payload.hold_token {
+        Some(hold_token) => {
+            let hold = db.get_active_hold_by_token(hold_token, now).await?;
+            if hold.event_id != event.id {
+                return Err(DatabaseError::HoldNotFound.into());
+            }
+            if hold.spot_count != payload.spot_count {
+                return Err(AppError::Validation("Spot count must match the held spots".to_string()));
+            }
+            Some(hold)
+        }
+        None => None,
+    };
+    let held_by_others = db.count_held_spots(&event.id, now).await?
+        - hold.as_ref().map_or(0, |hold| hold.spot_count);
+
+    let current_count = db.count_event_reservations(&event.id, state.config.count_pending_toward_capacity).await? + held_by_others;
     
     if current_count > event.capacity {
-        return Err(AppError::Validation("Event is at full capacity".to_string()));
+        return Err(AppError::CapacityExceeded("Event is at full capacity".to_string()));
     }
 
     if current_count + payload.spot_count > event.capacity {
-        return Err(AppError::Validation("Cannot reserve this many slots for this event".to_string()));
+        return Err(AppError::CapacityExceeded("Cannot reserve this many slots for this event".to_string()));
     }
     
+    if !event.skip_verification {
+        check_verification_email_throttle(&state, &db, &payload.user_email).await?;
+    }
+
     // Insert pending reservation
-    let reservation = db.insert_reservation(
-        models::CreatingReservation::prepare(payload.event_id, payload.user_name, payload.user_email, payload.spot_count)
-    ).await?;
+    let creating_reservation = models::CreatingReservation::prepare(payload.event_id, payload.user_name, payload.user_email, payload.spot_count, payload.labels)
+        .with_verification_token(models::VerificationToken::generate(state.config.verification_token_length))
+        .with_answers(payload.answers.filter(|answers| !answers.is_null()))
+        .with_party_name(payload.party_name.map(|name| name.trim().to_string()).filter(|name| !name.is_empty()));
+    let reservation = db.insert_reservation(creating_reservation).await?;
+    tracing::Span::current().record("reservation_id", tracing::field::display(reservation.id));
+    tracing::info!("reservation created");
+
+    // Large events can issue tokens up front so the rush of confirmations only has to activate them
+    if state.config.pre_issue_reservation_tokens {
+        db.pre_issue_reservation_tokens(&reservation).await?;
+    }
+
+    if let Some(hold) = hold {
+        db.release_hold(hold).await?;
+    }
     
+    // Trusted events skip email verification: confirm straight away and send the confirmation instead
+    if event.skip_verification {
+        let confirmed = db.confirm_reservation(reservation).await?;
+        tracing::info!("reservation confirmed without verification");
+        state.email_sender.send_confirmation(&confirmed.user_email, &confirmed).await?;
+
+        return Ok(Json(api::ReserveResponse {
+            reservation_id: confirmed.id,
+            status: confirmed.status.into(),
+            verification_expires_at: None,
+        }));
+    }
+
     // Send verification email with the verification token, not the reservation token
     state.email_sender.send_verification(&reservation.user_email, &reservation.verification_token.0).await?;
 
     let response = api::ReserveResponse {
         reservation_id: reservation.id,
-        status: reservation.status.into(),  
+        verification_expires_at: reservation.verification_expires_at(state.config.verification_ttl()),
+        status: reservation.status.into(),
     };
     
     Ok(Json(response))
 }
 
+/// Stop the reserve and resend endpoints being used to flood an inbox with verification emails
+async fn check_verification_email_throttle(state: &AppState, db: &Database, email: &str) -> Result<(), AppError> {
+    let limit = state.config.verification_emails_per_hour;
+    if limit == 0 {
+        return Ok(());
+    }
+
+    let since = OffsetDateTime::now_utc() - Duration::hours(1);
+    if db.count_emails_sent_since(email, email::EmailKind::Verification, since).await? >= limit {
+        return Err(AppError::TooManyRequests("Too many verification emails sent to this address, try again later".to_string()));
+    }
+
+    Ok(())
+}
+
+/// Lets a frontend recognise a returning guest without revealing anything else about their reservation
+async fn get_reservation_status(
+    Path(event_id): Path<String>,
+    Query(query): Query<api::ReservationStatusQuery>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    admin: Option<AdminAuth>,
+) -> Result<Json<api::ReservationStatusResponse>, AppError> {
+    if !state.lookup_limiter.check(&rate_limit::client_key(&headers)) {
+        return Err(AppError::TooManyRequests("Too many reservation lookups, try again later".to_string()));
+    }
+
+    let event_id = parse_path_id(&event_id)?;
+
+    let db = Database { pool: state.pool.clone() };
+    let event = db.get_event_by_id(&event_id).await?;
+    if event.visibility() == models::EventVisibility::Private && admin.is_none() {
+        return Err(AppError::Unauthorized);
+    }
+
+    let (status, cancelled_at) = db.get_reservation_status_for_email(&event_id, &query.email).await?.unzip();
+
+    Ok(Json(api::ReservationStatusResponse {
+        has_reservation: matches!(status.as_deref(), Some("pending" | "confirmed")),
+        status,
+        cancelled_at: cancelled_at.flatten(),
+    }))
+}
+
+/// Email a short-lived link to the address's tickets. The response is the same whether or not the
+/// address holds any, so this can't be used to find out who has reserved.
+async fn request_my_tokens_access(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Json(payload): Json<api::MyTokensAccessRequest>,
+) -> Result<StatusCode, AppError> {
+    if !state.lookup_limiter.check(&rate_limit::client_key(&headers)) {
+        return Err(AppError::TooManyRequests("Too many ticket lookups, try again later".to_string()));
+    }
+    payload.validate()?;
+
+    let db = Database { pool: state.pool.clone() };
+    if db.get_active_tokens_by_email(&payload.email).await?.is_empty() {
+        return Ok(StatusCode::ACCEPTED);
+    }
+
+    let ttl_minutes = state.config.my_tokens_access_ttl_minutes;
+    let access_token = models::VerificationToken::new_with_len(32).0;
+    let now = OffsetDateTime::now_utc();
+    db.insert_email_access_token(&payload.email, &access_token, now, now + Duration::minutes(ttl_minutes as i64)).await?;
+
+    // A failed send is only logged, so the response still doesn't depend on the address
+    if let Err(e) = state.email_sender.send_access(&payload.email, &access_token, ttl_minutes).await {
+        eprintln!("Failed to send ticket access email: {}", e);
+    }
+
+    Ok(StatusCode::ACCEPTED)
+}
+
+/// Every active ticket across an email's confirmed reservations, for a "my tickets" wallet view
+async fn get_my_tokens(
+    Query(query): Query<api::MyTokensQuery>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<api::MyTokenResponse>>, AppError> {
+    if !state.lookup_limiter.check(&rate_limit::client_key(&headers)) {
+        return Err(AppError::TooManyRequests("Too many ticket lookups, try again later".to_string()));
+    }
+
+    let db = Database { pool: state.pool.clone() };
+    if !db.email_access_token_is_valid(&query.email, &query.access_token, OffsetDateTime::now_utc()).await? {
+        return Err(AppError::Unauthorized);
+    }
+
+    let tokens = db.get_active_tokens_by_email(&query.email).await?;
+    Ok(Json(tokens.into_iter().map(api::MyTokenResponse::from).collect()))
+}
+
+async fn hold_spots(
+    Path(event_id): Path<String>,
+    State(state): State<AppState>,
+    admin: Option<AdminAuth>,
+    Json(payload): Json<api::HoldRequest>,
+) -> Result<Json<api::HoldResponse>, AppError> {
+    payload.validate()?;
+    let event_id = parse_path_id(&event_id)?;
+
+    let db = Database { pool: state.pool.clone() };
+    let event = db.get_open_event_by_id(&event_id).await?;
+
+    if event.visibility == models::EventVisibility::Private && admin.is_none() {
+        return Err(AppError::Unauthorized);
+    }
+
+    if let Some(max_spots) = event.max_spots_per_reservation {
+        if payload.spot_count > max_spots {
+            return Err(AppError::Validation(format!("Cannot reserve more than {} spots per reservation for this event", max_spots)));
+        }
+    }
+
+    // Group-only events set a minimum party size
+    if let Some(min_spots) = event.min_spots_per_reservation {
+        if payload.spot_count < min_spots {
+            return Err(AppError::Validation(format!("Must reserve at least {} spots per reservation for this event", min_spots)));
+        }
+    }
+
+    let now = OffsetDateTime::now_utc();
+    db.delete_expired_holds(now).await?;
+
+    let current_count = db.count_event_reservations(&event.id, state.config.count_pending_toward_capacity).await? + db.count_held_spots(&event.id, now).await?;
+    if current_count + payload.spot_count > event.capacity {
+        return Err(AppError::CapacityExceeded("Cannot reserve this many slots for this event".to_string()));
+    }
+
+    let ttl = Duration::seconds(state.config.reservation_hold_ttl_seconds as i64);
+    let hold = db.insert_hold(models::HoldingReservation::place(event.id, payload.spot_count, now, ttl)).await?;
+
+    Ok(Json(api::HoldResponse {
+        hold_token: hold.status.hold_token,
+        event_id: hold.event_id,
+        spot_count: hold.spot_count,
+        expires_at: hold.status.expires_at,
+    }))
+}
+
+async fn resend_verification_by_email(
+    State(state): State<AppState>,
+    Json(payload): Json<api::ResendVerificationRequest>,
+) -> Result<StatusCode, AppError> {
+    payload.validate()?;
+
+    let db = Database { pool: state.pool.clone() };
+    check_verification_email_throttle(&state, &db, &payload.user_email).await?;
+
+    // Always answer 202 so the endpoint can't be used to discover who has reserved
+    match db.get_pending_reservation_by_email(&payload.event_id, &payload.user_email).await {
+        Ok(pending) => {
+            // A failed send is already in the email log; surfacing it would reveal the reservation exists
+            if let Err(e) = state.email_sender.send_verification(&pending.user_email, &pending.verification_token.0).await {
+                eprintln!("Failed to resend verification email: {}", e);
+            }
+        }
+        Err(DatabaseError::ReservationNotFound) => {}
+        Err(e) => return Err(e.into()),
+    }
+
+    Ok(StatusCode::ACCEPTED)
+}
+
+#[tracing::instrument(skip_all, fields(reservation_id = tracing::field::Empty, event_id = tracing::field::Empty))]
 async fn verify_email(
     Path(token): Path<String>,
+    Query(query): Query<api::VerifyEmailQuery>,
     State(state): State<AppState>,
-) -> Result<Json<api::VerifyEmailResponse>, AppError> {
+) -> Result<Response, AppError> {
     let db = Database { pool: state.pool.clone() };
+
+    // Copied links often pick up stray whitespace or a trailing slash; the token itself is case-sensitive
+    let token = token.trim().trim_matches('/');
     
     // Find pending reservation by token
-    let pending_reservation = match db.get_pending_reservation_by_verification_token(&token).await {
+    let pending_reservation = match db.get_pending_reservation_by_verification_token(token).await {
         Ok(res) => res,
         Err(_) => {
-            match db.get_confirmed_reservation_by_verification_token(&token).await {
+            match db.get_confirmed_reservation_by_verification_token(token).await {
                 Ok(_) =>{
                     return Err(AppError::Validation("Reservation already confirmed".to_string()));
                 }
@@ -137,25 +625,51 @@ async fn verify_email(
             }
         }
     };
-    
+
     // Store data before moving the reservation into confirm_reservation
     let user_email = pending_reservation.user_email.clone();
     let event_id = pending_reservation.event_id;
     let reservation_id = pending_reservation.id;
+    tracing::Span::current()
+        .record("reservation_id", tracing::field::display(reservation_id))
+        .record("event_id", tracing::field::display(event_id));
     
-    // Confirm the reservation using type-safe state transition
-    let confirmed_reservation = db.confirm_reservation(pending_reservation).await?;
+    // Confirm the reservation using type-safe state transition; a concurrent verification may have got there first
+    let confirmed_reservation = match db.confirm_reservation(pending_reservation).await {
+        Ok(confirmed) => confirmed,
+        Err(DatabaseError::ReservationNotFound) => {
+            return Err(AppError::Validation("Reservation already confirmed".to_string()));
+        }
+        Err(e) => return Err(e.into()),
+    };
+    tracing::info!("reservation confirmed");
     
     // Send confirmation email
     state.email_sender.send_confirmation(&user_email, &confirmed_reservation).await?;
 
+    // Browsers following the email link can be sent on to the frontend's success page
+    if query.redirect {
+        if let Some(redirect_url) = &state.config.confirm_redirect_url {
+            let separator = if redirect_url.contains('?') { '&' } else { '?' };
+            let location = format!("{}{}reservation_id={}", redirect_url, separator, reservation_id);
+            return Ok((StatusCode::FOUND, [(header::LOCATION, location)]).into_response());
+        }
+    }
+
+    // Frontends can skip the follow-up retrieve call by asking for the full reservation straight away
+    if query.expand {
+        let (confirmed_reservation, event) = db.get_confirmed_reservation_with_event(&reservation_id).await?;
+        let response = confirmed_reservation_response(&state, &db, confirmed_reservation, event).await?;
+        return Ok(Json(response).into_response());
+    }
+
     let response = api::VerifyEmailResponse {
         event_id,
         reservation_id,
         verified_at: confirmed_reservation.status.verified_at,
     };
     
-    Ok(Json(response))
+    Ok(Json(response).into_response())
 }
 
 async fn generate_random_event(
@@ -208,121 +722,3412 @@ async fn generate_random_event(
     let seed = now.unix_timestamp() as usize;
     
     let name = event_names[seed % event_names.len()];
-    let location = Some(locations[seed % locations.len()]);
-    let description = Some(descriptions[seed % descriptions.len()]);
+    let location = locations[seed % locations.len()];
+    let description = descriptions[seed % descriptions.len()];
     
     // Set event to start in 24-48 hours and last 2-4 hours
     let start_time = now + Duration::hours(24 + (seed % 24) as i64);
     let end_time = start_time + Duration::hours(2 + (seed % 3) as i64);
-    let capacity = 20 + (seed % 80) as i32; // Random capacity between 20-100
+    let capacity_range = state.config.random_event_max_capacity - state.config.random_event_min_capacity + 1;
+    let capacity = state.config.random_event_min_capacity + (seed % capacity_range as usize) as u32;
     
     // Create the event
-    let event = db.create_event(
-        name,
-        description.as_deref(),
+    let event = db.create_event(models::NewEvent {
+        name: name.to_string(),
+        description: Some(description.to_string()),
         start_time,
         end_time,
         capacity,
-        location.as_deref(),
-    ).await?;
-    
-    let response = api::OpenEventResponse {
-        id: event.id,
-        name: event.name,
-        description: event.description,
-        start_time: event.start_time,
-        end_time: event.end_time,
-        capacity: event.capacity,
-        location: event.location,
-        created_at: event.created_at,
-        updated_at: event.updated_at,
-        status: api::EventStatus::Open,
-    };
+        location: Some(location.to_string()),
+        max_reservations: None,
+        max_spots_per_reservation: Some(state.config.random_event_max_spots),
+        min_spots_per_reservation: None,
+        visibility: models::EventVisibility::Public,
+        reservations_open_at: None,
+        reservations_close_at: None,
+        skip_verification: false,
+        require_name_match: false,
+        token_rotation_minutes: None,
+        series_id: None,
+    }).await?;
     
-    Ok(Json(response))
+    Ok(Json(event.into()))
 }
 
 async fn get_reservation_by_magic_token(
     Path(magic_token): Path<String>,
+    Query(query): Query<api::RetrieveReservationQuery>,
+    headers: HeaderMap,
     State(state): State<AppState>,
-) -> Result<Json<api::RetrieveReservationResponse>, AppError> {
+) -> Result<Response, AppError> {
+    let result = retrieve_reservation(&state, &magic_token, &query).await?;
+
+    // Frontends that prefer normalized responses ask for a JSON:API compound document instead
+    if jsonapi::accepts_json_api(&headers) {
+        let document = jsonapi::reservation_document(&result);
+        return Ok(([(header::CONTENT_TYPE, jsonapi::JSON_API_CONTENT_TYPE)], Json(document)).into_response());
+    }
+
+    Ok(Json(result).into_response())
+}
+
+async fn retrieve_reservation(
+    state: &AppState,
+    magic_token: &str,
+    query: &api::RetrieveReservationQuery,
+) -> Result<api::RetrieveReservationResult, AppError> {
     let db = Database { pool: state.pool.clone() };
     
-    // First, try to find a confirmed reservation by exact token match
-    let confirmed_reservation = match db.get_confirmed_reservation_by_reservation_token(&magic_token).await {
-        Ok(confirmed) => confirmed,
+    // First, try to find a confirmed reservation (with its event, whatever its status) by exact token match
+    let confirmed = match db.get_reservation_id_by_active_token(magic_token).await {
+        Ok(reservation_id) => db.get_confirmed_reservation_with_event(&reservation_id).await,
+        Err(e) => Err(e),
+    };
+
+    // Cancelled reservations keep answering, with a trimmed response saying when it happened
+    if confirmed.is_err() {
+        if let Ok(cancelled) = db.get_cancelled_reservation_by_token(magic_token).await {
+            let event = db.get_event_by_id(&cancelled.event_id).await?;
+            let spots_confirmed = db.count_event_reservations(&cancelled.event_id, false).await?;
+
+            return Ok(api::RetrieveReservationResult::Cancelled(api::CancelledReservationSummaryResponse {
+                reservation_id: cancelled.id,
+                created_at: cancelled.status.created_at,
+                cancelled_at: cancelled.status.cancelled_at,
+                status: cancelled.status.into(),
+                event: event.into_reservation_event_response(spots_confirmed),
+            }));
+        }
+    }
+
+    let (confirmed_reservation, event) = match confirmed {
+        Ok(found) => found,
+        Err(_) if query.include_pending => {
+            // Unverified reservations have no reservation tokens yet, so also accept the verification token
+            let pending = match db.get_pending_reservation_by_reservation_token(magic_token).await {
+                Ok(pending) => pending,
+                Err(_) => db.get_pending_reservation_by_verification_token(magic_token).await?,
+            };
+            let event = db.get_event_by_id(&pending.event_id).await?;
+            let spots_confirmed = db.count_event_reservations(&pending.event_id, false).await?;
+
+            return Ok(api::RetrieveReservationResult::Pending(api::PendingReservationSummaryResponse {
+                reservation_id: pending.id,
+                created_at: pending.status.created_at,
+                verification_expires_at: pending.verification_expires_at(state.config.verification_ttl()),
+                status: pending.status.into(),
+                event: event.into_reservation_event_response(spots_confirmed),
+            }));
+        }
         Err(_) => {
-            db.get_pending_reservation_by_reservation_token(&magic_token)
-            .await
-            .map_err(DatabaseError::from)?;
+            db.get_pending_reservation_by_reservation_token(magic_token)
+            .await?;
             
             return Err(AppError::Validation("Reservation must be confirmed before it can be retrieved. Please check your email for the verification link.".to_string()));
         }
     };
     
-    let response = api::RetrieveReservationResponse {
+    Ok(api::RetrieveReservationResult::Confirmed(confirmed_reservation_response(state, &db, confirmed_reservation, event).await?))
+}
+
+/// Full view of a confirmed reservation: its tokens (as current codes for rotating events) and its event
+async fn confirmed_reservation_response(
+    state: &AppState,
+    db: &Database,
+    confirmed_reservation: models::ConfirmedReservation,
+    event: models::AnyEvent,
+) -> Result<api::RetrieveReservationResponse, AppError> {
+    let spots_confirmed = db.count_event_reservations(&confirmed_reservation.event_id, false).await?;
+    let mut reservation_tokens: Vec<api::ReservationTokenResponse> = confirmed_reservation.status.reservation_tokens.clone().into_iter().map(Into::into).collect();
+
+    // Rotating events show the current window's code for each active token instead of the bare token
+    let now = OffsetDateTime::now_utc();
+    let tokens_rotate_at = event.token_rotation_minutes().map(|rotation_minutes| {
+        for token in reservation_tokens.iter_mut().filter(|token| matches!(token.status, api::ReservationTokenStatus::Active)) {
+            token.token = models::RotatedToken::issue(&token.token, &state.config.jwt_secret, rotation_minutes, now).to_string();
+        }
+        models::RotatedToken::window_ends_at(models::RotatedToken::current_window(rotation_minutes, now), rotation_minutes)
+    });
+
+    Ok(api::RetrieveReservationResponse {
         reservation_id: confirmed_reservation.id,
         user_name: confirmed_reservation.user_name,
         user_email: confirmed_reservation.user_email,
         created_at: confirmed_reservation.status.created_at,
         updated_at: confirmed_reservation.status.updated_at,
         verified_at: Some(confirmed_reservation.status.verified_at),
-        reservation_tokens: confirmed_reservation.status.reservation_tokens.clone().into_iter().map(|token| token.token().to_string()).collect(),
+        reservation_tokens,
+        tokens_rotate_at,
         status: confirmed_reservation.status.into(),
-        event: {
-            let event = db.get_open_event_by_id(&confirmed_reservation.event_id).await?;
-            api::RetrieveReservationEventResponse {
-                id: event.id,
-                name: event.name,
-                description: event.description,
-                start_time: event.start_time,
-                end_time: event.end_time,
-                capacity: event.capacity,
-                location: event.location,
-            }
-        },
-    };  
-    
-    Ok(Json(response))
+        event: event.into_reservation_event_response(spots_confirmed),
+    })
 }
 
-async fn hello_world() -> &'static str {
-    "Hello, world!"
+async fn get_event_tokens(
+    Path(event_id): Path<String>,
+    Query(query): Query<api::EventTokensQuery>,
+    State(state): State<AppState>,
+    _admin: AdminAuth,
+) -> Result<Json<Vec<api::ReservationTokenResponse>>, AppError> {
+    let event_id = parse_path_id(&event_id)?;
+
+    let db = Database { pool: state.pool.clone() };
+    // Tokens are reconciled once an event is full or over, so look past open events
+    if !db.event_exists(&event_id).await? {
+        return Err(DatabaseError::EventNotFound.into());
+    }
+
+    let status = query.status.map(|status| match status {
+        api::ReservationTokenStatus::Inactive => "inactive",
+        api::ReservationTokenStatus::Active => "active",
+        api::ReservationTokenStatus::Used => "used",
+        api::ReservationTokenStatus::Expired => "expired",
+    });
+    let tokens = db.get_tokens_for_event(&event_id, status).await?;
+
+    Ok(Json(tokens.into_iter().map(Into::into).collect()))
 }
 
-#[shuttle_runtime::main]
-async fn main() -> shuttle_axum::ShuttleAxum {
-    // Load environment variables from .env file
-    dotenvy::dotenv().ok();
-    
-    // Optionally, you can handle errors or print a message about loading the variables
-    // Initialize database
-    let db = Database::new().await.expect("Failed to initialize database");
-    
-    // Initialize email sender
-    let email_sender = EmailSender::new();
-    
-    // Create application state with pool and email_sender
-    let state = AppState {
-        pool: db.pool,
-        email_sender,
+#[tracing::instrument(skip_all, fields(reservation_id = tracing::field::Empty))]
+async fn scan_reservation_token(
+    Path(presented): Path<String>,
+    Query(query): Query<api::ScanQuery>,
+    State(state): State<AppState>,
+    _admin: AdminAuth,
+) -> Result<Json<api::ReservationTokenResponse>, AppError> {
+    let db = Database { pool: state.pool.clone() };
+
+    // A hand-typed short code only identifies a ticket together with its event
+    let (rotated, token) = match query.event_id {
+        Some(event_id) => (None, db.get_token_by_short_code(&event_id, &presented).await?),
+        None => {
+            let rotated = models::RotatedToken::parse(&presented);
+            let token = rotated.as_ref().map_or(presented.clone(), |rotated| rotated.token.clone());
+            (rotated, token)
+        }
     };
+
+    // Higher-assurance events also need the guest's name before the ticket is accepted
+    if let Some(required_name) = db.get_token_required_name(&token).await? {
+        match query.name.as_deref().map(str::trim) {
+            None | Some("") => return Err(AppError::Validation("Guest name is required to check in for this event".to_string())),
+            Some(name) if !name.eq_ignore_ascii_case(required_name.trim()) => {
+                return Err(AppError::Conflict("Guest name does not match the reservation".to_string()));
+            }
+            Some(_) => {}
+        }
+    }
+
+    // Events that rotate ticket codes check this against the current window when marking the token used
+    let rotated_code = rotated.as_ref().map(|rotated| (rotated, state.config.jwt_secret.as_str()));
+    let current = match db.get_reservation_token(&token).await? {
+        models::AnyReservationToken::Active(active) => match db.mark_reservation_token_used(active, rotated_code).await {
+            Ok(used) => {
+                tracing::Span::current().record("reservation_id", tracing::field::display(used.reservation_id));
+                tracing::info!("reservation token scanned");
+                return Ok(Json(models::AnyReservationToken::Used(used).into()));
+            }
+            // A concurrent scan got there first; judge this one against its used_at
+            Err(DatabaseError::ReservationTokenNotFound) => db.get_reservation_token(&token).await?,
+            Err(e) => return Err(e.into()),
+        },
+        other => other,
+    };
+
+    match current {
+        // Scanners often double-read a ticket; treat a quick rescan as the same scan
+        models::AnyReservationToken::Used(used)
+            if OffsetDateTime::now_utc() - used.status.used_at <= Duration::seconds(state.config.scan_dedupe_seconds as i64) =>
+        {
+            Ok(Json(models::AnyReservationToken::Used(used).into()))
+        }
+        models::AnyReservationToken::Used(_) => Err(AppError::Conflict("Reservation token already used".to_string())),
+        models::AnyReservationToken::Expired(_) => Err(AppError::Conflict("Reservation token has expired".to_string())),
+        models::AnyReservationToken::Inactive(_) => Err(AppError::Conflict("Reservation is not confirmed yet".to_string())),
+        models::AnyReservationToken::Active(_) => Err(AppError::Conflict("Reservation token changed while scanning, try again".to_string())),
+    }
+}
+
+async fn unscan_reservation_token(
+    Path(token): Path<String>,
+    State(state): State<AppState>,
+    _admin: AdminAuth,
+) -> Result<Json<api::ReservationTokenResponse>, AppError> {
+    let db = Database { pool: state.pool.clone() };
+
+    let used = match db.get_reservation_token(&token).await? {
+        models::AnyReservationToken::Used(used) => used,
+        models::AnyReservationToken::Inactive(_) | models::AnyReservationToken::Active(_) => {
+            return Err(AppError::Conflict("Reservation token has not been scanned".to_string()));
+        }
+        models::AnyReservationToken::Expired(_) => {
+            return Err(AppError::Conflict("Cannot unscan an expired reservation token".to_string()));
+        }
+    };
+
+    let active = db.unmark_reservation_token_used(used).await?;
+
+    Ok(Json(models::AnyReservationToken::Active(active).into()))
+}
+
+async fn expire_active_event_tokens(
+    Path(event_id): Path<String>,
+    State(state): State<AppState>,
+    _admin: AdminAuth,
+) -> Result<Json<api::ExpireActiveTokensResponse>, AppError> {
+    let event_id = parse_path_id(&event_id)?;
+
+    let db = Database { pool: state.pool.clone() };
+    // Finished events are no longer open, so only check that the event exists
+    if !db.event_exists(&event_id).await? {
+        return Err(DatabaseError::EventNotFound.into());
+    }
+
+    let expired_count = db.expire_active_tokens_for_event(&event_id).await?;
+
+    Ok(Json(api::ExpireActiveTokensResponse { event_id, expired_count }))
+}
+
+async fn list_admin_events(
+    Query(query): Query<api::AdminEventsQuery>,
+    State(state): State<AppState>,
+    _admin: AdminAuth,
+) -> Result<Json<Vec<api::AdminEventResponse>>, AppError> {
+    let db = Database { pool: state.pool.clone() };
+    let events = db.get_all_events(query.status.map(Into::into)).await?;
+
+    Ok(Json(events.into_iter().map(Into::into).collect()))
+}
+
+/// Archive an event with its questions, reservations and tokens as one JSON document
+async fn export_event_backup(
+    Path(event_id): Path<String>,
+    State(state): State<AppState>,
+    _admin: AdminAuth,
+) -> Result<Json<backup::EventBackup>, AppError> {
+    let event_id = parse_path_id(&event_id)?;
+
+    let db = Database { pool: state.pool.clone() };
+    let event_backup = db.export_event_backup(&event_id).await?;
+
+    Ok(Json(event_backup))
+}
+
+/// Copy an event's settings to a new date, optionally as the next event in its series
+async fn duplicate_event(
+    Path(event_id): Path<String>,
+    State(state): State<AppState>,
+    _admin: AdminAuth,
+    AppJson(payload): AppJson<api::DuplicateEventRequest>,
+) -> Result<(StatusCode, Json<api::OpenEventResponse>), AppError> {
+    let event_id = parse_path_id(&event_id)?;
+
+    let db = Database { pool: state.pool.clone() };
+    let source = db.get_event_by_id(&event_id).await?;
+    let event = db.duplicate_event(&source, payload.start_time, payload.attach_to_series).await?;
+
+    Ok((StatusCode::CREATED, Json(event.into())))
+}
+
+/// All events in a series, earliest first; private events are only listed for authenticated callers
+async fn get_series_events(
+    Path(series_id): Path<String>,
+    State(state): State<AppState>,
+    admin: Option<AdminAuth>,
+) -> Result<Json<Vec<api::OpenEventResponse>>, AppError> {
+    let series_id = parse_path_id(&series_id)?;
+
+    let db = Database { pool: state.pool.clone() };
+    let events = db.get_events_in_series(&series_id).await?;
+
+    Ok(Json(events.into_iter()
+        .filter(|event| admin.is_some() || event.visibility() != models::EventVisibility::Private)
+        .map(Into::into)
+        .collect()))
+}
+
+/// Recreate an event from an export under new ids
+async fn import_event_backup(
+    State(state): State<AppState>,
+    _admin: AdminAuth,
+    AppJson(event_backup): AppJson<backup::EventBackup>,
+) -> Result<(StatusCode, Json<api::ImportEventBackupResponse>), AppError> {
+    if event_backup.version != backup::BACKUP_VERSION {
+        return Err(AppError::Validation(format!("Unsupported backup version {}", event_backup.version)));
+    }
+    if event_backup.event.capacity > state.config.max_event_capacity {
+        return Err(AppError::Validation(format!("Capacity must be between 1 and {}", state.config.max_event_capacity)));
+    }
+
+    let db = Database { pool: state.pool.clone() };
+    let event_id = db.import_event_backup(&event_backup).await?;
+
+    Ok((StatusCode::CREATED, Json(api::ImportEventBackupResponse {
+        event_id,
+        reservations: event_backup.reservations.len(),
+    })))
+}
+
+/// Daily operations view: every event starting on the given UTC day with its confirmed spots and capacity
+async fn get_daily_events(
+    Query(query): Query<api::DailyEventsQuery>,
+    State(state): State<AppState>,
+    _admin: AdminAuth,
+) -> Result<Json<Vec<api::AdminEventResponse>>, AppError> {
+    let date = time::Date::parse(&query.date, &time::format_description::well_known::Iso8601::DATE)
+        .map_err(|_| AppError::Validation("Invalid date, expected YYYY-MM-DD".to_string()))?;
+
+    let db = Database { pool: state.pool.clone() };
+    let events = db.get_events_with_counts_for_day(date).await?;
+
+    Ok(Json(events.into_iter().map(Into::into).collect()))
+}
+
+async fn import_events_csv(
+    State(state): State<AppState>,
+    _admin: AdminAuth,
+    body: String,
+) -> Result<(StatusCode, Json<api::ImportEventsResponse>), AppError> {
+    let new_events = import::parse_events_csv(&body, state.config.max_event_capacity)
+        .map_err(|errors| AppError::Validation(errors.join("; ")))?;
+
+    let db = Database { pool: state.pool.clone() };
+    let event_ids = db.create_events(&new_events).await?;
+
+    Ok((StatusCode::CREATED, Json(api::ImportEventsResponse {
+        imported: event_ids.len(),
+        event_ids,
+    })))
+}
+
+async fn finish_past_events(
+    State(state): State<AppState>,
+    _admin: AdminAuth,
+) -> Result<Json<api::FinishPastEventsResponse>, AppError> {
+    let db = Database { pool: state.pool.clone() };
+    let finished = db.finish_past_events().await?;
+
+    Ok(Json(api::FinishPastEventsResponse {
+        finished_event_ids: finished.into_iter().map(|event| event.id).collect(),
+    }))
+}
+
+/// Repair an event whose open/full status or token states have drifted from its confirmed reservations
+async fn reconcile_event(
+    Path(event_id): Path<String>,
+    State(state): State<AppState>,
+    _admin: AdminAuth,
+) -> Result<Json<api::ReconcileEventResponse>, AppError> {
+    let event_id = parse_path_id(&event_id)?;
+
+    let db = Database { pool: state.pool.clone() };
+    let status = db.get_event_status(&event_id).await?;
+    if !matches!(status, models::EventStatus::Open | models::EventStatus::Full) {
+        return Err(AppError::Conflict(format!("Only open or full events can be reconciled; this event is {}", status)));
+    }
+
+    let reconciliation = db.reconcile_event(&event_id).await?;
+    Ok(Json(reconciliation.into()))
+}
+
+/// Re-send verification emails to every pending reservation for an event, e.g. after fixing the email provider
+async fn resend_pending_verifications(
+    Path(event_id): Path<String>,
+    State(state): State<AppState>,
+    _admin: AdminAuth,
+) -> Result<Json<api::ResendPendingResponse>, AppError> {
+    let event_id = parse_path_id(&event_id)?;
+
+    let db = Database { pool: state.pool.clone() };
+    db.get_event_by_id(&event_id).await?;
+
+    let pending = db.get_pending_reservations_for_event(&event_id).await?;
+    let (sent, failed) = state.email_sender.send_verifications(pending).await;
+
+    Ok(Json(api::ResendPendingResponse { event_id, sent, failed }))
+}
+
+async fn dedupe_pending_reservations(
+    Query(query): Query<api::DedupeReservationsQuery>,
+    State(state): State<AppState>,
+    _admin: AdminAuth,
+) -> Result<Json<api::DedupeReservationsResponse>, AppError> {
+    let db = Database { pool: state.pool.clone() };
+
+    let mut kept_reservation_ids = Vec::new();
+    let mut cancelled_reservation_ids = Vec::new();
+    for group in db.find_duplicate_pending(&query.event_id).await? {
+        // Groups are ordered oldest first: keep the earliest, cancel the rest
+        let mut group = group.into_iter();
+        if let Some(kept) = group.next() {
+            kept_reservation_ids.push(kept.id);
+        }
+        for duplicate in group {
+            cancelled_reservation_ids.push(duplicate.id);
+            db.cancel_pending_reservation(duplicate).await?;
+        }
+    }
+
+    Ok(Json(api::DedupeReservationsResponse {
+        event_id: query.event_id,
+        kept_reservation_ids,
+        cancelled_reservation_ids,
+    }))
+}
+
+async fn get_email_log(
+    Query(query): Query<api::EmailLogQuery>,
+    State(state): State<AppState>,
+    _admin: AdminAuth,
+) -> Result<Json<Vec<api::EmailLogEntryResponse>>, AppError> {
+    let db = Database { pool: state.pool.clone() };
+    let entries = db.get_email_log(query.email.as_deref()).await?;
+
+    Ok(Json(entries.into_iter().map(Into::into).collect()))
+}
+
+/// Find reservations from part of an email, for support callers who only remember some of it.
+/// Rate-limited like the public lookups, since a leaked admin key would otherwise allow
+/// walking the whole guest list.
+async fn search_reservations(
+    Query(query): Query<api::ReservationSearchQuery>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    _admin: AdminAuth,
+) -> Result<Json<Vec<api::ReservationSearchResultResponse>>, AppError> {
+    if !state.lookup_limiter.check(&rate_limit::client_key(&headers)) {
+        return Err(AppError::TooManyRequests("Too many reservation searches, try again later".to_string()));
+    }
+    query.validate()?;
+
+    let db = Database { pool: state.pool.clone() };
+    let results = db.search_reservations_by_email_fragment(query.email_contains.trim()).await?;
+
+    Ok(Json(results.into_iter().map(Into::into).collect()))
+}
+
+/// The verification link from a pending reservation's email, for support to pass on when the email never arrived
+async fn get_verification_link(
+    Path(reservation_id): Path<String>,
+    State(state): State<AppState>,
+    _admin: AdminAuth,
+) -> Result<Json<api::VerificationLinkResponse>, AppError> {
+    let reservation_id = parse_path_id(&reservation_id)?;
+
+    let db = Database { pool: state.pool.clone() };
+    let pending_reservation = match db.get_pending_reservation_by_id(&reservation_id).await {
+        Ok(pending) => pending,
+        Err(DatabaseError::ReservationNotFound) => {
+            db.get_confirmed_reservation_by_id(&reservation_id).await?;
+            return Err(AppError::Conflict("Reservation is already confirmed".to_string()));
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    Ok(Json(api::VerificationLinkResponse {
+        reservation_id,
+        verification_url: email::verification_url(&state.config.app_url, &pending_reservation.verification_token.0),
+    }))
+}
+
+/// Unfiltered token record for troubleshooting tickets that won't scan
+async fn get_admin_token(
+    Path(token): Path<String>,
+    State(state): State<AppState>,
+    _admin: AdminAuth,
+) -> Result<Json<api::TokenRecordResponse>, AppError> {
+    let db = Database { pool: state.pool.clone() };
+    let record = db.get_token_row(&token).await?;
+
+    Ok(Json(record.into()))
+}
+
+async fn get_event_analytics(
+    Path(event_id): Path<String>,
+    State(state): State<AppState>,
+    _admin: AdminAuth,
+) -> Result<Json<api::EventAnalyticsResponse>, AppError> {
+    let event_id = parse_path_id(&event_id)?;
+
+    let db = Database { pool: state.pool.clone() };
+    let analytics = db.get_event_analytics(&event_id).await?;
+
+    Ok(Json(analytics.into_response(state.config.near_capacity_threshold)))
+}
+
+async fn get_reservation_timeline(
+    Path(event_id): Path<String>,
+    Query(query): Query<api::ReservationTimelineQuery>,
+    State(state): State<AppState>,
+    _admin: AdminAuth,
+) -> Result<Json<api::ReservationTimelineResponse>, AppError> {
+    let event_id = parse_path_id(&event_id)?;
+
+    let db = Database { pool: state.pool.clone() };
+    // Full and finished events are the ones most worth looking back on
+    if !db.event_exists(&event_id).await? {
+        return Err(DatabaseError::EventNotFound.into());
+    }
+
+    let bucket = match query.bucket {
+        api::ReservationTimelineBucket::Hour => db::TimelineBucket::Hour,
+        api::ReservationTimelineBucket::Day => db::TimelineBucket::Day,
+    };
+    let buckets = db.get_reservation_buckets(&event_id, bucket).await?;
+
+    let response = api::ReservationTimelineResponse {
+        event_id,
+        buckets: buckets.into_iter().map(|bucket| api::ReservationTimelineEntry {
+            bucket_start: bucket.bucket_start,
+            spots: bucket.spots,
+        }).collect(),
+    };
+
+    Ok(Json(response))
+}
+
+async fn get_event_questions(
+    Path(event_id): Path<String>,
+    State(state): State<AppState>,
+    admin: Option<AdminAuth>,
+) -> Result<Json<Vec<api::EventQuestionResponse>>, AppError> {
+    let event_id = parse_path_id(&event_id)?;
+
+    let db = Database { pool: state.pool.clone() };
+    let event = db.get_event_by_id(&event_id).await?;
+    if event.visibility() == models::EventVisibility::Private && admin.is_none() {
+        return Err(AppError::Unauthorized);
+    }
+
+    let questions = db.get_event_questions(&event_id).await?;
+
+    Ok(Json(questions.into_iter().map(Into::into).collect()))
+}
+
+async fn set_event_questions(
+    Path(event_id): Path<String>,
+    State(state): State<AppState>,
+    _admin: AdminAuth,
+    Json(payload): Json<Vec<api::EventQuestionRequest>>,
+) -> Result<Json<Vec<api::EventQuestionResponse>>, AppError> {
+    let event_id = parse_path_id(&event_id)?;
+
+    let mut keys = std::collections::HashSet::new();
+    for question in &payload {
+        question.validate()?;
+        if !keys.insert(question.key.as_str()) {
+            return Err(AppError::Validation(format!("Duplicate question key: {}", question.key)));
+        }
+    }
+
+    let db = Database { pool: state.pool.clone() };
+    if !db.event_exists(&event_id).await? {
+        return Err(DatabaseError::EventNotFound.into());
+    }
+
+    let questions: Vec<models::EventQuestion> = payload.into_iter().map(Into::into).collect();
+    db.set_event_questions(&event_id, &questions).await?;
+
+    Ok(Json(questions.into_iter().map(Into::into).collect()))
+}
+
+async fn get_event_answers(
+    Path(event_id): Path<String>,
+    State(state): State<AppState>,
+    _admin: AdminAuth,
+) -> Result<Json<Vec<api::ReservationAnswersResponse>>, AppError> {
+    let event_id = parse_path_id(&event_id)?;
+
+    let db = Database { pool: state.pool.clone() };
+    if !db.event_exists(&event_id).await? {
+        return Err(DatabaseError::EventNotFound.into());
+    }
+
+    let answers = db.get_event_answers(&event_id).await?;
+
+    Ok(Json(answers.into_iter().map(Into::into).collect()))
+}
+
+async fn get_event_roster(
+    Path(event_id): Path<String>,
+    State(state): State<AppState>,
+    _admin: AdminAuth,
+) -> Result<Json<Vec<api::RosterEntryResponse>>, AppError> {
+    let event_id = parse_path_id(&event_id)?;
+
+    let db = Database { pool: state.pool.clone() };
+    if !db.event_exists(&event_id).await? {
+        return Err(DatabaseError::EventNotFound.into());
+    }
+
+    let roster = db.get_event_roster(&event_id).await?;
+
+    Ok(Json(roster.into_iter().map(Into::into).collect()))
+}
+
+async fn get_remaining_checkins(
+    Path(reservation_id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<api::RemainingCheckinsResponse>, AppError> {
+    let reservation_id = parse_path_id(&reservation_id)?;
+
+    let db = Database { pool: state.pool.clone() };
+    let confirmed_reservation = db.get_confirmed_reservation_by_id(&reservation_id).await?;
+
+    Ok(Json(api::RemainingCheckinsResponse {
+        reservation_id: confirmed_reservation.id,
+        spot_count: confirmed_reservation.spot_count,
+        remaining_checkins: confirmed_reservation.get_active_reservation_tokens().len(),
+    }))
+}
+
+async fn get_verification_status(
+    Path(reservation_id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<api::VerificationStatusResponse>, AppError> {
+    let reservation_id = parse_path_id(&reservation_id)?;
+
+    // Pollers mostly see pending reservations, so check the cheaper single-row lookup first
+    let db = Database { pool: state.pool.clone() };
+    match db.get_pending_reservation_by_id(&reservation_id).await {
+        Ok(_) => return Ok(Json(api::VerificationStatusResponse { verified: false, verified_at: None, cancelled_at: None })),
+        Err(DatabaseError::ReservationNotFound) => {}
+        Err(e) => return Err(e.into()),
+    }
+
+    match db.get_confirmed_reservation_by_id(&reservation_id).await {
+        Ok(confirmed_reservation) => return Ok(Json(api::VerificationStatusResponse {
+            verified: true,
+            verified_at: Some(confirmed_reservation.status.verified_at),
+            cancelled_at: None,
+        })),
+        Err(DatabaseError::ReservationNotFound) => {}
+        Err(e) => return Err(e.into()),
+    }
+
+    let cancelled = db.get_cancelled_reservation_by_id(&reservation_id).await?;
+
+    Ok(Json(api::VerificationStatusResponse {
+        verified: cancelled.status.verified_at.is_some(),
+        verified_at: cancelled.status.verified_at,
+        cancelled_at: Some(cancelled.status.cancelled_at),
+    }))
+}
+
+async fn move_reservation(
+    Path(reservation_id): Path<String>,
+    State(state): State<AppState>,
+    _admin: AdminAuth,
+    Json(payload): Json<api::MoveReservationRequest>,
+) -> Result<Json<api::MoveReservationResponse>, AppError> {
+    let reservation_id = parse_path_id(&reservation_id)?;
+
+    let db = Database { pool: state.pool.clone() };
+    let confirmed_reservation = db.get_confirmed_reservation_by_id(&reservation_id).await?;
+
+    if confirmed_reservation.event_id == payload.target_event_id {
+        return Err(AppError::Validation("Reservation is already for this event".to_string()));
+    }
+
+    // Only open events can receive reservations; full or finished events are not found here
+    let target_event = db.get_open_event_by_id(&payload.target_event_id).await?;
+
+    // The party has to fit the target event the same way a fresh reservation would
+    if let Some(max_reservations) = target_event.max_reservations {
+        if db.count_event_parties(&target_event.id).await? >= max_reservations {
+            return Err(AppError::CapacityExceeded("Target event has reached its maximum number of reservations".to_string()));
+        }
+    }
+
+    check_spots_per_reservation(target_event.max_spots_per_reservation, target_event.min_spots_per_reservation, confirmed_reservation.spot_count)?;
+
+    let now = OffsetDateTime::now_utc();
+    let current_count = db.count_event_reservations(&target_event.id, state.config.count_pending_toward_capacity).await?
+        + db.count_held_spots(&target_event.id, now).await?;
+
+    if current_count + confirmed_reservation.spot_count > target_event.capacity {
+        return Err(AppError::CapacityExceeded("Target event does not have enough capacity for this reservation".to_string()));
+    }
+
+    let moved_reservation = db.move_reservation(confirmed_reservation, &target_event.id).await?;
+
+    let response = api::MoveReservationResponse {
+        reservation_id: moved_reservation.id,
+        event_id: moved_reservation.event_id,
+        reservation_tokens: moved_reservation.status.reservation_tokens.into_iter().map(Into::into).collect(),
+    };
+
+    Ok(Json(response))
+}
+
+/// Wallet pass for a confirmed reservation, with its first active token as the QR code
+async fn get_reservation_pass(
+    Path(reservation_id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Response, AppError> {
+    let reservation_id = parse_path_id(&reservation_id)?;
+
+    let db = Database { pool: state.pool.clone() };
+    let (confirmed_reservation, event) = db.get_confirmed_reservation_with_event(&reservation_id).await?;
+
+    // A static pass can't show a code that changes every few minutes
+    if event.token_rotation_minutes().is_some() {
+        return Err(AppError::Validation("Wallet passes are not available for events with rotating ticket codes".to_string()));
+    }
+
+    let Some(token) = confirmed_reservation.get_active_reservation_tokens().into_iter().next() else {
+        return Err(AppError::Conflict("Reservation has no unused tickets".to_string()));
+    };
+
+    let bundle = pass::build_pass(&pass::PassDetails {
+        pass_type_id: &state.config.wallet_pass_type_id,
+        team_id: &state.config.wallet_team_id,
+        organization_name: &state.config.app_name,
+        serial_number: confirmed_reservation.id.to_string(),
+        event_name: event.name(),
+        location: event.location(),
+        start_time: event.start_time(),
+        spot_count: confirmed_reservation.spot_count,
+        barcode_message: &token.token,
+    })
+    .map_err(|e| {
+        eprintln!("Failed to build wallet pass: {}", e);
+        AppError::InternalServerError
+    })?;
+
+    Ok(([(header::CONTENT_TYPE, pass::PKPASS_CONTENT_TYPE)], bundle).into_response())
+}
+
+/// Printable PDF of the QR code for every unused ticket of an event's confirmed reservations, captioned
+/// with the guest's name, for organizers printing badges
+async fn get_event_qr_sheet(
+    Path(event_id): Path<String>,
+    State(state): State<AppState>,
+    _admin: AdminAuth,
+) -> Result<Response, AppError> {
+    let event_id = parse_path_id(&event_id)?;
+
+    let db = Database { pool: state.pool.clone() };
+    let event = db.get_event_by_id(&event_id).await?;
+
+    // A printed code can't follow a code that changes every few minutes
+    if event.token_rotation_minutes().is_some() {
+        return Err(AppError::Validation("QR sheets are not available for events with rotating ticket codes".to_string()));
+    }
+
+    let mut tickets = Vec::new();
+    for ticket in db.get_qr_sheet_tickets(&event_id).await? {
+        let qr = qr::TicketQr::new(&ticket.token).map_err(|e| {
+            eprintln!("Failed to encode ticket QR code: {}", e);
+            AppError::InternalServerError
+        })?;
+        tickets.push(qr_sheet::SheetTicket { qr, caption: ticket.caption() });
+    }
+
+    let sheet = qr_sheet::build_qr_sheet(event.name(), &tickets);
+    Ok(([(header::CONTENT_TYPE, qr_sheet::PDF_CONTENT_TYPE)], sheet).into_response())
+}
+
+/// Add or remove spots on a confirmed reservation (e.g. a plus-one), issuing or expiring tokens to match
+/// The per-reservation spot limits `reserve` enforces, for admin changes that set a party's size directly
+fn check_spots_per_reservation(max_spots: Option<u32>, min_spots: Option<u32>, spot_count: u32) -> Result<(), AppError> {
+    if let Some(max_spots) = max_spots {
+        if spot_count > max_spots {
+            return Err(AppError::Validation(format!("Cannot reserve more than {} spots per reservation for this event", max_spots)));
+        }
+    }
+
+    if let Some(min_spots) = min_spots {
+        if spot_count < min_spots {
+            return Err(AppError::Validation(format!("Must reserve at least {} spots per reservation for this event", min_spots)));
+        }
+    }
+
+    Ok(())
+}
+
+async fn adjust_reservation_spots(
+    Path(reservation_id): Path<String>,
+    State(state): State<AppState>,
+    _admin: AdminAuth,
+    Json(payload): Json<api::AdjustSpotsRequest>,
+) -> Result<Json<api::AdjustSpotsResponse>, AppError> {
+    payload.validate()?;
+    let reservation_id = parse_path_id(&reservation_id)?;
+
+    let db = Database { pool: state.pool.clone() };
+    let (confirmed_reservation, event) = db.get_confirmed_reservation_with_event(&reservation_id).await?;
+
+    let checked_in = confirmed_reservation.get_used_reservation_tokens().len() as u32;
+    if payload.spot_count < checked_in {
+        return Err(AppError::Validation(format!("Cannot go below the {} spots already checked in", checked_in)));
+    }
+
+    check_spots_per_reservation(event.max_spots_per_reservation(), event.min_spots_per_reservation(), payload.spot_count)?;
+
+    // Added spots can't take ones held by guests still checking out
+    if payload.spot_count > confirmed_reservation.spot_count {
+        let now = OffsetDateTime::now_utc();
+        let current_count = db.count_event_reservations(&confirmed_reservation.event_id, state.config.count_pending_toward_capacity).await?
+            + db.count_held_spots(&confirmed_reservation.event_id, now).await?;
+        if current_count - confirmed_reservation.spot_count + payload.spot_count > event.capacity() {
+            return Err(AppError::CapacityExceeded("Event does not have enough capacity for the added spots".to_string()));
+        }
+    }
+
+    let adjusted_reservation = db.adjust_reservation_spots(confirmed_reservation, payload.spot_count).await?;
+
+    Ok(Json(api::AdjustSpotsResponse {
+        reservation_id: adjusted_reservation.id,
+        event_id: adjusted_reservation.event_id,
+        spot_count: adjusted_reservation.spot_count,
+        reservation_tokens: adjusted_reservation.status.reservation_tokens.into_iter().map(Into::into).collect(),
+    }))
+}
+
+/// Cancel a confirmed reservation with the token from its confirmation email, no login required
+async fn cancel_reservation_by_token(
+    Path(token): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<api::CancelReservationResponse>, AppError> {
+    let db = Database { pool: state.pool.clone() };
+
+    let reservation_id = db.get_reservation_id_by_cancellation_token(&token).await?;
+    let (confirmed_reservation, event) = db.get_confirmed_reservation_with_event(&reservation_id).await?;
+
+    // Cancellations close once the event has started
+    let now = OffsetDateTime::now_utc();
+    if now >= event.start_time() {
+        return Err(AppError::Validation("Cancellation deadline has passed".to_string()));
+    }
+
+    let refund_status = models::RefundStatus::for_cancellation(event.start_time(), now, state.config.refund_cutoff());
+    let (reservation_id, event_id, spot_count) = (confirmed_reservation.id, confirmed_reservation.event_id, confirmed_reservation.spot_count);
+    let cancelled_at = db.cancel_confirmed_reservation(confirmed_reservation, refund_status).await?;
+
+    Ok(Json(api::CancelReservationResponse {
+        reservation_id,
+        event_id,
+        spot_count,
+        refund_status: refund_status.into(),
+        cancelled_at,
+    }))
+}
+
+/// Cancel an event's reservations in bulk, e.g. once the event itself is called off
+async fn cancel_event_reservations(
+    Path(event_id): Path<String>,
+    Query(query): Query<api::BulkCancelQuery>,
+    State(state): State<AppState>,
+    _admin: AdminAuth,
+) -> Result<Json<api::BulkCancelResponse>, AppError> {
+    let event_id = parse_path_id(&event_id)?;
+
+    let (mut include_pending, mut include_confirmed) = (false, false);
+    for status in query.status.as_deref().unwrap_or("pending").split(',').map(str::trim) {
+        match status {
+            "pending" => include_pending = true,
+            "confirmed" => include_confirmed = true,
+            other => return Err(AppError::Validation(format!("Unknown status: {}; expected pending or confirmed", other))),
+        }
+    }
+
+    let db = Database { pool: state.pool.clone() };
+    let event = db.get_event_by_id(&event_id).await?;
+    let cancelled = db.cancel_event_reservations(&event_id, include_pending, include_confirmed).await?;
+
+    let mut notified = 0;
+    if query.notify {
+        for reservation in &cancelled {
+            match state.email_sender.send_cancellation(&reservation.user_email, &reservation.user_name, event.name()).await {
+                Ok(()) => notified += 1,
+                Err(e) => eprintln!("Failed to send cancellation email: {}", e),
+            }
+        }
+    }
+
+    let cancelled_confirmed = cancelled.iter().filter(|reservation| reservation.confirmed).count();
+
+    Ok(Json(api::BulkCancelResponse {
+        event_id,
+        cancelled_pending: cancelled.len() - cancelled_confirmed,
+        cancelled_confirmed,
+        notified,
+    }))
+}
+
+async fn get_version(State(state): State<AppState>) -> Result<Json<api::VersionResponse>, AppError> {
+    let db = Database { pool: state.pool.clone() };
+
+    Ok(Json(api::VersionResponse {
+        version: env!("CARGO_PKG_VERSION"),
+        // Set GIT_SHA in the build environment to report the deployed commit
+        git_sha: option_env!("GIT_SHA"),
+        migration_version: db.latest_migration_version().await?,
+    }))
+}
+
+/// Whether the database schema matches the migrations this build was compiled with
+async fn get_migration_health(State(state): State<AppState>) -> Result<Json<api::MigrationHealthResponse>, AppError> {
+    let db = Database { pool: state.pool.clone() };
+    let drift = db.migration_drift().await?;
+
+    Ok(Json(api::MigrationHealthResponse {
+        up_to_date: drift.is_empty(),
+        pending: drift.into_iter().map(Into::into).collect(),
+    }))
+}
+
+/// Ids in the path that aren't UUIDs can't name anything, so they're not found rather than bad requests
+fn parse_path_id(id: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(id).map_err(|_| AppError::not_found())
+}
+
+/// Unmatched routes answer with the same JSON error shape as every other endpoint
+async fn route_not_found() -> AppError {
+    AppError::not_found()
+}
+
+async fn hello_world() -> &'static str {
+    "Hello, world!"
+}
+
+/// Build the CORS layer from the configured origins, methods, and headers
+fn cors_layer(config: &Config) -> CorsLayer {
+    let origins: Vec<HeaderValue> = config.cors_allowed_origins
+        .iter()
+        .filter_map(|origin| origin.parse().ok())
+        .collect();
+    let methods: Vec<Method> = config.cors_allowed_methods
+        .iter()
+        .filter_map(|method| method.parse().ok())
+        .collect();
+    let headers: Vec<HeaderName> = config.cors_allowed_headers
+        .iter()
+        .filter_map(|header| header.parse().ok())
+        .collect();
+
+    CorsLayer::new()
+        .allow_origin(origins)
+        .allow_methods(methods)
+        .allow_headers(headers)
+        .expose_headers([HeaderName::from_static(SPOTS_REMAINING_HEADER)])
+        .max_age(std::time::Duration::from_secs(config.cors_max_age_seconds))
+}
+
+/// Fail a request whose handler takes longer than `REQUEST_TIMEOUT_SECONDS`. Only producing the
+/// response is timed; a download's body is streamed afterwards, outside the deadline.
+async fn request_timeout(State(state): State<AppState>, request: axum::extract::Request, next: Next) -> Response {
+    let limit = std::time::Duration::from_secs(state.config.request_timeout_seconds);
+    match tokio::time::timeout(limit, next.run(request)).await {
+        Ok(response) => response,
+        Err(_) => AppError::Timeout.into_response(),
+    }
+}
+
+/// Build the Axum router with all routes and middleware layers
+fn app(state: AppState) -> Router {
+    let cors = cors_layer(&state.config);
+
+    Router::new()
+        .route("/", get(hello_world))
+        .route("/version", get(get_version))
+        .route("/health/migrations", get(get_migration_health))
+        .route("/events/new", post(generate_random_event))
+        .route("/events/search", get(search_events))
+        .route("/events/{id}", get(get_event_by_id))
+        .route("/events/slug/{slug}", get(get_event_by_slug))
+        .route("/events/{id}/status", patch(update_event_status))
+        .route("/events/{id}/close-sales", post(close_event_sales))
+        .route("/events/{id}/reopen-sales", post(reopen_event_sales))
+        .route("/events/{id}/tokens", get(get_event_tokens))
+        .route("/events/{id}/tokens/expire-active", post(expire_active_event_tokens))
+        .route("/events/{id}/qr-sheet.pdf", get(get_event_qr_sheet))
+        .route("/events/{id}/reservation-timeline", get(get_reservation_timeline))
+        .route("/events/{id}/analytics", get(get_event_analytics))
+        .route("/events/{id}/hold", post(hold_spots))
+        .route("/events/{id}/reservation-status", get(get_reservation_status))
+        .route("/series/{series_id}/events", get(get_series_events))
+        .route("/events/{id}/questions", get(get_event_questions).put(set_event_questions))
+        .route("/reserve", post(reserve))
+        .route("/reservations/resend-by-email", post(resend_verification_by_email))
+        .route("/my/tokens", get(get_my_tokens))
+        .route("/my/tokens/access", post(request_my_tokens_access))
+        .route("/reservations/{id}/move", post(move_reservation))
+        .route("/reservations/{id}/remaining-checkins", get(get_remaining_checkins))
+        .route("/reservations/{id}/pass.pkpass", get(get_reservation_pass))
+        .route("/reservations/{id}/verified", get(get_verification_status))
+        .route("/reservation-tokens/{token}/scan", post(scan_reservation_token))
+        .route("/reservation-tokens/{token}/unscan", post(unscan_reservation_token))
+        .route("/cancel/{token}", post(cancel_reservation_by_token))
+        .route("/verify/{token}", get(verify_email))
+        .route("/verify/{token}/", get(verify_email))
+        .route("/admin/email-log", get(get_email_log))
+        .route("/admin/daily", get(get_daily_events))
+        .route("/admin/events", get(list_admin_events))
+        .route("/admin/events/import", post(import_event_backup))
+        .route("/admin/events/import.csv", post(import_events_csv))
+        .route("/admin/events/{id}/export", get(export_event_backup))
+        .route("/admin/events/{id}/duplicate", post(duplicate_event))
+        .route("/admin/events/{id}/answers", get(get_event_answers))
+        .route("/admin/events/{id}/reservations", get(get_event_roster))
+        .route("/admin/events/{id}/resend-pending", post(resend_pending_verifications))
+        .route("/admin/events/{id}/cancel-reservations", post(cancel_event_reservations))
+        .route("/admin/events/{id}/reconcile", post(reconcile_event))
+        .route("/admin/events/finish-past", post(finish_past_events))
+        .route("/admin/tokens/{token}", get(get_admin_token))
+        .route("/admin/reservations/search", get(search_reservations))
+        .route("/admin/reservations/dedupe", post(dedupe_pending_reservations))
+        .route("/admin/reservations/{id}/adjust-spots", post(adjust_reservation_spots))
+        .route("/admin/reservations/{id}/verification-link", get(get_verification_link))
+        .route("/retrieve/{magic_token}", get(get_reservation_by_magic_token)) // TODO: do we want a retrieval token? or just use the id? 
+        .fallback(route_not_found)
+        .layer(middleware::from_fn_with_state(state.clone(), request_timeout))
+        .with_state(state)
+        // Layer with Trace for request logging
+        .layer(TraceLayer::new_for_http())
+        // Layer with CORS
+        .layer(cors)
+        // Layer with JSON extractor limits (16MB limit)
+        .layer(axum::extract::DefaultBodyLimit::max(16 * 1024 * 1024))
+}
+
+#[shuttle_runtime::main]
+async fn main() -> shuttle_axum::ShuttleAxum {
+    // Load environment variables from .env file
+    dotenvy::dotenv().ok();
+    let config = Config::from_env().expect("Failed to load configuration");
+    config.validate().expect("Invalid configuration");
+    
+    // Optionally, you can handle errors or print a message about loading the variables
+    // Initialize database
+    let db = Database::new().await.expect("Failed to initialize database");
+    
+    // Initialize email sender
+    let provider = email::provider_from_config(&config).expect("Failed to configure email provider");
+    let email_sender = EmailSender::with_provider(provider).with_from_addresses(&config).with_log(db.pool.clone());
     
-    // Build Axum router with middleware layers
-    let router = Router::new()
-        .route("/", get(hello_world))
-        .route("/events/new", post(generate_random_event))
-        .route("/events/{id}", get(get_event_by_id))
-        .route("/reserve", post(reserve))
-        .route("/verify/{token}", get(verify_email))
-        .route("/retrieve/{magic_token}", get(get_reservation_by_magic_token)) // TODO: do we want a retrieval token? or just use the id? 
-        .with_state(state)
-        // Layer with Trace for request logging
-        .layer(TraceLayer::new_for_http())
-        // Layer with CORS
-        .layer(CorsLayer::permissive())
-        // Layer with JSON extractor limits (16MB limit)
-        .layer(axum::extract::DefaultBodyLimit::max(16 * 1024 * 1024));
+    // Create application state with pool, email_sender, and config
+    let state = AppState {
+        pool: db.pool,
+        email_sender,
+        lookup_limiter: rate_limit::RateLimiter::per_minute(config.rate_limit_requests_per_minute),
+        config,
+    };
+    
+    let router = app(state);
+
+    Ok(router.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::{header, Request, StatusCode};
+    use http_body_util::BodyExt;
+    use serde_json::{json, Value};
+    use tower::ServiceExt;
+
+    /// Captures sent emails instead of delivering them
+    #[derive(Debug, Default)]
+    struct RecordingProvider {
+        sent: std::sync::Mutex<Vec<email::EmailMessage>>,
+    }
+
+    impl RecordingProvider {
+        fn sent_count(&self) -> usize {
+            self.sent.lock().unwrap().len()
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl EmailProvider for RecordingProvider {
+        async fn send(&self, message: &email::EmailMessage) -> Result<(), EmailError> {
+            self.sent.lock().unwrap().push(message.clone());
+            Ok(())
+        }
+    }
+
+    /// Fails every send, as a provider outage would
+    #[derive(Debug)]
+    struct FailingProvider;
+
+    #[async_trait::async_trait]
+    impl EmailProvider for FailingProvider {
+        async fn send(&self, _message: &email::EmailMessage) -> Result<(), EmailError> {
+            Err(EmailError::SendFailure("provider unavailable".to_string()))
+        }
+    }
+
+    async fn test_state() -> AppState {
+        test_state_with_provider(Arc::new(RecordingProvider::default())).await
+    }
+
+    async fn test_state_with_provider(provider: Arc<dyn EmailProvider>) -> AppState {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        sqlx::migrate!("./migrations")
+            .run(&db.pool)
+            .await
+            .expect("Failed to run migrations");
+
+        let config = Config {
+            api_keys: vec![TEST_API_KEY.to_string()],
+            ..Config::from_env().unwrap()
+        };
+
+        AppState {
+            email_sender: EmailSender::with_provider(provider).with_from_addresses(&config).with_log(db.pool.clone()),
+            pool: db.pool,
+            lookup_limiter: rate_limit::RateLimiter::per_minute(config.rate_limit_requests_per_minute),
+            config,
+        }
+    }
+
+    const TEST_API_KEY: &str = "test-api-key";
+
+    fn with_api_key(mut request: Request<Body>, api_key: &str) -> Request<Body> {
+        request.headers_mut().insert(auth::API_KEY_HEADER, api_key.parse().unwrap());
+        request
+    }
+
+    fn test_new_event(capacity: u32) -> models::NewEvent {
+        let start_time = OffsetDateTime::now_utc() + Duration::hours(24);
+        models::NewEvent {
+            name: "Test Event".to_string(),
+            description: None,
+            start_time,
+            end_time: start_time + Duration::hours(2),
+            capacity,
+            location: None,
+            max_reservations: None,
+            max_spots_per_reservation: None,
+            min_spots_per_reservation: None,
+            visibility: models::EventVisibility::Public,
+            reservations_open_at: None,
+            reservations_close_at: None,
+            skip_verification: false,
+            require_name_match: false,
+            token_rotation_minutes: None,
+            series_id: None,
+        }
+    }
+
+    async fn send(state: &AppState, request: Request<Body>) -> (StatusCode, Value) {
+        let response = app(state.clone()).oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let body = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+        (status, body)
+    }
+
+    fn post_json(uri: &str, body: Value) -> Request<Body> {
+        Request::post(uri)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    async fn create_confirmed_reservation(db: &Database, event_id: Uuid, user_email: &str, spot_count: u32) -> models::ConfirmedReservation {
+        let pending = db.insert_reservation(
+            models::CreatingReservation::prepare(event_id, "Test User".to_string(), user_email.to_string(), spot_count, None)
+        ).await.unwrap();
+
+        db.confirm_reservation(pending).await.unwrap()
+    }
+
+    fn reserve_request(event_id: Uuid, user_email: &str, spot_count: u32) -> Request<Body> {
+        post_json("/reserve", json!({
+            "event_id": event_id,
+            "user_name": "Test User",
+            "user_email": user_email,
+            "spot_count": spot_count,
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_reserve_rejects_when_max_reservations_reached() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(models::NewEvent {
+            max_reservations: Some(2),
+            ..test_new_event(50)
+        }).await.unwrap();
+
+        let (status, _) = send(&state, reserve_request(event.id, "one@example.com", 1)).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = send(&state, reserve_request(event.id, "two@example.com", 1)).await;
+        assert_eq!(status, StatusCode::OK);
+
+        // Only 2 of 50 spots are taken, but the party cap has been hit
+        let (status, body) = send(&state, reserve_request(event.id, "three@example.com", 1)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "Event has reached its maximum number of reservations");
+    }
+
+    #[tokio::test]
+    async fn test_capacity_counts_spots_not_reservations() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(4)).await.unwrap();
+        create_confirmed_reservation(&db, event.id, "john@example.com", 3).await;
+        assert_eq!(db.count_event_reservations(&event.id, false).await.unwrap(), 3);
+
+        // One reservation holds 3 of the 4 spots, so a party of 2 no longer fits
+        let (status, body) = send(&state, reserve_request(event.id, "jane@example.com", 2)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "Cannot reserve this many slots for this event");
+
+        let (status, _) = send(&state, reserve_request(event.id, "jane@example.com", 1)).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_unlisted_event_hidden_from_list_but_fetchable_by_id() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let public = db.create_event(test_new_event(50)).await.unwrap();
+        let unlisted = db.create_event(models::NewEvent {
+            visibility: models::EventVisibility::Unlisted,
+            ..test_new_event(50)
+        }).await.unwrap();
+        let private = db.create_event(models::NewEvent {
+            visibility: models::EventVisibility::Private,
+            ..test_new_event(50)
+        }).await.unwrap();
+
+        let listed: Vec<Uuid> = db.get_all_open_events().await.unwrap().into_iter().map(|e| e.id).collect();
+        assert_eq!(listed, vec![public.id]);
+
+        let (status, body) = send(&state, Request::get(format!("/events/{}", unlisted.id)).body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["visibility"], "unlisted");
+
+        let (status, _) = send(&state, Request::get(format!("/events/{}", private.id)).body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let (status, _) = send(&state, with_api_key(Request::get(format!("/events/{}", private.id)).body(Body::empty()).unwrap(), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_private_event_requires_auth_to_reserve_hold_and_look_up() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let private = db.create_event(models::NewEvent {
+            visibility: models::EventVisibility::Private,
+            ..test_new_event(50)
+        }).await.unwrap();
+
+        let hold = || post_json(&format!("/events/{}/hold", private.id), json!({ "spot_count": 1 }));
+        let questions = || Request::get(format!("/events/{}/questions", private.id)).body(Body::empty()).unwrap();
+
+        let (status, _) = send(&state, reserve_request(private.id, "john@example.com", 1)).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, _) = send(&state, hold()).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, _) = send(&state, questions()).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, _) = send(&state, reservation_status_request(private.id, "john@example.com")).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(db.count_event_parties(&private.id).await.unwrap(), 0);
+
+        let (status, _) = send(&state, with_api_key(reserve_request(private.id, "john@example.com", 1), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = send(&state, with_api_key(hold(), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = send(&state, with_api_key(questions(), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, body) = send(&state, with_api_key(reservation_status_request(private.id, "john@example.com"), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "pending");
+    }
+
+    #[tokio::test]
+    async fn test_hold_sets_aside_spots_until_completed() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(3)).await.unwrap();
+
+        let (status, body) = send(&state, post_json(&format!("/events/{}/hold", event.id), json!({ "spot_count": 2 }))).await;
+        assert_eq!(status, StatusCode::OK);
+        let hold_token = body["hold_token"].as_str().unwrap().to_string();
+
+        // Only one spot is left for everyone else while the hold is active
+        let (status, body) = send(&state, reserve_request(event.id, "other@example.com", 2)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["code"], "capacity_exceeded");
+
+        let (status, _) = send(&state, post_json("/reserve", json!({
+            "event_id": event.id,
+            "user_name": "Test User",
+            "user_email": "holder@example.com",
+            "spot_count": 2,
+            "hold_token": hold_token,
+        }))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(db.count_held_spots(&event.id, OffsetDateTime::now_utc()).await.unwrap(), 0);
+
+        // A completed hold can't be reused
+        let (status, body) = send(&state, post_json("/reserve", json!({
+            "event_id": event.id,
+            "user_name": "Test User",
+            "user_email": "again@example.com",
+            "spot_count": 2,
+            "hold_token": hold_token,
+        }))).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["code"], "hold_not_found");
+    }
+
+    #[tokio::test]
+    async fn test_expired_hold_frees_spots() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(2)).await.unwrap();
+
+        let placed_at = OffsetDateTime::now_utc() - Duration::minutes(10);
+        let hold = db.insert_hold(models::HoldingReservation::place(event.id, 2, placed_at, Duration::minutes(5))).await.unwrap();
+        assert!(hold.is_expired(OffsetDateTime::now_utc()));
+        assert_eq!(db.count_held_spots(&event.id, OffsetDateTime::now_utc()).await.unwrap(), 0);
+
+        let (status, body) = send(&state, post_json("/reserve", json!({
+            "event_id": event.id,
+            "user_name": "Test User",
+            "user_email": "late@example.com",
+            "spot_count": 2,
+            "hold_token": hold.status.hold_token,
+        }))).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["code"], "hold_not_found");
+
+        let (status, _) = send(&state, reserve_request(event.id, "someone@example.com", 2)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(db.delete_expired_holds(OffsetDateTime::now_utc()).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_failed_email_send_is_logged() {
+        let state = test_state().await;
+
+        let result = state.email_sender.send_verification("bad", "some-token").await;
+        assert!(matches!(result, Err(EmailError::InvalidEmail(_))));
+        state.email_sender.send_verification("good@example.com", "some-token").await.unwrap();
+
+        let (status, body) = send(&state, with_api_key(Request::get("/admin/email-log?email=bad").body(Body::empty()).unwrap(), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+        let entries = body.as_array().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0]["to"], "bad");
+        assert_eq!(entries[0]["kind"], "verification");
+        assert_eq!(entries[0]["status"], "failed");
+        assert_eq!(entries[0]["error"], "Invalid email address: bad");
+
+        let (_, body) = send(&state, with_api_key(Request::get("/admin/email-log").body(Body::empty()).unwrap(), TEST_API_KEY)).await;
+        assert_eq!(body.as_array().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_close_sales_rejects_reserve() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+
+        let uri = format!("/events/{}/close-sales", event.id);
+        let (status, _) = send(&state, post_json(&uri, json!({}))).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let (status, body) = send(&state, with_api_key(post_json(&uri, json!({})), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["sales_closed"], true);
+
+        // Closing sales leaves the event open with its spots unclaimed
+        let (_, body) = send(&state, Request::get(format!("/events/{}", event.id)).body(Body::empty()).unwrap()).await;
+        assert_eq!(body["sales_closed"], true);
+        assert_eq!(body["status"], "Open");
+
+        let (status, body) = send(&state, reserve_request(event.id, "john@example.com", 1)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "Sales closed");
+        assert_eq!(db.count_event_reservations(&event.id, true).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_reopen_sales_allows_reserve_again() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+        db.set_event_sales_closed(&event.id, true).await.unwrap();
+
+        let uri = format!("/events/{}/reopen-sales", event.id);
+        let (status, body) = send(&state, with_api_key(post_json(&uri, json!({})), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["sales_closed"], false);
+
+        let (status, _) = send(&state, reserve_request(event.id, "john@example.com", 1)).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let uri = format!("/events/{}/reopen-sales", Uuid::new_v4());
+        let (status, _) = send(&state, with_api_key(post_json(&uri, json!({})), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_reserve_respects_reservation_window() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let now = OffsetDateTime::now_utc();
+
+        let not_yet_open = db.create_event(models::NewEvent {
+            reservations_open_at: Some(now + Duration::hours(1)),
+            ..test_new_event(50)
+        }).await.unwrap();
+        let (status, body) = send(&state, reserve_request(not_yet_open.id, "early@example.com", 1)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "Reservations not yet open");
+
+        let within_window = db.create_event(models::NewEvent {
+            reservations_open_at: Some(now - Duration::hours(1)),
+            reservations_close_at: Some(now + Duration::hours(1)),
+            ..test_new_event(50)
+        }).await.unwrap();
+        let (status, _) = send(&state, reserve_request(within_window.id, "ontime@example.com", 1)).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let closed = db.create_event(models::NewEvent {
+            reservations_close_at: Some(now - Duration::minutes(1)),
+            ..test_new_event(50)
+        }).await.unwrap();
+        let (status, body) = send(&state, reserve_request(closed.id, "late@example.com", 1)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "Reservations closed");
+
+        let (_, body) = send(&state, Request::get(format!("/events/{}", closed.id)).body(Body::empty()).unwrap()).await;
+        assert!(body["reservations_open_at"].is_null());
+        assert!(body["reservations_close_at"].is_string());
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_marks_full_event_wrongly_left_open() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(3)).await.unwrap();
+        create_confirmed_reservation(&db, event.id, "john@example.com", 2).await;
+        create_confirmed_reservation(&db, event.id, "jane@example.com", 1).await;
+        let cancelled = create_confirmed_reservation(&db, event.id, "bob@example.com", 1).await;
+
+        // Drift: a cancelled reservation left behind with a live token, and no longer counted
+        sqlx::query("UPDATE reservations SET status = 'cancelled' WHERE id = ?")
+            .bind(cancelled.id.to_string())
+            .execute(&db.pool)
+            .await
+            .unwrap();
+        assert_eq!(db.get_event_status(&event.id).await.unwrap(), models::EventStatus::Open);
+
+        let uri = format!("/admin/events/{}/reconcile", event.id);
+        let (status, body) = send(&state, with_api_key(post_json(&uri, json!({})), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["confirmed_spots"], 3);
+        assert_eq!(body["capacity"], 3);
+        assert_eq!(body["status"]["before"], "Open");
+        assert_eq!(body["status"]["after"], "Full");
+        assert_eq!(body["tokens"]["expired"], 1);
+        assert_eq!(body["tokens"]["activated"], 0);
+
+        assert_eq!(db.get_event_status(&event.id).await.unwrap(), models::EventStatus::Full);
+        let token = cancelled.get_active_reservation_tokens().remove(0);
+        assert!(matches!(db.get_reservation_token(&token.token).await.unwrap(), models::AnyReservationToken::Expired(_)));
+
+        // A second pass finds nothing left to fix
+        let (_, body) = send(&state, with_api_key(post_json(&uri, json!({})), TEST_API_KEY)).await;
+        assert_eq!(body["status"]["before"], "Full");
+        assert_eq!(body["status"]["after"], "Full");
+        assert_eq!(body["tokens"]["expired"], 0);
+    }
+
+    #[tokio::test]
+    async fn test_retrieve_reservation_for_full_event() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(2)).await.unwrap();
+        let confirmed = create_confirmed_reservation(&db, event.id, "john@example.com", 2).await;
+
+        sqlx::query("UPDATE events SET status = 'full' WHERE id = ?")
+            .bind(event.id.to_string())
+            .execute(&db.pool)
+            .await
+            .unwrap();
+
+        let (_, joined_event) = db.get_confirmed_reservation_with_event(&confirmed.id).await.unwrap();
+        assert!(matches!(joined_event, models::AnyEvent::Full(_)));
+
+        let token = confirmed.get_active_reservation_tokens().remove(0).token;
+        let (status, body) = send(&state, Request::get(format!("/retrieve/{}", token)).body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["reservation_id"], confirmed.id.to_string());
+        assert_eq!(body["event"]["id"], event.id.to_string());
+        assert_eq!(body["event"]["status"], "Full");
+        assert_eq!(body["event"]["spots_confirmed"], 2);
+        assert_eq!(body["event"]["spots_remaining"], 0);
+    }
+
+    #[tokio::test]
+    async fn test_retrieve_as_json_api_compound_document() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+        let confirmed = create_confirmed_reservation(&db, event.id, "john@example.com", 2).await;
+        let token = confirmed.get_active_reservation_tokens().remove(0).token;
+
+        let request = Request::get(format!("/retrieve/{}", token))
+            .header(header::ACCEPT, "application/vnd.api+json")
+            .body(Body::empty())
+            .unwrap();
+        let (status, body) = send(&state, request).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["type"], "reservation");
+        assert_eq!(body["data"]["id"], confirmed.id.to_string());
+        assert_eq!(body["data"]["attributes"]["user_email"], "john@example.com");
+        assert!(body["data"]["attributes"].get("event").is_none());
+        assert_eq!(body["data"]["relationships"]["event"]["data"]["id"], event.id.to_string());
+        assert_eq!(body["data"]["relationships"]["reservation_tokens"]["data"].as_array().unwrap().len(), 2);
+
+        let included = body["included"].as_array().unwrap();
+        let event_resource = included.iter().find(|resource| resource["type"] == "event").unwrap();
+        assert_eq!(event_resource["id"], event.id.to_string());
+        assert_eq!(event_resource["attributes"]["name"], "Test Event");
+        assert_eq!(included.iter().filter(|resource| resource["type"] == "reservation_token").count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_reserve_skips_verification_when_event_allows() {
+        let provider = Arc::new(RecordingProvider::default());
+        let state = test_state_with_provider(provider.clone()).await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(models::NewEvent {
+            skip_verification: true,
+            ..test_new_event(50)
+        }).await.unwrap();
+
+        let (status, body) = send(&state, reserve_request(event.id, "staff@example.com", 2)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "Confirmed");
+
+        let reservation_id = Uuid::parse_str(body["reservation_id"].as_str().unwrap()).unwrap();
+        let confirmed = db.get_confirmed_reservation_by_id(&reservation_id).await.unwrap();
+        assert_eq!(confirmed.get_active_reservation_tokens().len(), 2);
+
+        let sent = provider.sent.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        assert!(sent[0].subject.starts_with("Reservation Confirmed"));
+    }
+
+    #[tokio::test]
+    async fn test_retrieve_pending_reservation_requires_flag() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+
+        let (status, _) = send(&state, reserve_request(event.id, "john@example.com", 2)).await;
+        assert_eq!(status, StatusCode::OK);
+        let pending = db.get_pending_reservation_by_email(&event.id, "john@example.com").await.unwrap();
+        let token = pending.verification_token.0;
+
+        let (status, _) = send(&state, Request::get(format!("/retrieve/{}", token)).body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (status, body) = send(&state, Request::get(format!("/retrieve/{}?include_pending=true", token)).body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "Pending");
+        assert_eq!(body["reservation_id"], pending.id.to_string());
+        assert_eq!(body["event"]["id"], event.id.to_string());
+        assert!(body.get("reservation_tokens").is_none());
+        assert!(body.get("user_email").is_none());
+    }
+
+    fn patch_status(event_id: Uuid, status: &str) -> Request<Body> {
+        Request::patch(format!("/events/{}/status", event_id))
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(json!({ "status": status }).to_string()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_update_event_status_transitions() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+
+        let (status, _) = send(&state, patch_status(event.id, "cancelled")).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(db.get_event_status(&event.id).await.unwrap(), models::EventStatus::Open);
+
+        let (status, body) = send(&state, with_api_key(patch_status(event.id, "cancelled"), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "Cancelled");
+        assert!(matches!(db.get_event_by_id(&event.id).await.unwrap(), models::AnyEvent::Cancelled(_)));
+        assert!(db.get_open_event_by_id(&event.id).await.is_err());
+
+        // Cancellation is final
+        let (status, body) = send(&state, with_api_key(patch_status(event.id, "open"), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["error"], "Cannot change event status from cancelled to open");
+        assert_eq!(db.get_event_status(&event.id).await.unwrap(), models::EventStatus::Cancelled);
+    }
+
+    #[tokio::test]
+    async fn test_dedupe_pending_reservations() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+
+        let prepare = |email: &str| models::CreatingReservation::prepare(event.id, "Test User".to_string(), email.to_string(), 1, None);
+        let original = db.insert_reservation(prepare("john@example.com")).await.unwrap();
+        let duplicate = db.insert_reservation(prepare("john.legacy@example.com")).await.unwrap();
+        let unrelated = db.insert_reservation(prepare("jane@example.com")).await.unwrap();
+
+        // Rows stored before emails were normalized can still differ only by case
+        sqlx::query("UPDATE reservations SET user_email = 'John@Example.com' WHERE id = ?")
+            .bind(duplicate.id.to_string())
+            .execute(&state.pool)
+            .await
+            .unwrap();
+
+        let (status, body) = send(&state, with_api_key(post_json(&format!("/admin/reservations/dedupe?event_id={}", event.id), json!({})), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["kept_reservation_ids"], json!([original.id]));
+        assert_eq!(body["cancelled_reservation_ids"], json!([duplicate.id]));
+
+        assert!(db.get_pending_reservation_by_id(&original.id).await.is_ok());
+        assert!(db.get_pending_reservation_by_id(&duplicate.id).await.is_err());
+        assert!(db.get_pending_reservation_by_id(&unrelated.id).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_verify_email_redirects_when_requested() {
+        let mut state = test_state().await;
+        state.config.confirm_redirect_url = Some("https://app.example.com/confirmed".to_string());
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+
+        send(&state, reserve_request(event.id, "john@example.com", 1)).await;
+        let pending = db.get_pending_reservation_by_email(&event.id, "john@example.com").await.unwrap();
+
+        let response = app(state.clone())
+            .oneshot(Request::get(format!("/verify/{}?redirect=true", pending.verification_token.0)).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FOUND);
+        assert_eq!(
+            response.headers()[header::LOCATION],
+            format!("https://app.example.com/confirmed?reservation_id={}", pending.id).as_str()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_verify_email_expanded_returns_retrieve_payload() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+
+        send(&state, reserve_request(event.id, "john@example.com", 2)).await;
+        let pending = db.get_pending_reservation_by_email(&event.id, "john@example.com").await.unwrap();
+
+        let uri = format!("/verify/{}?expand=true", pending.verification_token.0);
+        let (status, body) = send(&state, Request::get(uri).body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["reservation_id"], pending.id.to_string());
+        assert_eq!(body["status"], "Confirmed");
+        assert_eq!(body["event"]["id"], event.id.to_string());
+
+        let confirmed = db.get_confirmed_reservation_by_id(&pending.id).await.unwrap();
+        let mut expected: Vec<String> = confirmed.get_active_reservation_tokens().into_iter().map(|token| token.token).collect();
+        let mut tokens: Vec<String> = body["reservation_tokens"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|token| token["token"].as_str().unwrap().to_string())
+            .collect();
+        expected.sort();
+        tokens.sort();
+        assert_eq!(tokens, expected);
+    }
+
+    #[tokio::test]
+    async fn test_unscan_reservation_token() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+
+        let confirmed = create_confirmed_reservation(&db, event.id, "john@example.com", 2).await;
+        let mut tokens = confirmed.get_active_reservation_tokens();
+        let used = db.mark_reservation_token_used(tokens.remove(0), None).await.unwrap();
+
+        let uri = format!("/reservation-tokens/{}/unscan", used.token);
+        let (status, body) = send(&state, with_api_key(post_json(&uri, json!({})), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "Active");
+        assert!(body["used_at"].is_null());
+        assert!(db.get_reservation_token(&used.token).await.unwrap().is_active());
+
+        // Unscanning again is a conflict, the token is no longer used
+        let (status, _) = send(&state, with_api_key(post_json(&uri, json!({})), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+
+        db.expire_active_tokens_for_event(&event.id).await.unwrap();
+        let (status, body) = send(&state, with_api_key(post_json(&uri, json!({})), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["error"], "Cannot unscan an expired reservation token");
+    }
+
+    #[tokio::test]
+    async fn test_rescan_within_dedupe_window_succeeds() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+
+        let confirmed = create_confirmed_reservation(&db, event.id, "john@example.com", 1).await;
+        let token = confirmed.get_active_reservation_tokens().remove(0);
+
+        let uri = format!("/reservation-tokens/{}/scan", token.token);
+        let (status, first) = send(&state, with_api_key(post_json(&uri, json!({})), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(first["status"], "Used");
+
+        let (status, second) = send(&state, with_api_key(post_json(&uri, json!({})), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(second["used_at"], first["used_at"]);
+    }
+
+    #[tokio::test]
+    async fn test_rescan_outside_dedupe_window_is_rejected() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+
+        let confirmed = create_confirmed_reservation(&db, event.id, "john@example.com", 1).await;
+        let used = db.mark_reservation_token_used(confirmed.get_active_reservation_tokens().remove(0), None).await.unwrap();
+        sqlx::query("UPDATE reservation_tokens SET used_at = ? WHERE token = ?")
+            .bind(OffsetDateTime::now_utc() - Duration::minutes(1))
+            .bind(&used.token)
+            .execute(&state.pool)
+            .await
+            .unwrap();
+
+        let uri = format!("/reservation-tokens/{}/scan", used.token);
+        let (status, body) = send(&state, with_api_key(post_json(&uri, json!({})), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["error"], "Reservation token already used");
+    }
+
+    #[tokio::test]
+    async fn test_scan_requires_matching_name_when_event_asks_for_it() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(models::NewEvent {
+            require_name_match: true,
+            ..test_new_event(50)
+        }).await.unwrap();
+
+        let confirmed = create_confirmed_reservation(&db, event.id, "john@example.com", 1).await;
+        let token = confirmed.get_active_reservation_tokens().remove(0);
+        let scan = |name: &str| with_api_key(post_json(&format!("/reservation-tokens/{}/scan?name={}", token.token, name), json!({})), TEST_API_KEY);
+
+        let (status, body) = send(&state, scan("Someone%20Else")).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["error"], "Guest name does not match the reservation");
+
+        let (status, body) = send(&state, scan("test%20USER")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "Used");
+    }
+
+    #[tokio::test]
+    async fn test_scan_by_short_code_marks_that_token_used() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+        create_confirmed_reservation(&db, event.id, "john@example.com", 2).await;
+        create_confirmed_reservation(&db, event.id, "jane@example.com", 1).await;
+
+        let tokens = db.get_tokens_for_event(&event.id, None).await.unwrap();
+        let codes: std::collections::HashSet<_> = tokens.iter().map(|token| token.short_code().unwrap().to_string()).collect();
+        assert_eq!(codes.len(), 3);
+        let target = &tokens[1];
+        let short_code = target.short_code().unwrap();
+        assert_eq!(short_code.len(), models::SHORT_CODE_LENGTH);
+
+        // Typed codes are matched case-insensitively
+        let uri = format!("/reservation-tokens/{}/scan?event_id={}", short_code.to_lowercase(), event.id);
+        let (status, body) = send(&state, with_api_key(post_json(&uri, json!({})), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["token"], target.token());
+        assert_eq!(body["short_code"], short_code);
+        assert_eq!(body["status"], "Used");
+
+        let used = db.get_tokens_for_event(&event.id, Some("used")).await.unwrap();
+        assert_eq!(used.len(), 1);
+        assert_eq!(used[0].token(), target.token());
+
+        // The code means nothing without its event
+        let other_event = db.create_event(test_new_event(50)).await.unwrap();
+        let uri = format!("/reservation-tokens/{}/scan?event_id={}", short_code, other_event.id);
+        let (status, _) = send(&state, with_api_key(post_json(&uri, json!({})), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_remaining_checkins_decrease_as_tokens_are_scanned() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+
+        let confirmed = create_confirmed_reservation(&db, event.id, "john@example.com", 3).await;
+        let uri = format!("/reservations/{}/remaining-checkins", confirmed.id);
+
+        let (status, body) = send(&state, Request::get(&uri).body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["spot_count"], 3);
+        assert_eq!(body["remaining_checkins"], 3);
+
+        let mut tokens = confirmed.get_active_reservation_tokens();
+        db.mark_reservation_token_used(tokens.remove(0), None).await.unwrap();
+        let (_, body) = send(&state, Request::get(&uri).body(Body::empty()).unwrap()).await;
+        assert_eq!(body["remaining_checkins"], 2);
+
+        db.mark_reservation_token_used(tokens.remove(0), None).await.unwrap();
+        let (_, body) = send(&state, Request::get(&uri).body(Body::empty()).unwrap()).await;
+        assert_eq!(body["remaining_checkins"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_verification_status_is_false_while_pending() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+        let pending = db.insert_reservation(
+            models::CreatingReservation::prepare(event.id, "Test User".to_string(), "john@example.com".to_string(), 1, None)
+        ).await.unwrap();
+
+        let request = Request::get(format!("/reservations/{}/verified", pending.id)).body(Body::empty()).unwrap();
+        let (status, body) = send(&state, request).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, json!({ "verified": false, "verified_at": null }));
+    }
+
+    #[tokio::test]
+    async fn test_verification_status_is_true_with_timestamp_once_confirmed() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+        let confirmed = create_confirmed_reservation(&db, event.id, "john@example.com", 1).await;
+
+        let request = Request::get(format!("/reservations/{}/verified", confirmed.id)).body(Body::empty()).unwrap();
+        let (status, body) = send(&state, request).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["verified"], true);
+        let verified_at = OffsetDateTime::parse(body["verified_at"].as_str().unwrap(), &time::format_description::well_known::Iso8601::DEFAULT).unwrap();
+        assert_eq!(verified_at.unix_timestamp(), confirmed.status.verified_at.unix_timestamp());
+    }
+
+    async fn set_questions(state: &AppState, event_id: Uuid) {
+        let request = Request::put(format!("/events/{}/questions", event_id))
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(json!([
+                { "key": "tshirt", "prompt": "T-shirt size", "required": true },
+                { "key": "company", "prompt": "Company" },
+            ]).to_string()))
+            .unwrap();
+        let (status, _) = send(state, with_api_key(request, TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_reserve_rejects_unanswered_required_question() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+        set_questions(&state, event.id).await;
+
+        let (status, body) = send(&state, post_json("/reserve", json!({
+            "event_id": event.id,
+            "user_name": "John Doe",
+            "user_email": "john@example.com",
+            "spot_count": 1,
+            "answers": { "company": "Acme" },
+        }))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "Missing answer to required question: tshirt");
+    }
+
+    #[tokio::test]
+    async fn test_reserve_persists_valid_answers() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+        set_questions(&state, event.id).await;
+
+        let (status, _) = send(&state, post_json("/reserve", json!({
+            "event_id": event.id,
+            "user_name": "John Doe",
+            "user_email": "john@example.com",
+            "spot_count": 1,
+            "answers": { "tshirt": "M", "company": "Acme" },
+        }))).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let request = Request::get(format!("/admin/events/{}/answers", event.id)).body(Body::empty()).unwrap();
+        let (status, body) = send(&state, with_api_key(request, TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body[0]["user_email"], "john@example.com");
+        assert_eq!(body[0]["answers"], json!({ "tshirt": "M", "company": "Acme" }));
+    }
+
+    #[tokio::test]
+    async fn test_reserve_with_party_name_shows_on_admin_roster() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+
+        let (status, _) = send(&state, post_json("/reserve", json!({
+            "event_id": event.id,
+            "user_name": "John Doe",
+            "user_email": "john@example.com",
+            "spot_count": 4,
+            "party_name": "Doe family",
+        }))).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = send(&state, reserve_request(event.id, "jane@example.com", 1)).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let request = Request::get(format!("/admin/events/{}/reservations", event.id)).body(Body::empty()).unwrap();
+        let (status, body) = send(&state, with_api_key(request, TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body[0]["user_email"], "john@example.com");
+        assert_eq!(body[0]["party_name"], "Doe family");
+        assert_eq!(body[1]["user_email"], "jane@example.com");
+        assert_eq!(body[1]["party_name"], body[1]["user_name"]);
+    }
+
+    #[tokio::test]
+    async fn test_reserve_reports_verification_deadline_when_ttl_configured() {
+        let mut state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+
+        let (_, body) = send(&state, reserve_request(event.id, "jane@example.com", 1)).await;
+        assert!(body.get("verification_expires_at").is_none());
+
+        state.config.verification_ttl_hours = Some(48);
+        let (status, body) = send(&state, reserve_request(event.id, "john@example.com", 1)).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let pending = db.get_pending_reservation_by_email(&event.id, "john@example.com").await.unwrap();
+        let expires_at = OffsetDateTime::parse(
+            body["verification_expires_at"].as_str().unwrap(),
+            &time::format_description::well_known::Iso8601::DEFAULT,
+        ).unwrap();
+        assert_eq!(expires_at, pending.status.created_at + Duration::hours(48));
+    }
+
+    #[tokio::test]
+    async fn test_admin_events_include_finished_events() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let open = db.create_event(test_new_event(50)).await.unwrap();
+        let finished = db.create_event(test_new_event(50)).await.unwrap();
+        create_confirmed_reservation(&db, finished.id, "john@example.com", 3).await;
+        db.set_event_status(&finished.id, models::EventStatus::Finished).await.unwrap();
+
+        let (_, public) = send(&state, Request::get("/events/search").body(Body::empty()).unwrap()).await;
+        let public_ids: Vec<&str> = public.as_array().unwrap().iter().map(|e| e["id"].as_str().unwrap()).collect();
+        assert_eq!(public_ids, vec![open.id.to_string()]);
+
+        let (status, body) = send(&state, with_api_key(Request::get("/admin/events").body(Body::empty()).unwrap(), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body.as_array().unwrap().len(), 2);
+
+        let (_, body) = send(&state, with_api_key(Request::get("/admin/events?status=finished").body(Body::empty()).unwrap(), TEST_API_KEY)).await;
+        let events = body.as_array().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0]["id"], finished.id.to_string());
+        assert_eq!(events[0]["status"], "Finished");
+        assert_eq!(events[0]["reservation_count"], 1);
+        assert_eq!(events[0]["reserved_spots"], 3);
+    }
+
+    #[tokio::test]
+    async fn test_daily_events_report_counts_for_that_day() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let day = (OffsetDateTime::now_utc() + Duration::days(2)).date();
+        let day_start = day.midnight().assume_utc();
+
+        let mut morning = test_new_event(20);
+        morning.start_time = day_start + Duration::hours(9);
+        morning.end_time = morning.start_time + Duration::hours(2);
+        let morning = db.create_event(morning).await.unwrap();
+
+        let mut evening = test_new_event(40);
+        evening.start_time = day_start + Duration::hours(19);
+        evening.end_time = evening.start_time + Duration::hours(2);
+        let evening = db.create_event(evening).await.unwrap();
+
+        let mut next_day = test_new_event(10);
+        next_day.start_time = day_start + Duration::hours(33);
+        next_day.end_time = next_day.start_time + Duration::hours(2);
+        db.create_event(next_day).await.unwrap();
+
+        create_confirmed_reservation(&db, morning.id, "john@example.com", 3).await;
+        create_confirmed_reservation(&db, evening.id, "jane@example.com", 2).await;
+        create_confirmed_reservation(&db, evening.id, "joe@example.com", 4).await;
+
+        let request = Request::get(format!("/admin/daily?date={}", day)).body(Body::empty()).unwrap();
+        let (status, body) = send(&state, with_api_key(request, TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let events = body.as_array().unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0]["id"], morning.id.to_string());
+        assert_eq!(events[0]["capacity"], 20);
+        assert_eq!(events[0]["reserved_spots"], 3);
+        assert_eq!(events[1]["id"], evening.id.to_string());
+        assert_eq!(events[1]["capacity"], 40);
+        assert_eq!(events[1]["reserved_spots"], 6);
+
+        let request = Request::get("/admin/daily?date=tomorrow").body(Body::empty()).unwrap();
+        let (status, _) = send(&state, with_api_key(request, TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    fn csv_import_request(body: &str) -> Request<Body> {
+        let request = Request::post("/admin/events/import.csv")
+            .header(header::CONTENT_TYPE, "text/csv")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        with_api_key(request, TEST_API_KEY)
+    }
+
+    #[tokio::test]
+    async fn test_import_events_csv() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+
+        let csv = "\
+name,description,location,start_time,end_time,capacity,max_spots_per_reservation
+Workshop,Hands-on,Room 1,2030-05-01T18:00:00Z,2030-05-01T20:00:00Z,20,4
+Meetup,,,2030-05-02T18:00:00Z,2030-05-02T21:00:00Z,50,
+";
+        let (status, body) = send(&state, csv_import_request(csv)).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(body["imported"], 2);
+
+        let events = db.get_all_open_events().await.unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].name, "Workshop");
+        assert_eq!(events[0].max_spots_per_reservation, Some(4));
+        assert_eq!(events[1].location, None);
+    }
+
+    #[tokio::test]
+    async fn test_import_events_csv_rolls_back_on_bad_row() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+
+        let csv = "\
+name,description,location,start_time,end_time,capacity,max_spots_per_reservation
+Workshop,Hands-on,Room 1,2030-05-01T18:00:00Z,2030-05-01T20:00:00Z,20,4
+Meetup,,,2030-05-02T18:00:00Z,2030-05-02T21:00:00Z,50,
+Broken,,,next tuesday,2030-05-03T21:00:00Z,10,
+";
+        let (status, body) = send(&state, csv_import_request(csv)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "Line 4: start_time must be an RFC 3339 timestamp, got \"next tuesday\"");
+
+        assert!(db.get_all_open_events().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reserve_rejects_blocked_email_domain() {
+        let mut state = test_state().await;
+        state.config.blocked_email_domains = vec!["mailinator.com".to_string()];
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+
+        let (status, body) = send(&state, reserve_request(event.id, "spam@MailInator.com", 1)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "Email domain not allowed");
+    }
+
+    #[tokio::test]
+    async fn test_reserve_allows_unblocked_email_domain() {
+        let mut state = test_state().await;
+        state.config.blocked_email_domains = vec!["mailinator.com".to_string()];
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+
+        let (status, _) = send(&state, reserve_request(event.id, "john@example.com", 1)).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_malformed_path_ids_are_not_found() {
+        let state = test_state().await;
+
+        let (status, body) = send(&state, Request::get("/events/not-a-uuid").body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["code"], "not_found");
+
+        let (status, _) = send(&state, Request::get("/retrieve/not-a-uuid").body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (status, _) = send(&state, with_api_key(post_json("/reservations/not-a-uuid/move", json!({ "target_event_id": Uuid::new_v4() })), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_route_returns_json_404() {
+        let state = test_state().await;
+
+        let (status, body) = send(&state, Request::get("/no/such/route").body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body, json!({ "error": "Resource not found", "code": "not_found", "status": 404 }));
+    }
+
+    #[tokio::test]
+    async fn test_handler_running_past_request_timeout_gets_504() {
+        let mut state = test_state().await;
+        state.config.request_timeout_seconds = 1;
+
+        let router = Router::new()
+            .route("/slow", get(|| async {
+                tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+                "done"
+            }))
+            .layer(middleware::from_fn_with_state(state.clone(), request_timeout))
+            .with_state(state);
 
-    Ok(router.into())
+        let response = router.oneshot(Request::get("/slow").body(Body::empty()).unwrap()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let body: Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["code"], "timeout");
+    }
+
+    #[tokio::test]
+    async fn test_rotating_ticket_scans_only_in_current_window() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(models::NewEvent {
+            token_rotation_minutes: Some(5),
+            ..test_new_event(50)
+        }).await.unwrap();
+
+        let confirmed = create_confirmed_reservation(&db, event.id, "john@example.com", 2).await;
+        let mut tokens = confirmed.get_active_reservation_tokens();
+        let (fresh, stale) = (tokens.remove(0), tokens.remove(0));
+
+        let (_, body) = send(&state, Request::get(format!("/retrieve/{}", fresh.token)).body(Body::empty()).unwrap()).await;
+        assert!(body["tokens_rotate_at"].is_string());
+        let current_code = body["reservation_tokens"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|token| token["token"].as_str().unwrap())
+            .find(|code| code.starts_with(&fresh.token))
+            .unwrap()
+            .to_string();
+
+        let uri = format!("/reservation-tokens/{}/scan", current_code);
+        let (status, body) = send(&state, with_api_key(post_json(&uri, json!({})), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "Used");
+
+        let stale_code = models::RotatedToken::issue(
+            &stale.token,
+            &state.config.jwt_secret,
+            5,
+            OffsetDateTime::now_utc() - Duration::minutes(10),
+        );
+        let uri = format!("/reservation-tokens/{}/scan", stale_code);
+        let (status, body) = send(&state, with_api_key(post_json(&uri, json!({})), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["error"], "Ticket code has expired; refresh it and scan again");
+
+        // The bare token no longer scans for a rotating event
+        let uri = format!("/reservation-tokens/{}/scan", stale.token);
+        let (status, _) = send(&state, with_api_key(post_json(&uri, json!({})), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn test_get_event_by_slug() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+        assert!(event.slug.starts_with("test-event-"));
+
+        let (status, body) = send(&state, Request::get(format!("/events/slug/{}", event.slug)).body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["id"], event.id.to_string());
+        assert_eq!(body["slug"], event.slug);
+
+        let (status, _) = send(&state, Request::get("/events/slug/no-such-event").body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_version_reports_crate_and_schema() {
+        let state = test_state().await;
+
+        let (status, body) = send(&state, Request::get("/version").body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(body["migration_version"], 28);
+    }
+
+    #[tokio::test]
+    async fn test_migration_health_reports_up_to_date_schema() {
+        let state = test_state().await;
+
+        let (status, body) = send(&state, Request::get("/health/migrations").body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, json!({ "up_to_date": true, "pending": [] }));
+
+        // Forget the latest migration, as if the database predates this build
+        let latest: i64 = sqlx::query_scalar("SELECT MAX(version) FROM _sqlx_migrations").fetch_one(&state.pool).await.unwrap();
+        sqlx::query("DELETE FROM _sqlx_migrations WHERE version = ?").bind(latest).execute(&state.pool).await.unwrap();
+        let (_, body) = send(&state, Request::get("/health/migrations").body(Body::empty()).unwrap()).await;
+        assert_eq!(body["up_to_date"], false);
+        assert_eq!(body["pending"][0]["version"], latest);
+        assert_eq!(body["pending"][0]["reason"], "not_applied");
+    }
+
+    #[tokio::test]
+    async fn test_verify_email_tolerates_trailing_slash() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+
+        send(&state, reserve_request(event.id, "john@example.com", 1)).await;
+        let pending = db.get_pending_reservation_by_email(&event.id, "john@example.com").await.unwrap();
+
+        let uri = format!("/verify/{}/", pending.verification_token.0);
+        let (status, body) = send(&state, Request::get(uri).body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["reservation_id"], pending.id.to_string());
+    }
+
+    #[tokio::test]
+    async fn test_admin_endpoints_require_valid_api_key() {
+        let state = test_state().await;
+        let request = || Request::get("/admin/email-log").body(Body::empty()).unwrap();
+
+        let (status, _) = send(&state, with_api_key(request(), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (status, body) = send(&state, with_api_key(request(), "wrong-key")).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["code"], "unauthorized");
+
+        let (status, _) = send(&state, request()).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_resend_verification_by_email() {
+        let provider = Arc::new(RecordingProvider::default());
+        let state = test_state_with_provider(provider.clone()).await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+
+        let (status, _) = send(&state, reserve_request(event.id, "john@example.com", 1)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(provider.sent_count(), 1);
+
+        // Matching pending reservation: verification is re-sent
+        let (status, _) = send(&state, post_json("/reservations/resend-by-email", json!({
+            "event_id": event.id,
+            "user_email": "john@example.com",
+        }))).await;
+        assert_eq!(status, StatusCode::ACCEPTED);
+        assert_eq!(provider.sent_count(), 2);
+
+        // No match: same response, nothing sent
+        let (status, _) = send(&state, post_json("/reservations/resend-by-email", json!({
+            "event_id": event.id,
+            "user_email": "nobody@example.com",
+        }))).await;
+        assert_eq!(status, StatusCode::ACCEPTED);
+        assert_eq!(provider.sent_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_resend_verification_by_email_hides_failed_send() {
+        let state = test_state_with_provider(Arc::new(FailingProvider)).await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+        db.insert_reservation(
+            models::CreatingReservation::prepare(event.id, "John".to_string(), "john@example.com".to_string(), 1, None)
+        ).await.unwrap();
+
+        // Same answer as for an address with no reservation; the failure only shows in the email log
+        let (status, _) = send(&state, post_json("/reservations/resend-by-email", json!({
+            "event_id": event.id,
+            "user_email": "john@example.com",
+        }))).await;
+        assert_eq!(status, StatusCode::ACCEPTED);
+
+        let (_, body) = send(&state, with_api_key(Request::get("/admin/email-log?email=john@example.com").body(Body::empty()).unwrap(), TEST_API_KEY)).await;
+        assert_eq!(body[0]["status"], "failed");
+    }
+
+    #[tokio::test]
+    async fn test_verification_emails_are_throttled_per_address() {
+        let mut state = test_state().await;
+        state.config.verification_emails_per_hour = 3;
+        let db = Database { pool: state.pool.clone() };
+
+        // Reserve at one event, then keep asking for the link again
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+        let (status, _) = send(&state, reserve_request(event.id, "john@example.com", 1)).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let resend = || post_json("/reservations/resend-by-email", json!({ "event_id": event.id, "user_email": "john@example.com" }));
+        for _ in 1..3 {
+            let (status, _) = send(&state, resend()).await;
+            assert_eq!(status, StatusCode::ACCEPTED);
+        }
+
+        let (status, body) = send(&state, resend()).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(body["code"], "too_many_requests");
+
+        // The limit is per address, across events, and applies to new reservations too
+        let other_event = db.create_event(test_new_event(50)).await.unwrap();
+        let (status, _) = send(&state, reserve_request(other_event.id, "JOHN@example.com", 1)).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        let (status, _) = send(&state, reserve_request(other_event.id, "jane@example.com", 1)).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_cors_layer_applies_max_age() {
+        let mut config = Config::from_env().unwrap();
+        config.cors_allowed_origins = vec!["http://localhost:5173".to_string()];
+        config.cors_max_age_seconds = 600;
+
+        let router = Router::new()
+            .route("/", get(hello_world))
+            .layer(cors_layer(&config));
+
+        let preflight = Request::options("/")
+            .header(header::ORIGIN, "http://localhost:5173")
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+            .body(Body::empty())
+            .unwrap();
+        let response = router.oneshot(preflight).await.unwrap();
+
+        assert_eq!(response.headers()[header::ACCESS_CONTROL_MAX_AGE], "600");
+        assert_eq!(response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN], "http://localhost:5173");
+    }
+
+    #[tokio::test]
+    async fn test_spot_labels_round_trip_on_retrieve() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+
+        let (status, body) = send(&state, post_json("/reserve", json!({
+            "event_id": event.id,
+            "user_name": "Test User",
+            "user_email": "john@example.com",
+            "spot_count": 2,
+            "labels": ["Row A Seat 3", "Row A Seat 4"],
+        }))).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let reservation_id = Uuid::parse_str(body["reservation_id"].as_str().unwrap()).unwrap();
+        let pending = db.get_pending_reservation_by_id(&reservation_id).await.unwrap();
+        let uri = format!("/verify/{}", pending.verification_token.0);
+        let (status, _) = send(&state, Request::get(uri).body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let confirmed = db.get_confirmed_reservation_by_id(&reservation_id).await.unwrap();
+        let uri = format!("/retrieve/{}", confirmed.status.reservation_tokens[0].token());
+        let (status, body) = send(&state, Request::get(uri).body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let mut labels: Vec<&str> = body["reservation_tokens"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|token| token["label"].as_str().unwrap())
+            .collect();
+        labels.sort();
+        assert_eq!(labels, vec!["Row A Seat 3", "Row A Seat 4"]);
+    }
+
+    #[tokio::test]
+    async fn test_reserve_rejects_mismatched_label_count() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+
+        let (status, body) = send(&state, post_json("/reserve", json!({
+            "event_id": event.id,
+            "user_name": "Test User",
+            "user_email": "john@example.com",
+            "spot_count": 2,
+            "labels": ["Row A Seat 3"],
+        }))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "Number of labels must match spot count");
+    }
+
+    #[tokio::test]
+    async fn test_event_tokens_require_api_key_and_include_full_events() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(2)).await.unwrap();
+        create_confirmed_reservation(&db, event.id, "john@example.com", 2).await;
+
+        sqlx::query("UPDATE events SET status = 'full' WHERE id = ?")
+            .bind(event.id.to_string())
+            .execute(&db.pool)
+            .await
+            .unwrap();
+
+        let request = || Request::get(format!("/events/{}/tokens", event.id)).body(Body::empty()).unwrap();
+        let (status, _) = send(&state, request()).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let (status, body) = send(&state, with_api_key(request(), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body.as_array().unwrap().len(), 2);
+
+        let unknown = Request::get(format!("/events/{}/tokens", Uuid::new_v4())).body(Body::empty()).unwrap();
+        let (status, _) = send(&state, with_api_key(unknown, TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_reservation_timeline_for_full_event() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(3)).await.unwrap();
+        create_confirmed_reservation(&db, event.id, "john@example.com", 2).await;
+        create_confirmed_reservation(&db, event.id, "jane@example.com", 1).await;
+        db.set_event_status(&event.id, models::EventStatus::Full).await.unwrap();
+
+        let request = || Request::get(format!("/events/{}/reservation-timeline?bucket=day", event.id)).body(Body::empty()).unwrap();
+        let (status, _) = send(&state, request()).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let (status, body) = send(&state, with_api_key(request(), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["event_id"], event.id.to_string());
+        let buckets = body["buckets"].as_array().unwrap();
+        assert_eq!(buckets.len(), 1);
+        assert_eq!(buckets[0]["spots"], 3);
+    }
+
+    #[tokio::test]
+    async fn test_expire_active_event_tokens_requires_api_key() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+        create_confirmed_reservation(&db, event.id, "john@example.com", 2).await;
+
+        let uri = format!("/events/{}/tokens/expire-active", event.id);
+        let (status, _) = send(&state, post_json(&uri, json!({}))).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(db.get_tokens_for_event(&event.id, Some("active")).await.unwrap().len(), 2);
+
+        let (status, body) = send(&state, with_api_key(post_json(&uri, json!({})), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["expired_count"], 2);
+        assert!(db.get_tokens_for_event(&event.id, Some("active")).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_move_reservation_to_another_event() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let source = db.create_event(test_new_event(50)).await.unwrap();
+        let target = db.create_event(test_new_event(50)).await.unwrap();
+        let confirmed = create_confirmed_reservation(&db, source.id, "john@example.com", 2).await;
+        let mut old_tokens: Vec<String> = confirmed.status.reservation_tokens.iter().map(|t| t.token().to_string()).collect();
+
+        let uri = format!("/reservations/{}/move", confirmed.id);
+        let (status, _) = send(&state, post_json(&uri, json!({ "target_event_id": target.id }))).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(db.count_event_reservations(&source.id, false).await.unwrap(), 2);
+
+        let (status, body) = send(&state, with_api_key(post_json(&uri, json!({ "target_event_id": target.id })), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["event_id"], target.id.to_string());
+        assert_eq!(body["reservation_tokens"].as_array().unwrap().len(), 2);
+
+        assert_eq!(db.count_event_reservations(&source.id, false).await.unwrap(), 0);
+        assert_eq!(db.count_event_reservations(&target.id, false).await.unwrap(), 2);
+
+        let expired = db.get_tokens_for_event(&target.id, Some("expired")).await.unwrap();
+        let mut expired: Vec<String> = expired.iter().map(|t| t.token().to_string()).collect();
+        expired.sort();
+        old_tokens.sort();
+        assert_eq!(expired, old_tokens);
+        assert_eq!(db.get_tokens_for_event(&target.id, Some("active")).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_reservation_by_token() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+        let confirmed = create_confirmed_reservation(&db, event.id, "john@example.com", 2).await;
+
+        let uri = format!("/cancel/{}", confirmed.status.cancellation_token);
+        let (status, body) = send(&state, post_json(&uri, json!({}))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["reservation_id"], confirmed.id.to_string());
+        assert_eq!(body["spot_count"], 2);
+
+        assert_eq!(db.count_event_reservations(&event.id, false).await.unwrap(), 0);
+        assert!(db.get_tokens_for_event(&event.id, Some("active")).await.unwrap().is_empty());
+
+        // The token only works once
+        let (status, _) = send(&state, post_json(&uri, json!({}))).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_records_cancelled_at() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+        let confirmed = create_confirmed_reservation(&db, event.id, "john@example.com", 2).await;
+        let token = confirmed.get_active_reservation_tokens().remove(0);
+
+        let uri = format!("/cancel/{}", confirmed.status.cancellation_token);
+        let (status, body) = send(&state, post_json(&uri, json!({}))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body["cancelled_at"].is_string());
+
+        let request = Request::get(format!("/admin/tokens/{}", token.token)).body(Body::empty()).unwrap();
+        let (status, record) = send(&state, with_api_key(request, TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(record["reservation_status"], "cancelled");
+        assert_eq!(record["reservation_cancelled_at"], body["cancelled_at"]);
+
+        // Retrieve, verification status and the email status lookup all report the same cancellation
+        let (status, retrieved) = send(&state, Request::get(format!("/retrieve/{}", token.token)).body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(retrieved["status"], "Cancelled");
+        assert_eq!(retrieved["cancelled_at"], body["cancelled_at"]);
+        assert!(retrieved.get("reservation_tokens").is_none());
+
+        let (status, verified) = send(&state, Request::get(format!("/reservations/{}/verified", confirmed.id)).body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(verified["verified"], true);
+        assert_eq!(verified["cancelled_at"], body["cancelled_at"]);
+
+        let (_, reservation_status) = send(&state, reservation_status_request(event.id, "john@example.com")).await;
+        assert_eq!(reservation_status, json!({ "has_reservation": false, "status": "cancelled", "cancelled_at": body["cancelled_at"] }));
+
+        // The audit entry carries the same instant as the reservation row
+        let (cancelled_at, audited_at): (Option<i64>, i64) = sqlx::query_as(
+            "SELECT r.cancelled_at, a.at FROM reservations r JOIN reservation_audit a ON a.reservation_id = r.id AND a.action = 'cancel' WHERE r.id = ?",
+        )
+        .bind(confirmed.id.to_string())
+        .fetch_one(&state.pool)
+        .await
+        .unwrap();
+        assert_eq!(cancelled_at, Some(audited_at));
+    }
+
+    async fn cancel_and_get_refund_status(state: &AppState, hours_until_start: i64) -> (serde_json::Value, String) {
+        let db = Database { pool: state.pool.clone() };
+        let mut new_event = test_new_event(50);
+        new_event.start_time = OffsetDateTime::now_utc() + Duration::hours(hours_until_start);
+        new_event.end_time = new_event.start_time + Duration::hours(2);
+        let event = db.create_event(new_event).await.unwrap();
+        let confirmed = create_confirmed_reservation(&db, event.id, "john@example.com", 1).await;
+
+        let uri = format!("/cancel/{}", confirmed.status.cancellation_token);
+        let (status, body) = send(state, post_json(&uri, json!({}))).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let stored: String = sqlx::query_scalar("SELECT refund_status FROM reservations WHERE id = ?")
+            .bind(confirmed.id.to_string())
+            .fetch_one(&state.pool)
+            .await
+            .unwrap();
+        (body["refund_status"].clone(), stored)
+    }
+
+    #[tokio::test]
+    async fn test_cancel_before_refund_cutoff_owes_refund() {
+        let mut state = test_state().await;
+        state.config.refund_cutoff_hours = Some(48);
+
+        let (refund_status, stored) = cancel_and_get_refund_status(&state, 72).await;
+        assert_eq!(refund_status, "pending");
+        assert_eq!(stored, "pending");
+    }
+
+    #[tokio::test]
+    async fn test_cancel_after_refund_cutoff_owes_nothing() {
+        let mut state = test_state().await;
+        state.config.refund_cutoff_hours = Some(48);
+
+        let (refund_status, stored) = cancel_and_get_refund_status(&state, 24).await;
+        assert_eq!(refund_status, "none");
+        assert_eq!(stored, "none");
+    }
+
+    #[tokio::test]
+    async fn test_cancel_reservation_by_token_rejected_after_event_starts() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let mut new_event = test_new_event(50);
+        new_event.start_time = OffsetDateTime::now_utc() - Duration::hours(1);
+        let event = db.create_event(new_event).await.unwrap();
+        let confirmed = create_confirmed_reservation(&db, event.id, "john@example.com", 1).await;
+
+        let uri = format!("/cancel/{}", confirmed.status.cancellation_token);
+        let (status, body) = send(&state, post_json(&uri, json!({}))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "Cancellation deadline has passed");
+        assert_eq!(db.count_event_reservations(&event.id, false).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_move_reservation_rejects_when_target_lacks_capacity() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let source = db.create_event(test_new_event(50)).await.unwrap();
+        let target = db.create_event(test_new_event(3)).await.unwrap();
+        create_confirmed_reservation(&db, target.id, "jane@example.com", 2).await;
+        let confirmed = create_confirmed_reservation(&db, source.id, "john@example.com", 2).await;
+
+        let uri = format!("/reservations/{}/move", confirmed.id);
+        let (status, body) = send(&state, with_api_key(post_json(&uri, json!({ "target_event_id": target.id })), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "Target event does not have enough capacity for this reservation");
+
+        let unchanged = db.get_confirmed_reservation_by_id(&confirmed.id).await.unwrap();
+        assert_eq!(unchanged.event_id, source.id);
+    }
+
+    #[tokio::test]
+    async fn test_move_reservation_applies_target_reservation_limits() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let source = db.create_event(test_new_event(50)).await.unwrap();
+        let confirmed = create_confirmed_reservation(&db, source.id, "john@example.com", 3).await;
+        let uri = format!("/reservations/{}/move", confirmed.id);
+
+        let small_parties = db.create_event(models::NewEvent { max_spots_per_reservation: Some(2), ..test_new_event(50) }).await.unwrap();
+        let (status, body) = send(&state, with_api_key(post_json(&uri, json!({ "target_event_id": small_parties.id })), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "Cannot reserve more than 2 spots per reservation for this event");
+
+        let one_party = db.create_event(models::NewEvent { max_reservations: Some(1), ..test_new_event(50) }).await.unwrap();
+        create_confirmed_reservation(&db, one_party.id, "jane@example.com", 1).await;
+        let (status, body) = send(&state, with_api_key(post_json(&uri, json!({ "target_event_id": one_party.id })), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "Target event has reached its maximum number of reservations");
+
+        // Spots held at checkout on the target count against it
+        let held = db.create_event(test_new_event(5)).await.unwrap();
+        db.insert_hold(models::HoldingReservation::place(held.id, 3, OffsetDateTime::now_utc(), Duration::minutes(5))).await.unwrap();
+        let (status, body) = send(&state, with_api_key(post_json(&uri, json!({ "target_event_id": held.id })), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "Target event does not have enough capacity for this reservation");
+
+        let unchanged = db.get_confirmed_reservation_by_id(&confirmed.id).await.unwrap();
+        assert_eq!(unchanged.event_id, source.id);
+    }
+
+    #[tokio::test]
+    async fn test_generated_event_has_max_spots_per_reservation() {
+        let state = test_state().await;
+
+        let (status, body) = send(&state, Request::post("/events/new").body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body["max_spots_per_reservation"].as_u64().unwrap() > 0);
+    }
+
+    #[tokio::test]
+    async fn test_generated_event_capacity_is_within_configured_range() {
+        let mut state = test_state().await;
+        state.config.random_event_min_capacity = 7;
+        state.config.random_event_max_capacity = 9;
+
+        let (status, body) = send(&state, Request::post("/events/new").body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+        let capacity = body["capacity"].as_u64().unwrap();
+        assert!((7..=9).contains(&capacity), "capacity {} outside 7..=9", capacity);
+    }
+
+    #[tokio::test]
+    async fn test_reserve_rejects_more_than_max_spots_per_reservation() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(models::NewEvent {
+            max_spots_per_reservation: Some(2),
+            visibility: models::EventVisibility::Public,
+            reservations_open_at: None,
+            reservations_close_at: None,
+            skip_verification: false,
+            require_name_match: false,
+            token_rotation_minutes: None,
+            ..test_new_event(50)
+        }).await.unwrap();
+
+        let (status, body) = send(&state, reserve_request(event.id, "john@example.com", 3)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "Cannot reserve more than 2 spots per reservation for this event");
+
+        let (status, _) = send(&state, reserve_request(event.id, "john@example.com", 2)).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_reserve_enforces_min_spots_per_reservation() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(models::NewEvent {
+            min_spots_per_reservation: Some(4),
+            ..test_new_event(50)
+        }).await.unwrap();
+
+        let (status, body) = send(&state, reserve_request(event.id, "john@example.com", 3)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "Must reserve at least 4 spots per reservation for this event");
+
+        let (status, _) = send(&state, reserve_request(event.id, "john@example.com", 4)).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_import_events_csv_enforces_configured_capacity_ceiling() {
+        let mut state = test_state().await;
+        state.config.max_event_capacity = 100;
+        let csv = |capacity: u32| format!("\
+name,description,location,start_time,end_time,capacity
+Gala,,,2030-06-01T18:00:00Z,2030-06-01T23:00:00Z,{}
+", capacity);
+
+        let (status, body) = send(&state, csv_import_request(&csv(101))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "Line 2: capacity (101) cannot exceed the maximum of 100");
+
+        let (status, body) = send(&state, csv_import_request(&csv(100))).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(body["imported"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_import_events_csv_rejects_min_above_max_spots() {
+        let state = test_state().await;
+        let csv = "\
+name,description,location,start_time,end_time,capacity,max_spots_per_reservation,min_spots_per_reservation
+Team Trivia,,,2030-06-01T18:00:00Z,2030-06-01T21:00:00Z,60,4,6
+";
+
+        let (status, body) = send(&state, csv_import_request(csv)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "Line 2: min_spots_per_reservation (6) cannot exceed max_spots_per_reservation (4)");
+    }
+
+    #[tokio::test]
+    async fn test_admin_verification_link_for_pending_reservation() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+        let pending = db.insert_reservation(
+            models::CreatingReservation::prepare(event.id, "Jane".to_string(), "jane@example.com".to_string(), 1, None)
+        ).await.unwrap();
+
+        let uri = format!("/admin/reservations/{}/verification-link", pending.id);
+        let (status, body) = send(&state, with_api_key(Request::get(&uri).body(Body::empty()).unwrap(), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["reservation_id"], pending.id.to_string());
+        let url = body["verification_url"].as_str().unwrap();
+        assert!(url.starts_with(&state.config.app_url));
+        assert!(url.ends_with(&format!("/verify/{}", pending.verification_token.0)));
+
+        let (status, _) = send(&state, Request::get(&uri).body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let confirmed = create_confirmed_reservation(&db, event.id, "john@example.com", 1).await;
+        let uri = format!("/admin/reservations/{}/verification-link", confirmed.id);
+        let (status, body) = send(&state, with_api_key(Request::get(&uri).body(Body::empty()).unwrap(), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["error"], "Reservation is already confirmed");
+    }
+
+    #[tokio::test]
+    async fn test_search_reservations_by_email_fragment() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+        create_confirmed_reservation(&db, event.id, "john.smith@example.com", 1).await;
+        create_confirmed_reservation(&db, event.id, "jane.smithers@example.org", 2).await;
+        create_confirmed_reservation(&db, event.id, "bob@example.com", 1).await;
+
+        let request = Request::get("/admin/reservations/search?email_contains=SMITH").body(Body::empty()).unwrap();
+        let (status, body) = send(&state, with_api_key(request, TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+        let results = body.as_array().unwrap();
+        assert_eq!(results.len(), 2);
+        let mut emails: Vec<&str> = results.iter().map(|r| r["user_email"].as_str().unwrap()).collect();
+        emails.sort();
+        assert_eq!(emails, vec!["j***@example.com", "j***@example.org"]);
+        assert!(results.iter().all(|r| r["event_name"] == "Test Event"));
+
+        // Wildcards in the fragment match literally
+        let request = Request::get("/admin/reservations/search?email_contains=j%25n").body(Body::empty()).unwrap();
+        let (status, body) = send(&state, with_api_key(request, TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.as_array().unwrap().is_empty());
+
+        let request = Request::get("/admin/reservations/search?email_contains=smith").body(Body::empty()).unwrap();
+        let (status, _) = send(&state, request).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_admin_token_lookup_returns_expired_token() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+        let confirmed = create_confirmed_reservation(&db, event.id, "john@example.com", 1).await;
+        let token = confirmed.get_active_reservation_tokens().remove(0);
+        db.expire_active_tokens_for_event(&event.id).await.unwrap();
+
+        let request = Request::get(format!("/admin/tokens/{}", token.token)).body(Body::empty()).unwrap();
+        let (status, body) = send(&state, with_api_key(request, TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "expired");
+        assert_eq!(body["reservation_id"], confirmed.id.to_string());
+        assert_eq!(body["event_id"], event.id.to_string());
+        assert!(body["created_at"].is_string());
+        assert!(body["used_at"].is_null());
+
+        let request = Request::get("/admin/tokens/no-such-token").body(Body::empty()).unwrap();
+        let (status, _) = send(&state, with_api_key(request, TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_event_backup_round_trips_into_fresh_database() {
+        let source = test_state().await;
+        let db = Database { pool: source.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+        set_questions(&source, event.id).await;
+
+        let confirmed = create_confirmed_reservation(&db, event.id, "john@example.com", 2).await;
+        db.mark_reservation_token_used(confirmed.get_active_reservation_tokens().remove(0), None).await.unwrap();
+        db.insert_reservation(
+            models::CreatingReservation::prepare(event.id, "Jane".to_string(), "jane@example.com".to_string(), 1, None)
+        ).await.unwrap();
+
+        let export_uri = format!("/admin/events/{}/export", event.id);
+        let (status, exported) = send(&source, with_api_key(Request::get(&export_uri).body(Body::empty()).unwrap(), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(exported["reservations"].as_array().unwrap().len(), 2);
+        assert_eq!(exported["reservations"][0]["tokens"].as_array().unwrap().len(), 2);
+
+        let target = test_state().await;
+        let (status, body) = send(&target, with_api_key(post_json("/admin/events/import", exported.clone()), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(body["reservations"], 2);
+        let imported_id = Uuid::parse_str(body["event_id"].as_str().unwrap()).unwrap();
+        assert_ne!(imported_id, event.id);
+
+        // Exporting the restored event gives back the same document
+        let export_uri = format!("/admin/events/{}/export", imported_id);
+        let (status, reexported) = send(&target, with_api_key(Request::get(&export_uri).body(Body::empty()).unwrap(), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(reexported, exported);
+
+        let target_db = Database { pool: target.pool.clone() };
+        assert_eq!(target_db.count_event_reservations(&imported_id, false).await.unwrap(), 2);
+        assert_eq!(target_db.get_tokens_for_event(&imported_id, Some("used")).await.unwrap().len(), 1);
+
+        // Restoring into the database that still holds the original tokens conflicts
+        let (status, body) = send(&source, with_api_key(post_json("/admin/events/import", exported), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["code"], "backup_conflict");
+        assert_eq!(db.get_all_open_events().await.unwrap().len(), 1);
+    }
+
+    fn reservation_status_request(event_id: Uuid, email: &str) -> Request<Body> {
+        Request::get(format!("/events/{}/reservation-status?email={}", event_id, email))
+            .header("x-forwarded-for", "203.0.113.7")
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_reservation_status_for_email() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+
+        let (status, body) = send(&state, reservation_status_request(event.id, "nobody@example.com")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, json!({ "has_reservation": false, "status": null }));
+
+        db.insert_reservation(
+            models::CreatingReservation::prepare(event.id, "Jane".to_string(), "jane@example.com".to_string(), 1, None)
+        ).await.unwrap();
+        let (_, body) = send(&state, reservation_status_request(event.id, "Jane@Example.com")).await;
+        assert_eq!(body, json!({ "has_reservation": true, "status": "pending" }));
+
+        create_confirmed_reservation(&db, event.id, "john@example.com", 2).await;
+        let (_, body) = send(&state, reservation_status_request(event.id, "john@example.com")).await;
+        assert_eq!(body, json!({ "has_reservation": true, "status": "confirmed" }));
+    }
+
+    #[tokio::test]
+    async fn test_analytics_flags_event_near_capacity() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(20)).await.unwrap();
+        let analytics = || with_api_key(Request::builder()
+            .uri(format!("/events/{}/analytics", event.id))
+            .body(Body::empty())
+            .unwrap(), TEST_API_KEY);
+
+        let (status, _) = send(&state, Request::get(format!("/events/{}/analytics", event.id)).body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        // 3 of 20 spots left is still above the default 10% threshold
+        create_confirmed_reservation(&db, event.id, "first@example.com", 17).await;
+        let (status, body) = send(&state, analytics()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["spots_remaining"], 3);
+        assert_eq!(body["near_capacity"], false);
+
+        create_confirmed_reservation(&db, event.id, "second@example.com", 1).await;
+        let (_, body) = send(&state, analytics()).await;
+        assert_eq!(body["spots_remaining"], 2);
+        assert_eq!(body["near_capacity"], true);
+    }
+
+    #[tokio::test]
+    async fn test_my_tokens_lists_active_tokens_across_events() {
+        let provider = Arc::new(RecordingProvider::default());
+        let state = test_state_with_provider(provider.clone()).await;
+        let db = Database { pool: state.pool.clone() };
+        let first = db.create_event(test_new_event(50)).await.unwrap();
+        let second = db.create_event(models::NewEvent {
+            start_time: OffsetDateTime::now_utc() + Duration::hours(48),
+            end_time: OffsetDateTime::now_utc() + Duration::hours(50),
+            ..test_new_event(50)
+        }).await.unwrap();
+        create_confirmed_reservation(&db, first.id, "john@example.com", 2).await;
+        create_confirmed_reservation(&db, second.id, "john@example.com", 1).await;
+        create_confirmed_reservation(&db, second.id, "jane@example.com", 1).await;
+
+        let my_tokens = |access_token: &str| Request::builder()
+            .uri(format!("/my/tokens?email=John%40Example.com&access_token={}", access_token))
+            .body(Body::empty())
+            .unwrap();
+
+        // Without the emailed access token nothing is revealed
+        let (status, _) = send(&state, my_tokens("guess")).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let (status, _) = send(&state, post_json("/my/tokens/access", json!({ "email": "john@example.com" }))).await;
+        assert_eq!(status, StatusCode::ACCEPTED);
+        let access_token = {
+            let sent = provider.sent.lock().unwrap();
+            assert_eq!(sent.len(), 1);
+            let (_, token) = sent[0].body.split_once("access_token=").unwrap();
+            token.lines().next().unwrap().to_string()
+        };
+
+        let (status, body) = send(&state, my_tokens(&access_token)).await;
+        assert_eq!(status, StatusCode::OK);
+        let tokens = body.as_array().unwrap();
+        assert_eq!(tokens.len(), 3);
+        let event_ids: Vec<&str> = tokens.iter().map(|token| token["event"]["id"].as_str().unwrap()).collect();
+        let (first_id, second_id) = (first.id.to_string(), second.id.to_string());
+        assert_eq!(event_ids, vec![first_id.as_str(), first_id.as_str(), second_id.as_str()]);
+
+        // An address without tickets gets the same response but no email
+        let (status, _) = send(&state, post_json("/my/tokens/access", json!({ "email": "nobody@example.com" }))).await;
+        assert_eq!(status, StatusCode::ACCEPTED);
+        assert_eq!(provider.sent_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_reservation_status_is_rate_limited() {
+        let mut state = test_state().await;
+        state.lookup_limiter = rate_limit::RateLimiter::per_minute(2);
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+
+        for _ in 0..2 {
+            let (status, _) = send(&state, reservation_status_request(event.id, "john@example.com")).await;
+            assert_eq!(status, StatusCode::OK);
+        }
+        let (status, body) = send(&state, reservation_status_request(event.id, "john@example.com")).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(body["code"], "too_many_requests");
+    }
+
+    #[tokio::test]
+    async fn test_malformed_timestamp_is_a_clean_validation_error() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+
+        let export_uri = format!("/admin/events/{}/export", event.id);
+        let (_, mut exported) = send(&state, with_api_key(Request::get(&export_uri).body(Body::empty()).unwrap(), TEST_API_KEY)).await;
+        exported["event"]["start_time"] = json!("next tuesday");
+
+        let (status, body) = send(&state, with_api_key(post_json("/admin/events/import", exported), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "start_time must be ISO8601");
+        assert_eq!(body["code"], "validation_failed");
+    }
+
+    fn adjust_spots_request(reservation_id: Uuid, spot_count: u32) -> Request<Body> {
+        with_api_key(post_json(&format!("/admin/reservations/{}/adjust-spots", reservation_id), json!({ "spot_count": spot_count })), TEST_API_KEY)
+    }
+
+    async fn count_reservation_audit_entries(state: &AppState, reservation_id: Uuid) -> i64 {
+        sqlx::query_scalar("SELECT COUNT(*) FROM reservation_audit WHERE reservation_id = ? AND action = 'adjust_spots'")
+            .bind(reservation_id.to_string())
+            .fetch_one(&state.pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_adjust_spots_up_issues_new_tokens() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(5)).await.unwrap();
+        let confirmed = create_confirmed_reservation(&db, event.id, "john@example.com", 2).await;
+
+        let (status, body) = send(&state, adjust_spots_request(confirmed.id, 3)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["spot_count"], 3);
+
+        let adjusted = db.get_confirmed_reservation_by_id(&confirmed.id).await.unwrap();
+        assert_eq!(adjusted.spot_count, 3);
+        assert_eq!(adjusted.get_active_reservation_tokens().len(), 3);
+        assert_eq!(count_reservation_audit_entries(&state, confirmed.id).await, 1);
+
+        // Capacity is re-checked against the other reservations
+        create_confirmed_reservation(&db, event.id, "jane@example.com", 2).await;
+        let (status, _) = send(&state, adjust_spots_request(confirmed.id, 4)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_adjust_spots_respects_party_limits_and_holds() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(models::NewEvent {
+            max_spots_per_reservation: Some(4),
+            min_spots_per_reservation: Some(2),
+            ..test_new_event(6)
+        }).await.unwrap();
+        let confirmed = create_confirmed_reservation(&db, event.id, "john@example.com", 2).await;
+
+        let (status, body) = send(&state, adjust_spots_request(confirmed.id, 5)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "Cannot reserve more than 4 spots per reservation for this event");
+
+        let (status, body) = send(&state, adjust_spots_request(confirmed.id, 1)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "Must reserve at least 2 spots per reservation for this event");
+
+        // Spots another guest is holding at checkout aren't free to add
+        db.insert_hold(models::HoldingReservation::place(event.id, 3, OffsetDateTime::now_utc(), Duration::minutes(5))).await.unwrap();
+        let (status, _) = send(&state, adjust_spots_request(confirmed.id, 4)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = send(&state, adjust_spots_request(confirmed.id, 3)).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_adjust_spots_down_expires_tokens() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+        let confirmed = create_confirmed_reservation(&db, event.id, "john@example.com", 4).await;
+        let mut tokens = confirmed.get_active_reservation_tokens();
+        db.mark_reservation_token_used(tokens.remove(0), None).await.unwrap();
+        db.mark_reservation_token_used(tokens.remove(0), None).await.unwrap();
+
+        // Checked-in spots can't be taken back
+        let (status, body) = send(&state, adjust_spots_request(confirmed.id, 1)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "Cannot go below the 2 spots already checked in");
+
+        let (status, _) = send(&state, adjust_spots_request(confirmed.id, 2)).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let adjusted = db.get_confirmed_reservation_by_id(&confirmed.id).await.unwrap();
+        assert_eq!(adjusted.spot_count, 2);
+        assert!(adjusted.get_active_reservation_tokens().is_empty());
+        assert_eq!(adjusted.get_used_reservation_tokens().len(), 2);
+        assert_eq!(adjusted.get_expired_reservation_tokens().len(), 2);
+        assert_eq!(count_reservation_audit_entries(&state, confirmed.id).await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_reserve_treats_email_case_variants_as_duplicates() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+
+        let (status, body) = send(&state, reserve_request(event.id, "John@Example.com", 1)).await;
+        assert_eq!(status, StatusCode::OK);
+        let reservation_id = Uuid::parse_str(body["reservation_id"].as_str().unwrap()).unwrap();
+
+        let (status, body) = send(&state, reserve_request(event.id, "john@EXAMPLE.com", 1)).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["error"], "A reservation already exists for this email and event");
+
+        // Stored normalized, with the address as typed kept alongside
+        let pending = db.get_pending_reservation_by_email(&event.id, "JOHN@example.com").await.unwrap();
+        assert_eq!(pending.id, reservation_id);
+        assert_eq!(pending.user_email, "john@example.com");
+        let original: String = sqlx::query_scalar("SELECT user_email_original FROM reservations WHERE id = ?")
+            .bind(reservation_id.to_string())
+            .fetch_one(&state.pool)
+            .await
+            .unwrap();
+        assert_eq!(original, "John@Example.com");
+    }
+
+    #[tokio::test]
+    async fn test_resend_pending_verifications_for_event() {
+        let provider = Arc::new(RecordingProvider::default());
+        let state = test_state_with_provider(provider.clone()).await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+        let other_event = db.create_event(test_new_event(50)).await.unwrap();
+
+        let prepare = |event_id: Uuid, email: &str| models::CreatingReservation::prepare(event_id, "Test User".to_string(), email.to_string(), 1, None);
+        db.insert_reservation(prepare(event.id, "john@example.com")).await.unwrap();
+        db.insert_reservation(prepare(event.id, "jane@example.com")).await.unwrap();
+        db.insert_reservation(prepare(other_event.id, "other@example.com")).await.unwrap();
+        create_confirmed_reservation(&db, event.id, "confirmed@example.com", 1).await;
+
+        let uri = format!("/admin/events/{}/resend-pending", event.id);
+        let (status, body) = send(&state, with_api_key(post_json(&uri, json!({})), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["sent"], 2);
+        assert_eq!(body["failed"], 0);
+
+        let mut recipients: Vec<String> = provider.sent.lock().unwrap().iter().map(|message| message.to.clone()).collect();
+        recipients.sort();
+        assert_eq!(recipients, vec!["jane@example.com", "john@example.com"]);
+    }
+
+    #[tokio::test]
+    async fn test_verification_emails_use_their_own_from_address() {
+        let provider = Arc::new(RecordingProvider::default());
+        let mut state = test_state_with_provider(provider.clone()).await;
+        state.config.email_from_verification = Some("verify@example.com".to_string());
+        state.email_sender = EmailSender::with_provider(provider.clone()).with_from_addresses(&state.config);
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+
+        let (status, _) = send(&state, reserve_request(event.id, "john@example.com", 1)).await;
+        assert_eq!(status, StatusCode::OK);
+
+        // Confirmations have no override, so they still come from EMAIL_FROM
+        let confirmed = create_confirmed_reservation(&db, event.id, "jane@example.com", 1).await;
+        state.email_sender.send_confirmation(&confirmed.user_email, &confirmed).await.unwrap();
+
+        let sent = provider.sent.lock().unwrap();
+        assert_eq!(sent[0].from_email, "verify@example.com");
+        assert_eq!(sent[1].from_email, state.config.email_from);
+        assert_ne!(sent[0].from_email, sent[1].from_email);
+    }
+
+    #[tokio::test]
+    async fn test_bulk_cancel_pending_reservations_leaves_confirmed_alone() {
+        let provider = Arc::new(RecordingProvider::default());
+        let state = test_state_with_provider(provider.clone()).await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+
+        let prepare = |email: &str| models::CreatingReservation::prepare(event.id, "Test User".to_string(), email.to_string(), 1, None);
+        let john = db.insert_reservation(prepare("john@example.com")).await.unwrap();
+        db.insert_reservation(prepare("jane@example.com")).await.unwrap();
+        let confirmed = create_confirmed_reservation(&db, event.id, "confirmed@example.com", 2).await;
+
+        let uri = format!("/admin/events/{}/cancel-reservations?status=pending&notify=true", event.id);
+        let (status, body) = send(&state, with_api_key(post_json(&uri, json!({})), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["cancelled_pending"], 2);
+        assert_eq!(body["cancelled_confirmed"], 0);
+        assert_eq!(body["notified"], 2);
+        assert_eq!(provider.sent_count(), 2);
+
+        assert!(matches!(db.get_pending_reservation_by_id(&john.id).await, Err(DatabaseError::ReservationNotFound)));
+        assert_eq!(db.get_confirmed_reservation_by_id(&confirmed.id).await.unwrap().get_active_reservation_tokens().len(), 2);
+
+        // Confirmed reservations go only when asked for, and their tickets stop scanning
+        let uri = format!("/admin/events/{}/cancel-reservations?status=pending,confirmed", event.id);
+        let (status, body) = send(&state, with_api_key(post_json(&uri, json!({})), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["cancelled_pending"], 0);
+        assert_eq!(body["cancelled_confirmed"], 1);
+        assert_eq!(body["notified"], 0);
+
+        let token = confirmed.get_active_reservation_tokens().remove(0);
+        let request = Request::get(format!("/admin/tokens/{}", token.token)).body(Body::empty()).unwrap();
+        let (_, body) = send(&state, with_api_key(request, TEST_API_KEY)).await;
+        assert_eq!(body["status"], "expired");
+    }
+
+    #[tokio::test]
+    async fn test_wallet_pass_for_confirmed_reservation() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+        let confirmed = create_confirmed_reservation(&db, event.id, "john@example.com", 2).await;
+
+        let request = Request::get(format!("/reservations/{}/pass.pkpass", confirmed.id)).body(Body::empty()).unwrap();
+        let response = app(state.clone()).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/vnd.apple.pkpass");
+
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        assert!(bytes.starts_with(b"PK\x03\x04"));
+
+        // Pending reservations don't get a pass
+        let pending = db.insert_reservation(
+            models::CreatingReservation::prepare(event.id, "Jane".to_string(), "jane@example.com".to_string(), 1, None)
+        ).await.unwrap();
+        let (status, _) = send(&state, Request::get(format!("/reservations/{}/pass.pkpass", pending.id)).body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_qr_sheet_pdf_for_event_with_confirmed_tokens() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+        create_confirmed_reservation(&db, event.id, "john@example.com", 2).await;
+        create_confirmed_reservation(&db, event.id, "jane@example.com", 1).await;
+
+        let uri = format!("/events/{}/qr-sheet.pdf", event.id);
+        let request = Request::get(&uri).body(Body::empty()).unwrap();
+        let response = app(state.clone()).oneshot(with_api_key(request, TEST_API_KEY)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/pdf");
+
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        assert!(bytes.starts_with(b"%PDF-"));
+        assert!(bytes.ends_with(b"%%EOF\n"));
+        let pdf = String::from_utf8_lossy(&bytes);
+        // One QR code per confirmed token, each drawn as filled module rectangles
+        assert_eq!(pdf.matches("\nf\n").count(), 3);
+        assert!(pdf.contains("(Test User) Tj"));
+
+        let (status, _) = send(&state, Request::get(&uri).body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_event_lookup_reports_spots_remaining_header() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(12)).await.unwrap();
+        create_confirmed_reservation(&db, event.id, "john@example.com", 3).await;
+        create_confirmed_reservation(&db, event.id, "jane@example.com", 2).await;
+        // Pending reservations don't count unless COUNT_PENDING_TOWARD_CAPACITY is set
+        send(&state, reserve_request(event.id, "pending@example.com", 4)).await;
+
+        let remaining = event.capacity - db.count_event_reservations(&event.id, false).await.unwrap();
+        assert_eq!(remaining, 7);
+
+        let request = Request::get(format!("/events/{}", event.id)).body(Body::empty()).unwrap();
+        let response = app(state.clone()).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["x-spots-remaining"], remaining.to_string().as_str());
+
+        let request = Request::get(format!("/events/slug/{}", event.slug)).body(Body::empty()).unwrap();
+        let response = app(state.clone()).oneshot(request).await.unwrap();
+        assert_eq!(response.headers()["x-spots-remaining"], remaining.to_string().as_str());
+    }
+
+    #[tokio::test]
+    async fn test_event_calendar_feed() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let event = db.create_event(test_new_event(50)).await.unwrap();
+
+        let request = Request::get(format!("/events/{}.ics", event.id)).body(Body::empty()).unwrap();
+        let response = app(state.clone()).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "text/calendar; charset=utf-8");
+
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let calendar = String::from_utf8(bytes.to_vec()).unwrap();
+        let start = event.start_time.to_offset(time::UtcOffset::UTC);
+        let dtstart = format!(
+            "DTSTART:{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+            start.year(), start.month() as u8, start.day(), start.hour(), start.minute(), start.second()
+        );
+        assert!(calendar.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(calendar.contains("BEGIN:VEVENT\r\n"));
+        assert!(calendar.contains(&format!("SUMMARY:{}\r\n", event.name)));
+        assert!(calendar.contains(&format!("{}\r\n", dtstart)));
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_event_into_series() {
+        let state = test_state().await;
+        let db = Database { pool: state.pool.clone() };
+        let week_one = db.create_event(test_new_event(30)).await.unwrap();
+
+        let duplicate = |source: Uuid, start_time: OffsetDateTime| {
+            with_api_key(post_json(&format!("/admin/events/{}/duplicate", source), json!({
+                "start_time": start_time.format(&time::format_description::well_known::Iso8601::DEFAULT).unwrap(),
+                "attach_to_series": true,
+            })), TEST_API_KEY)
+        };
+
+        let (status, week_two) = send(&state, duplicate(week_one.id, week_one.start_time + Duration::weeks(1))).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(week_two["capacity"], 30);
+        assert_eq!(week_two["series_id"], json!(week_one.id));
+        let week_two_id = Uuid::parse_str(week_two["id"].as_str().unwrap()).unwrap();
+
+        // Duplicating from a later event keeps it in the same series
+        let (status, _) = send(&state, duplicate(week_two_id, week_one.start_time + Duration::weeks(2))).await;
+        assert_eq!(status, StatusCode::CREATED);
+
+        // A copy that doesn't ask to join stays out of the series
+        let (status, standalone) = send(&state, with_api_key(post_json(&format!("/admin/events/{}/duplicate", week_one.id), json!({
+            "start_time": "2030-01-01T18:00:00Z",
+        })), TEST_API_KEY)).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(standalone["series_id"], Value::Null);
+
+        let (status, body) = send(&state, Request::get(format!("/series/{}/events", week_one.id)).body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+        let series = body.as_array().unwrap();
+        assert_eq!(series.len(), 3);
+        assert_eq!(series[0]["id"], json!(week_one.id));
+        assert_eq!(series[1]["id"], json!(week_two_id));
+        let end_time = OffsetDateTime::parse(series[1]["end_time"].as_str().unwrap(), &time::format_description::well_known::Iso8601::DEFAULT).unwrap();
+        assert_eq!(end_time, week_one.end_time + Duration::weeks(1));
+    }
 }
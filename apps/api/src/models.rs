@@ -1,3 +1,6 @@
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use sha2::{Digest, Sha256};
 use std::fmt::Display;
 use time::OffsetDateTime;
 use uuid::Uuid;
@@ -13,23 +16,808 @@ pub struct Full;
 #[derive(Debug, Clone)]
 pub struct Finished;
 
+#[derive(Debug, Clone)]
+pub struct Cancelled;
+
+/// Event lifecycle status, for manual status changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventStatus {
+    Open,
+    Full,
+    Finished,
+    Cancelled,
+}
+
+impl EventStatus {
+    /// Whether an organizer may move an event from this status to `next`; cancellation is final
+    pub fn can_transition_to(&self, next: EventStatus) -> bool {
+        use EventStatus::*;
+
+        match (self, next) {
+            (current, next) if *current == next => true,
+            (Cancelled, _) => false,
+            (Open, _) | (Full, _) => true,
+            (Finished, Open) => true,
+            (Finished, _) => false,
+        }
+    }
+}
+
+impl Display for EventStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EventStatus::Open => write!(f, "open"),
+            EventStatus::Full => write!(f, "full"),
+            EventStatus::Finished => write!(f, "finished"),
+            EventStatus::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+impl From<api::EventStatus> for EventStatus {
+    fn from(status: api::EventStatus) -> Self {
+        match status {
+            api::EventStatus::Open => EventStatus::Open,
+            api::EventStatus::Full => EventStatus::Full,
+            api::EventStatus::Finished => EventStatus::Finished,
+            api::EventStatus::Cancelled => EventStatus::Cancelled,
+        }
+    }
+}
+
+impl From<EventStatus> for api::EventStatus {
+    fn from(status: EventStatus) -> Self {
+        match status {
+            EventStatus::Open => api::EventStatus::Open,
+            EventStatus::Full => api::EventStatus::Full,
+            EventStatus::Finished => api::EventStatus::Finished,
+            EventStatus::Cancelled => api::EventStatus::Cancelled,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Event<State> {
     pub id: Uuid,
+    pub slug: String,
     pub name: String,
     pub description: Option<String>,
     pub start_time: OffsetDateTime,
     pub end_time: OffsetDateTime,
     pub capacity: u32,
     pub location: Option<String>,
+    pub max_reservations: Option<u32>,
+    pub max_spots_per_reservation: Option<u32>,
+    pub min_spots_per_reservation: Option<u32>,
+    pub visibility: EventVisibility,
+    pub reservations_open_at: Option<OffsetDateTime>,
+    pub reservations_close_at: Option<OffsetDateTime>,
+    pub skip_verification: bool,
+    /// Check-in requires the guest's name, matched case-insensitively against the reservation
+    pub require_name_match: bool,
+    pub token_rotation_minutes: Option<u32>,
+    /// Recurring events share a series id: the id of the event the series started from
+    pub series_id: Option<Uuid>,
+    /// Set by the organizer to stop new reservations whatever capacity is left
+    pub sales_closed: bool,
     pub created_at: OffsetDateTime,
     pub updated_at: OffsetDateTime,
+    /// Type-state marker; `AnyEvent` is matched on instead of reading it
+    #[expect(dead_code)]
     pub status: State,
 }
 
+/// Who can discover an event: listed publicly, by direct link only, or authenticated callers only
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EventVisibility {
+    #[default]
+    Public,
+    Unlisted,
+    Private,
+}
+
+impl EventVisibility {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventVisibility::Public => "public",
+            EventVisibility::Unlisted => "unlisted",
+            EventVisibility::Private => "private",
+        }
+    }
+
+    pub fn from_db(value: &str) -> Self {
+        match value {
+            "unlisted" => EventVisibility::Unlisted,
+            "private" => EventVisibility::Private,
+            _ => EventVisibility::Public,
+        }
+    }
+}
+
+impl From<api::EventVisibility> for EventVisibility {
+    fn from(visibility: api::EventVisibility) -> Self {
+        match visibility {
+            api::EventVisibility::Public => EventVisibility::Public,
+            api::EventVisibility::Unlisted => EventVisibility::Unlisted,
+            api::EventVisibility::Private => EventVisibility::Private,
+        }
+    }
+}
+
+impl From<EventVisibility> for api::EventVisibility {
+    fn from(visibility: EventVisibility) -> Self {
+        match visibility {
+            EventVisibility::Public => api::EventVisibility::Public,
+            EventVisibility::Unlisted => api::EventVisibility::Unlisted,
+            EventVisibility::Private => api::EventVisibility::Private,
+        }
+    }
+}
+
 pub type OpenEvent = Event<Open>;
 pub type FullEvent = Event<Full>;
+pub type FinishedEvent = Event<Finished>;
+pub type CancelledEvent = Event<Cancelled>;
+
+// Enum to represent events in any state
+#[derive(Debug)]
+pub enum AnyEvent {
+    Open(OpenEvent),
+    Full(FullEvent),
+    Finished(FinishedEvent),
+    Cancelled(CancelledEvent),
+}
+
+impl AnyEvent {
+    pub fn token_rotation_minutes(&self) -> Option<u32> {
+        match self {
+            AnyEvent::Open(event) => event.token_rotation_minutes,
+            AnyEvent::Full(event) => event.token_rotation_minutes,
+            AnyEvent::Finished(event) => event.token_rotation_minutes,
+            AnyEvent::Cancelled(event) => event.token_rotation_minutes,
+        }
+    }
+
+    pub fn start_time(&self) -> OffsetDateTime {
+        match self {
+            AnyEvent::Open(event) => event.start_time,
+            AnyEvent::Full(event) => event.start_time,
+            AnyEvent::Finished(event) => event.start_time,
+            AnyEvent::Cancelled(event) => event.start_time,
+        }
+    }
+
+    pub fn end_time(&self) -> OffsetDateTime {
+        match self {
+            AnyEvent::Open(event) => event.end_time,
+            AnyEvent::Full(event) => event.end_time,
+            AnyEvent::Finished(event) => event.end_time,
+            AnyEvent::Cancelled(event) => event.end_time,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            AnyEvent::Open(event) => &event.name,
+            AnyEvent::Full(event) => &event.name,
+            AnyEvent::Finished(event) => &event.name,
+            AnyEvent::Cancelled(event) => &event.name,
+        }
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        match self {
+            AnyEvent::Open(event) => event.description.as_deref(),
+            AnyEvent::Full(event) => event.description.as_deref(),
+            AnyEvent::Finished(event) => event.description.as_deref(),
+            AnyEvent::Cancelled(event) => event.description.as_deref(),
+        }
+    }
+
+    pub fn location(&self) -> Option<&str> {
+        match self {
+            AnyEvent::Open(event) => event.location.as_deref(),
+            AnyEvent::Full(event) => event.location.as_deref(),
+            AnyEvent::Finished(event) => event.location.as_deref(),
+            AnyEvent::Cancelled(event) => event.location.as_deref(),
+        }
+    }
+
+    pub fn id(&self) -> Uuid {
+        match self {
+            AnyEvent::Open(event) => event.id,
+            AnyEvent::Full(event) => event.id,
+            AnyEvent::Finished(event) => event.id,
+            AnyEvent::Cancelled(event) => event.id,
+        }
+    }
+
+    pub fn series_id(&self) -> Option<Uuid> {
+        match self {
+            AnyEvent::Open(event) => event.series_id,
+            AnyEvent::Full(event) => event.series_id,
+            AnyEvent::Finished(event) => event.series_id,
+            AnyEvent::Cancelled(event) => event.series_id,
+        }
+    }
+
+    pub fn visibility(&self) -> EventVisibility {
+        match self {
+            AnyEvent::Open(event) => event.visibility,
+            AnyEvent::Full(event) => event.visibility,
+            AnyEvent::Finished(event) => event.visibility,
+            AnyEvent::Cancelled(event) => event.visibility,
+        }
+    }
+
+    /// Settings for a copy of this event starting at `start_time`, whatever state this one is in
+    pub fn duplicate(&self, start_time: OffsetDateTime, series_id: Option<Uuid>) -> NewEvent {
+        match self {
+            AnyEvent::Open(event) => event.duplicate(start_time, series_id),
+            AnyEvent::Full(event) => event.duplicate(start_time, series_id),
+            AnyEvent::Finished(event) => event.duplicate(start_time, series_id),
+            AnyEvent::Cancelled(event) => event.duplicate(start_time, series_id),
+        }
+    }
+
+    pub fn capacity(&self) -> u32 {
+        match self {
+            AnyEvent::Open(event) => event.capacity,
+            AnyEvent::Full(event) => event.capacity,
+            AnyEvent::Finished(event) => event.capacity,
+            AnyEvent::Cancelled(event) => event.capacity,
+        }
+    }
+
+    pub fn max_spots_per_reservation(&self) -> Option<u32> {
+        match self {
+            AnyEvent::Open(event) => event.max_spots_per_reservation,
+            AnyEvent::Full(event) => event.max_spots_per_reservation,
+            AnyEvent::Finished(event) => event.max_spots_per_reservation,
+            AnyEvent::Cancelled(event) => event.max_spots_per_reservation,
+        }
+    }
 
+    pub fn min_spots_per_reservation(&self) -> Option<u32> {
+        match self {
+            AnyEvent::Open(event) => event.min_spots_per_reservation,
+            AnyEvent::Full(event) => event.min_spots_per_reservation,
+            AnyEvent::Finished(event) => event.min_spots_per_reservation,
+            AnyEvent::Cancelled(event) => event.min_spots_per_reservation,
+        }
+    }
+
+    pub fn status(&self) -> EventStatus {
+        match self {
+            AnyEvent::Open(_) => EventStatus::Open,
+            AnyEvent::Full(_) => EventStatus::Full,
+            AnyEvent::Finished(_) => EventStatus::Finished,
+            AnyEvent::Cancelled(_) => EventStatus::Cancelled,
+        }
+    }
+}
+
+impl<State> Event<State> {
+    fn into_reservation_event_response(self, status: EventStatus, spots_confirmed: u32) -> api::RetrieveReservationEventResponse {
+        api::RetrieveReservationEventResponse {
+            id: self.id,
+            name: self.name,
+            description: self.description,
+            start_time: self.start_time,
+            end_time: self.end_time,
+            capacity: self.capacity,
+            location: self.location,
+            status: status.into(),
+            spots_confirmed,
+            spots_remaining: self.capacity.saturating_sub(spots_confirmed),
+        }
+    }
+}
+
+impl AnyEvent {
+    /// The event as shown alongside a reservation, with `spots_confirmed` summed from its confirmed reservations
+    pub fn into_reservation_event_response(self, spots_confirmed: u32) -> api::RetrieveReservationEventResponse {
+        let status = self.status();
+        match self {
+            AnyEvent::Open(event) => event.into_reservation_event_response(status, spots_confirmed),
+            AnyEvent::Full(event) => event.into_reservation_event_response(status, spots_confirmed),
+            AnyEvent::Finished(event) => event.into_reservation_event_response(status, spots_confirmed),
+            AnyEvent::Cancelled(event) => event.into_reservation_event_response(status, spots_confirmed),
+        }
+    }
+}
+
+impl OpenEvent {
+    /// Close an event once it is over; no further reservations can be made
+    pub fn finish(self) -> FinishedEvent {
+        Event {
+            id: self.id,
+            slug: self.slug,
+            name: self.name,
+            description: self.description,
+            start_time: self.start_time,
+            end_time: self.end_time,
+            capacity: self.capacity,
+            location: self.location,
+            max_reservations: self.max_reservations,
+            max_spots_per_reservation: self.max_spots_per_reservation,
+            min_spots_per_reservation: self.min_spots_per_reservation,
+            visibility: self.visibility,
+            reservations_open_at: self.reservations_open_at,
+            reservations_close_at: self.reservations_close_at,
+            skip_verification: self.skip_verification,
+            require_name_match: self.require_name_match,
+            token_rotation_minutes: self.token_rotation_minutes,
+            series_id: self.series_id,
+            sales_closed: self.sales_closed,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            status: Finished,
+        }
+    }
+}
+
+impl<State> Event<State> {
+    /// Settings for a copy of this event starting at `start_time`. The end time and reservation
+    /// window move by the same amount, so a weekly event keeps its length and booking schedule.
+    pub fn duplicate(&self, start_time: OffsetDateTime, series_id: Option<Uuid>) -> NewEvent {
+        let shift = start_time - self.start_time;
+
+        NewEvent {
+            name: self.name.clone(),
+            description: self.description.clone(),
+            start_time,
+            end_time: self.end_time + shift,
+            capacity: self.capacity,
+            location: self.location.clone(),
+            max_reservations: self.max_reservations,
+            max_spots_per_reservation: self.max_spots_per_reservation,
+            min_spots_per_reservation: self.min_spots_per_reservation,
+            visibility: self.visibility,
+            reservations_open_at: self.reservations_open_at.map(|open_at| open_at + shift),
+            reservations_close_at: self.reservations_close_at.map(|close_at| close_at + shift),
+            skip_verification: self.skip_verification,
+            require_name_match: self.require_name_match,
+            token_rotation_minutes: self.token_rotation_minutes,
+            series_id,
+        }
+    }
+
+    fn into_response(self, status: api::EventStatus) -> api::OpenEventResponse {
+        api::OpenEventResponse {
+            id: self.id,
+            slug: self.slug,
+            name: self.name,
+            description: self.description,
+            start_time: self.start_time,
+            end_time: self.end_time,
+            capacity: self.capacity,
+            location: self.location,
+            max_spots_per_reservation: self.max_spots_per_reservation,
+            min_spots_per_reservation: self.min_spots_per_reservation,
+            visibility: self.visibility.into(),
+            reservations_open_at: self.reservations_open_at,
+            reservations_close_at: self.reservations_close_at,
+            skip_verification: self.skip_verification,
+            require_name_match: self.require_name_match,
+            token_rotation_minutes: self.token_rotation_minutes,
+            series_id: self.series_id,
+            sales_closed: self.sales_closed,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            status,
+        }
+    }
+}
+
+impl From<OpenEvent> for api::OpenEventResponse {
+    fn from(event: OpenEvent) -> Self {
+        event.into_response(api::EventStatus::Open)
+    }
+}
+
+impl From<AnyEvent> for api::OpenEventResponse {
+    fn from(event: AnyEvent) -> Self {
+        let status = event.status().into();
+        match event {
+            AnyEvent::Open(event) => event.into_response(status),
+            AnyEvent::Full(event) => event.into_response(status),
+            AnyEvent::Finished(event) => event.into_response(status),
+            AnyEvent::Cancelled(event) => event.into_response(status),
+        }
+    }
+}
+
+/// An event of any status with its confirmed reservation totals, for organizers
+#[derive(Debug)]
+pub struct AdminEventSummary {
+    pub event: AnyEvent,
+    pub reservation_count: u32,
+    pub reserved_spots: u32,
+}
+
+impl From<AdminEventSummary> for api::AdminEventResponse {
+    fn from(summary: AdminEventSummary) -> Self {
+        api::AdminEventResponse {
+            event: summary.event.into(),
+            reservation_count: summary.reservation_count,
+            reserved_spots: summary.reserved_spots,
+        }
+    }
+}
+
+// Filters for event search; all set filters must match
+#[derive(Debug, Clone, Default)]
+pub struct EventSearch {
+    pub name_query: Option<String>,
+    pub from: Option<OffsetDateTime>,
+    pub to: Option<OffsetDateTime>,
+    pub location: Option<String>,
+    pub only_available: bool,
+}
+
+// A single email send attempt from the delivery log
+#[derive(Debug, Clone)]
+pub struct EmailLogEntry {
+    pub to: String,
+    pub kind: String,
+    pub status: String,
+    pub error: Option<String>,
+    pub at: OffsetDateTime,
+}
+
+impl From<EmailLogEntry> for api::EmailLogEntryResponse {
+    fn from(entry: EmailLogEntry) -> Self {
+        api::EmailLogEntryResponse {
+            to: entry.to,
+            kind: entry.kind,
+            status: entry.status,
+            error: entry.error,
+            at: entry.at,
+        }
+    }
+}
+
+/// Why a migration keeps the database from matching what this build expects
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationDriftReason {
+    /// Embedded in the binary but not yet applied
+    NotApplied,
+    /// Applied, but the file has changed since
+    ChecksumMismatch,
+    /// Applied by a newer build this binary doesn't know about
+    UnknownToBinary,
+}
+
+impl MigrationDriftReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MigrationDriftReason::NotApplied => "not_applied",
+            MigrationDriftReason::ChecksumMismatch => "checksum_mismatch",
+            MigrationDriftReason::UnknownToBinary => "unknown_to_binary",
+        }
+    }
+}
+
+/// Refund owed on a cancelled reservation; payment happens elsewhere, this is for reconciliation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefundStatus {
+    None,
+    Pending,
+}
+
+impl RefundStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RefundStatus::None => "none",
+            RefundStatus::Pending => "pending",
+        }
+    }
+
+    /// Policy for a guest cancelling at `now`: a full refund is owed when the event starts at least
+    /// `refund_cutoff` later, nothing after that or when no refund window is configured
+    pub fn for_cancellation(start_time: OffsetDateTime, now: OffsetDateTime, refund_cutoff: Option<time::Duration>) -> Self {
+        match refund_cutoff {
+            Some(cutoff) if now <= start_time - cutoff => RefundStatus::Pending,
+            _ => RefundStatus::None,
+        }
+    }
+}
+
+impl From<RefundStatus> for api::RefundStatus {
+    fn from(status: RefundStatus) -> Self {
+        match status {
+            RefundStatus::None => api::RefundStatus::None,
+            RefundStatus::Pending => api::RefundStatus::Pending,
+        }
+    }
+}
+
+// What reconciling an event against its confirmed reservations found and corrected
+#[derive(Debug, Clone)]
+pub struct EventReconciliation {
+    pub event_id: Uuid,
+    pub capacity: u32,
+    pub confirmed_spots: u32,
+    pub status_before: EventStatus,
+    pub status_after: EventStatus,
+    pub tokens_expired: u64,
+    pub tokens_activated: u64,
+    pub tokens_deactivated: u64,
+}
+
+impl EventReconciliation {
+    /// The status an open or full event should have for the spots confirmed
+    pub fn expected_status(capacity: u32, confirmed_spots: u32) -> EventStatus {
+        if confirmed_spots >= capacity {
+            EventStatus::Full
+        } else {
+            EventStatus::Open
+        }
+    }
+}
+
+impl From<EventReconciliation> for api::ReconcileEventResponse {
+    fn from(reconciliation: EventReconciliation) -> Self {
+        api::ReconcileEventResponse {
+            event_id: reconciliation.event_id,
+            capacity: reconciliation.capacity,
+            confirmed_spots: reconciliation.confirmed_spots,
+            status: api::EventStatusChange {
+                before: reconciliation.status_before.into(),
+                after: reconciliation.status_after.into(),
+            },
+            tokens: api::TokenCorrections {
+                expired: reconciliation.tokens_expired,
+                activated: reconciliation.tokens_activated,
+                deactivated: reconciliation.tokens_deactivated,
+            },
+        }
+    }
+}
+
+// A migration out of step between the database and the binary
+#[derive(Debug, Clone)]
+pub struct MigrationDrift {
+    pub version: i64,
+    pub description: String,
+    pub reason: MigrationDriftReason,
+}
+
+impl From<MigrationDrift> for api::PendingMigrationResponse {
+    fn from(drift: MigrationDrift) -> Self {
+        api::PendingMigrationResponse {
+            version: drift.version,
+            description: drift.description,
+            reason: drift.reason.as_str(),
+        }
+    }
+}
+
+// Raw reservation token record for support staff, whatever state the token is in
+#[derive(Debug, Clone)]
+pub struct TokenRecord {
+    pub token: String,
+    pub status: String,
+    pub label: Option<String>,
+    pub created_at: OffsetDateTime,
+    pub used_at: Option<OffsetDateTime>,
+    pub reservation_id: Uuid,
+    /// Stored status: `pending`, `confirmed` or `cancelled`
+    pub reservation_status: String,
+    pub reservation_cancelled_at: Option<OffsetDateTime>,
+    pub event_id: Uuid,
+}
+
+impl From<TokenRecord> for api::TokenRecordResponse {
+    fn from(record: TokenRecord) -> Self {
+        api::TokenRecordResponse {
+            token: record.token,
+            status: record.status,
+            label: record.label,
+            created_at: record.created_at,
+            used_at: record.used_at,
+            reservation_id: record.reservation_id,
+            reservation_status: record.reservation_status,
+            reservation_cancelled_at: record.reservation_cancelled_at,
+            event_id: record.event_id,
+        }
+    }
+}
+
+// An active ticket held by an email address, with the event it admits to
+#[derive(Debug, Clone)]
+pub struct WalletToken {
+    pub token: String,
+    pub label: Option<String>,
+    pub reservation_id: Uuid,
+    pub event_id: Uuid,
+    pub event_name: String,
+    pub event_start_time: OffsetDateTime,
+    pub event_end_time: OffsetDateTime,
+    pub event_location: Option<String>,
+}
+
+impl From<WalletToken> for api::MyTokenResponse {
+    fn from(token: WalletToken) -> Self {
+        api::MyTokenResponse {
+            token: token.token,
+            reservation_id: token.reservation_id,
+            label: token.label,
+            event: api::MyTokenEventResponse {
+                id: token.event_id,
+                name: token.event_name,
+                start_time: token.event_start_time,
+                end_time: token.event_end_time,
+                location: token.event_location,
+            },
+        }
+    }
+}
+
+// An unused ticket to print on an event's QR sheet, with who it's for
+#[derive(Debug, Clone)]
+pub struct QrSheetTicket {
+    pub token: String,
+    pub guest_name: String,
+    pub label: Option<String>,
+    pub short_code: Option<String>,
+}
+
+impl QrSheetTicket {
+    /// Lines printed under the QR code: the guest, then the spot label and short code when there are any
+    pub fn caption(&self) -> Vec<String> {
+        let mut lines = vec![self.guest_name.clone()];
+        lines.extend(self.label.clone());
+        lines.extend(self.short_code.as_ref().map(|code| format!("Code {}", code)));
+        lines
+    }
+}
+
+// A reservation whose email matched a support search, with the event it's for
+#[derive(Debug, Clone)]
+pub struct ReservationSearchResult {
+    pub reservation_id: Uuid,
+    pub user_email: String,
+    pub user_name: String,
+    pub status: String,
+    pub spot_count: u32,
+    pub event_id: Uuid,
+    pub event_name: String,
+    pub created_at: OffsetDateTime,
+}
+
+impl From<ReservationSearchResult> for api::ReservationSearchResultResponse {
+    fn from(result: ReservationSearchResult) -> Self {
+        api::ReservationSearchResultResponse {
+            reservation_id: result.reservation_id,
+            user_email: crate::email::mask_email(&result.user_email),
+            user_name: result.user_name,
+            status: result.status,
+            spot_count: result.spot_count,
+            event_id: result.event_id,
+            event_name: result.event_name,
+            created_at: result.created_at,
+        }
+    }
+}
+
+// Aggregate reservation figures for an event
+#[derive(Debug, Clone)]
+pub struct EventAnalytics {
+    pub event_id: Uuid,
+    pub capacity: u32,
+    pub confirmed_spots: u32,
+    pub fill_rate: f64,
+    pub peak_hour: Option<OffsetDateTime>,
+    pub average_party_size: Option<f64>,
+}
+
+impl EventAnalytics {
+    pub fn spots_remaining(&self) -> u32 {
+        self.capacity.saturating_sub(self.confirmed_spots)
+    }
+
+    /// Whether the spots left are within `threshold` (a fraction of capacity) of selling out
+    pub fn is_near_capacity(&self, threshold: f64) -> bool {
+        f64::from(self.spots_remaining()) <= f64::from(self.capacity) * threshold
+    }
+
+    /// Analytics JSON, flagging `near_capacity` against the configured threshold
+    pub fn into_response(self, near_capacity_threshold: f64) -> api::EventAnalyticsResponse {
+        api::EventAnalyticsResponse {
+            spots_remaining: self.spots_remaining(),
+            near_capacity: self.is_near_capacity(near_capacity_threshold),
+            event_id: self.event_id,
+            capacity: self.capacity,
+            confirmed_spots: self.confirmed_spots,
+            fill_rate: self.fill_rate,
+            peak_hour: self.peak_hour,
+            average_party_size: self.average_party_size,
+        }
+    }
+}
+
+// Confirmed spots accrued within one time bucket
+#[derive(Debug, Clone)]
+pub struct ReservationBucket {
+    pub bucket_start: OffsetDateTime,
+    pub spots: u32,
+}
+
+// Event details before it has been persisted
+#[derive(Debug, Clone)]
+pub struct NewEvent {
+    pub name: String,
+    pub description: Option<String>,
+    pub start_time: OffsetDateTime,
+    pub end_time: OffsetDateTime,
+    pub capacity: u32,
+    pub location: Option<String>,
+    pub max_reservations: Option<u32>,
+    pub max_spots_per_reservation: Option<u32>,
+    pub min_spots_per_reservation: Option<u32>,
+    pub visibility: EventVisibility,
+    pub reservations_open_at: Option<OffsetDateTime>,
+    pub reservations_close_at: Option<OffsetDateTime>,
+    pub skip_verification: bool,
+    pub require_name_match: bool,
+    pub token_rotation_minutes: Option<u32>,
+    pub series_id: Option<Uuid>,
+}
+
+/// Longest name-derived part of an event slug, before the random suffix
+const MAX_SLUG_BASE_LENGTH: usize = 50;
+/// Length of the random suffix that keeps event slugs unique
+const SLUG_SUFFIX_LENGTH: usize = 6;
+
+impl NewEvent {
+    /// A party-size floor above the ceiling would make the event impossible to reserve
+    pub fn validate_spot_limits(&self) -> Result<(), String> {
+        match (self.min_spots_per_reservation, self.max_spots_per_reservation) {
+            (Some(min), Some(max)) if min > max => {
+                Err(format!("min_spots_per_reservation ({}) cannot exceed max_spots_per_reservation ({})", min, max))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Capacity has to fit within the deployment's `MAX_EVENT_CAPACITY`
+    pub fn validate_capacity(&self, max_capacity: u32) -> Result<(), String> {
+        if self.capacity > max_capacity {
+            return Err(format!("capacity ({}) cannot exceed the maximum of {}", self.capacity, max_capacity));
+        }
+        Ok(())
+    }
+
+    /// Friendly URL identifier: the name lowercased and hyphenated, plus a short random suffix
+    pub fn generate_slug(&self) -> String {
+        let mut base = String::new();
+        for c in self.name.chars().flat_map(char::to_lowercase) {
+            if c.is_ascii_alphanumeric() {
+                base.push(c);
+            } else if !base.is_empty() && !base.ends_with('-') {
+                base.push('-');
+            }
+            if base.len() >= MAX_SLUG_BASE_LENGTH {
+                break;
+            }
+        }
+        let base = base.trim_end_matches('-');
+
+        let suffix: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(SLUG_SUFFIX_LENGTH)
+            .map(|byte| char::from(byte).to_ascii_lowercase())
+            .collect();
+
+        if base.is_empty() {
+            format!("event-{}", suffix)
+        } else {
+            format!("{}-{}", base, suffix)
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct Creating;
@@ -49,6 +837,7 @@ impl Display for Creating {
 #[derive(Debug, Clone)]
 pub struct Pending {
     pub created_at: OffsetDateTime,
+    #[cfg_attr(not(test), expect(dead_code))]
     pub updated_at: OffsetDateTime,
 }
 
@@ -70,40 +859,112 @@ pub struct Confirmed {
     pub updated_at: OffsetDateTime,
     pub verified_at: OffsetDateTime,
     pub reservation_tokens: Vec<AnyReservationToken>,
+    /// Secret from the confirmation email that lets the guest cancel without logging in
+    pub cancellation_token: String,
+}
+
+impl From<Confirmed> for api::ReservationStatus {
+    fn from(_status: Confirmed) -> Self {
+        api::ReservationStatus::Confirmed
+    }
+}
+
+impl Display for Confirmed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Confirmed")
+    }
+}
+
+/// A cancelled reservation; named apart from the event's `Cancelled` state
+#[derive(Debug, Clone)]
+pub struct ReservationCancelled {
+    pub created_at: OffsetDateTime,
+    /// Set when the reservation was confirmed before it was cancelled
+    pub verified_at: Option<OffsetDateTime>,
+    pub cancelled_at: OffsetDateTime,
 }
 
-impl From<Confirmed> for api::ReservationStatus {
-    fn from(_status: Confirmed) -> Self {
-        api::ReservationStatus::Confirmed
+impl From<ReservationCancelled> for api::ReservationStatus {
+    fn from(_status: ReservationCancelled) -> Self {
+        api::ReservationStatus::Cancelled
     }
 }
 
-impl Display for Confirmed {
+impl Display for ReservationCancelled {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Confirmed")
+        write!(f, "Cancelled")
     }
 }
 
 
-#[derive(Debug)] // maybe should have a "new" function impl for this instead of pub String
+#[derive(Debug, Clone)] // maybe should have a "new" function impl for this instead of pub String
 pub struct VerificationToken(pub String);
 
+/// Shortest verification token we will generate, regardless of configuration
+pub const MIN_VERIFICATION_TOKEN_LENGTH: usize = 16;
+
 impl VerificationToken {
     pub fn new() -> Self {
         Self(Uuid::new_v4().to_string())
     }
+
+    /// Generate a URL-safe base62 token of the given length (at least `MIN_VERIFICATION_TOKEN_LENGTH`)
+    pub fn new_with_len(len: usize) -> Self {
+        let token = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(len.max(MIN_VERIFICATION_TOKEN_LENGTH))
+            .map(char::from)
+            .collect();
+        Self(token)
+    }
+
+    /// Generate a token of the configured length, or a UUID when no length is configured
+    pub fn generate(len: Option<usize>) -> Self {
+        match len {
+            Some(len) => Self::new_with_len(len),
+            None => Self::new(),
+        }
+    }
+}
+
+/// Characters short codes are drawn from: uppercase base32-style, without 0/O, 1/I/L and U,
+/// which are easily misread or mistyped
+const SHORT_CODE_ALPHABET: &[u8] = b"23456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// Length of a reservation token's short code
+pub const SHORT_CODE_LENGTH: usize = 6;
+
+/// Generate a random short code for manual entry at the door
+pub fn generate_short_code() -> String {
+    let mut rng = rand::thread_rng();
+    (0..SHORT_CODE_LENGTH)
+        .map(|_| char::from(SHORT_CODE_ALPHABET[rng.gen_range(0..SHORT_CODE_ALPHABET.len())]))
+        .collect()
+}
+
+/// Canonical form of a typed short code: trimmed and uppercase
+pub fn normalize_short_code(code: &str) -> String {
+    code.trim().to_ascii_uppercase()
 }
 
+#[derive(Debug, Clone)]
+pub struct Inactive;
 #[derive(Debug, Clone)]
 pub struct Active;
 #[derive(Debug, Clone)]
-pub struct Used;
+pub struct Used {
+    pub used_at: OffsetDateTime,
+}
 #[derive(Debug, Clone)]
 pub struct Expired;
-#[derive(Debug, Clone)]
-pub struct Unknown;
 
 // Implement Display for all token states
+impl Display for Inactive {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Inactive")
+    }
+}
+
 impl Display for Active {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "Active")
@@ -122,16 +983,13 @@ impl Display for Expired {
     }
 }
 
-impl Display for Unknown {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Unknown")
-    }
-}
-
 #[derive(Debug, Clone)]
 pub struct ReservationToken<State> where State: Display {
     pub token: String,
     pub reservation_id: Uuid,
+    pub label: Option<String>,
+    /// Short code for typing in by hand at the door; unique within the event, assigned when stored
+    pub short_code: Option<String>,
     pub created_at: OffsetDateTime,
     pub status: State,
 }
@@ -141,20 +999,181 @@ impl ReservationToken<Active> {
         Self {
             token: format!("r-{}", Uuid::new_v4()),
             reservation_id,
+            label: None,
+            short_code: None,
+            created_at,
+            status: Active,
+        }
+    }
+
+    /// Issue a replacement token value, keeping everything else (e.g. after a collision)
+    pub fn regenerate(&self) -> Self {
+        Self {
+            token: format!("r-{}", Uuid::new_v4()),
+            ..self.clone()
+        }
+    }
+
+    /// Attach a spot label (e.g. a seat identifier) to the token
+    pub fn with_label(self, label: Option<String>) -> Self {
+        Self { label, ..self }
+    }
+
+    /// Mark an active token as used (e.g. scanned at the door)
+    pub fn mark_used(self, used_at: OffsetDateTime) -> ReservationToken<Used> {
+        ReservationToken {
+            token: self.token,
+            reservation_id: self.reservation_id,
+            label: self.label,
+            short_code: self.short_code,
+            created_at: self.created_at,
+            status: Used { used_at },
+        }
+    }
+
+    /// Retire an active token that is no longer needed (e.g. its spot was removed)
+    pub fn expire(self) -> ReservationToken<Expired> {
+        ReservationToken {
+            token: self.token,
+            reservation_id: self.reservation_id,
+            label: self.label,
+            short_code: self.short_code,
+            created_at: self.created_at,
+            status: Expired,
+        }
+    }
+}
+
+impl ReservationToken<Inactive> {
+    /// Pre-issue a token for a pending reservation; it can't be scanned until activated on confirm
+    pub fn pre_issue(reservation_id: Uuid, created_at: OffsetDateTime) -> Self {
+        Self {
+            token: format!("r-{}", Uuid::new_v4()),
+            reservation_id,
+            label: None,
+            short_code: None,
             created_at,
+            status: Inactive,
+        }
+    }
+
+    /// Issue a replacement token value, keeping everything else (e.g. after a collision)
+    pub fn regenerate(&self) -> Self {
+        Self {
+            token: format!("r-{}", Uuid::new_v4()),
+            ..self.clone()
+        }
+    }
+
+    /// Attach a spot label (e.g. a seat identifier) to the token
+    pub fn with_label(self, label: Option<String>) -> Self {
+        Self { label, ..self }
+    }
+
+    /// Activate a pre-issued token once its reservation is confirmed
+    pub fn activate(self) -> ReservationToken<Active> {
+        ReservationToken {
+            token: self.token,
+            reservation_id: self.reservation_id,
+            label: self.label,
+            short_code: self.short_code,
+            created_at: self.created_at,
+            status: Active,
+        }
+    }
+}
+
+impl ReservationToken<Used> {
+    /// Revert a used token to active (e.g. the wrong ticket was scanned)
+    pub fn unmark_used(self) -> ReservationToken<Active> {
+        ReservationToken {
+            token: self.token,
+            reservation_id: self.reservation_id,
+            label: self.label,
+            short_code: self.short_code,
+            created_at: self.created_at,
             status: Active,
         }
     }
 }
 
+/// Time-windowed form of a reservation token (`{token}.{window}.{signature}`) shown for events that
+/// rotate their ticket codes; the window counter advances every rotation period, so a screenshot
+/// stops scanning once its window has passed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RotatedToken {
+    pub token: String,
+    pub window: i64,
+    signature: String,
+}
+
+impl RotatedToken {
+    /// The rotation window `now` falls in
+    pub fn current_window(rotation_minutes: u32, now: OffsetDateTime) -> i64 {
+        now.unix_timestamp().div_euclid(Self::window_seconds(rotation_minutes))
+    }
+
+    /// When a window ends and the next code takes over
+    pub fn window_ends_at(window: i64, rotation_minutes: u32) -> OffsetDateTime {
+        let end = (window + 1) * Self::window_seconds(rotation_minutes);
+        OffsetDateTime::from_unix_timestamp(end).expect("Rotation window out of range")
+    }
+
+    /// Issue the code for a token in the window `now` falls in
+    pub fn issue(token: &str, secret: &str, rotation_minutes: u32, now: OffsetDateTime) -> Self {
+        let window = Self::current_window(rotation_minutes, now);
+        RotatedToken {
+            token: token.to_string(),
+            window,
+            signature: Self::sign(token, window, secret),
+        }
+    }
+
+    /// Split a presented code into its parts; plain reservation tokens don't parse
+    pub fn parse(presented: &str) -> Option<Self> {
+        let mut parts = presented.rsplitn(3, '.');
+        let signature = parts.next()?;
+        let window = parts.next()?.parse().ok()?;
+        let token = parts.next()?;
+
+        Some(RotatedToken {
+            token: token.to_string(),
+            window,
+            signature: signature.to_string(),
+        })
+    }
+
+    /// Whether the code was issued by us for the current window
+    pub fn is_current(&self, secret: &str, rotation_minutes: u32, now: OffsetDateTime) -> bool {
+        self.window == Self::current_window(rotation_minutes, now)
+            && self.signature == Self::sign(&self.token, self.window, secret)
+    }
+
+    fn window_seconds(rotation_minutes: u32) -> i64 {
+        i64::from(rotation_minutes.max(1)) * 60
+    }
+
+    fn sign(token: &str, window: i64, secret: &str) -> String {
+        let digest = Sha256::digest(format!("{}:{}:{}", secret, token, window));
+        digest[..16].iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+}
+
+impl Display for RotatedToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.token, self.window, self.signature)
+    }
+}
+
+pub type InactiveReservationToken = ReservationToken<Inactive>;
 pub type ActiveReservationToken = ReservationToken<Active>;
 pub type UsedReservationToken = ReservationToken<Used>;
 pub type ExpiredReservationToken = ReservationToken<Expired>;
-pub type UnknownReservationToken = ReservationToken<Unknown>;
 
 // Enum to represent tokens in any state
 #[derive(Debug, Clone)]
 pub enum AnyReservationToken {
+    Inactive(ReservationToken<Inactive>),
     Active(ReservationToken<Active>),
     Used(ReservationToken<Used>),
     Expired(ReservationToken<Expired>),
@@ -164,6 +1183,7 @@ impl AnyReservationToken {
     /// Get the token string regardless of state
     pub fn token(&self) -> &str {
         match self {
+            AnyReservationToken::Inactive(token) => &token.token,
             AnyReservationToken::Active(token) => &token.token,
             AnyReservationToken::Used(token) => &token.token,
             AnyReservationToken::Expired(token) => &token.token,
@@ -173,6 +1193,7 @@ impl AnyReservationToken {
     /// Get the reservation ID regardless of state
     pub fn reservation_id(&self) -> Uuid {
         match self {
+            AnyReservationToken::Inactive(token) => token.reservation_id,
             AnyReservationToken::Active(token) => token.reservation_id,
             AnyReservationToken::Used(token) => token.reservation_id,
             AnyReservationToken::Expired(token) => token.reservation_id,
@@ -182,15 +1203,80 @@ impl AnyReservationToken {
     /// Get the creation time regardless of state
     pub fn created_at(&self) -> OffsetDateTime {
         match self {
+            AnyReservationToken::Inactive(token) => token.created_at,
             AnyReservationToken::Active(token) => token.created_at,
             AnyReservationToken::Used(token) => token.created_at,
             AnyReservationToken::Expired(token) => token.created_at,
         }
     }
+
+    /// Get the spot label regardless of state
+    pub fn label(&self) -> Option<&str> {
+        match self {
+            AnyReservationToken::Inactive(token) => token.label.as_deref(),
+            AnyReservationToken::Active(token) => token.label.as_deref(),
+            AnyReservationToken::Used(token) => token.label.as_deref(),
+            AnyReservationToken::Expired(token) => token.label.as_deref(),
+        }
+    }
+
+    /// Get the manual-entry short code regardless of state
+    pub fn short_code(&self) -> Option<&str> {
+        match self {
+            AnyReservationToken::Inactive(token) => token.short_code.as_deref(),
+            AnyReservationToken::Active(token) => token.short_code.as_deref(),
+            AnyReservationToken::Used(token) => token.short_code.as_deref(),
+            AnyReservationToken::Expired(token) => token.short_code.as_deref(),
+        }
+    }
+
+    /// Set the manual-entry short code regardless of state
+    pub fn set_short_code(&mut self, short_code: String) {
+        let slot = match self {
+            AnyReservationToken::Inactive(token) => &mut token.short_code,
+            AnyReservationToken::Active(token) => &mut token.short_code,
+            AnyReservationToken::Used(token) => &mut token.short_code,
+            AnyReservationToken::Expired(token) => &mut token.short_code,
+        };
+        *slot = Some(short_code);
+    }
+
+    /// Get the time the token was used, if it has been
+    pub fn used_at(&self) -> Option<OffsetDateTime> {
+        match self {
+            AnyReservationToken::Used(token) => Some(token.status.used_at),
+            _ => None,
+        }
+    }
+}
+
+impl From<&AnyReservationToken> for api::ReservationTokenStatus {
+    fn from(token: &AnyReservationToken) -> Self {
+        match token {
+            AnyReservationToken::Inactive(_) => api::ReservationTokenStatus::Inactive,
+            AnyReservationToken::Active(_) => api::ReservationTokenStatus::Active,
+            AnyReservationToken::Used(_) => api::ReservationTokenStatus::Used,
+            AnyReservationToken::Expired(_) => api::ReservationTokenStatus::Expired,
+        }
+    }
+}
+
+impl From<AnyReservationToken> for api::ReservationTokenResponse {
+    fn from(token: AnyReservationToken) -> Self {
+        api::ReservationTokenResponse {
+            token: token.token().to_string(),
+            reservation_id: token.reservation_id(),
+            label: token.label().map(str::to_string),
+            short_code: token.short_code().map(str::to_string),
+            status: (&token).into(),
+            created_at: token.created_at(),
+            used_at: token.used_at(),
+        }
+    }
 }
 
 // Generic reservation with type-state
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Reservation<State> where State: Display {
     pub id: Uuid,
     pub event_id: Uuid,
@@ -198,10 +1284,80 @@ pub struct Reservation<State> where State: Display {
     pub user_email: String,
     pub verification_token: VerificationToken,
     pub spot_count: u32,
+    pub spot_labels: Option<Vec<String>>,
+    pub answers: Option<serde_json::Value>,
+    /// Who the reservation is for, when it differs from the person making it
+    pub party_name: Option<String>,
     pub status: State,
 }
 
+impl<State: Display> Reservation<State> {
+    /// The name to show the reservation under: the party name, falling back to the reserver's name
+    pub fn display_name(&self) -> &str {
+        self.party_name.as_deref().unwrap_or(&self.user_name)
+    }
+}
+
 impl ConfirmedReservation {
+    /// Move a confirmed reservation to another event, replacing its tokens with freshly issued ones
+    pub fn move_to(self, event_id: Uuid, moved_at: OffsetDateTime) -> ConfirmedReservation {
+        let reservation_tokens = issue_reservation_tokens(self.id, self.spot_count, self.spot_labels.as_deref(), moved_at);
+
+        Reservation {
+            id: self.id,
+            event_id,
+            user_name: self.user_name,
+            user_email: self.user_email,
+            verification_token: self.verification_token,
+            spot_count: self.spot_count,
+            spot_labels: self.spot_labels,
+            answers: self.answers,
+            party_name: self.party_name,
+            status: Confirmed {
+                created_at: self.status.created_at,
+                updated_at: moved_at,
+                verified_at: self.status.verified_at,
+                reservation_tokens,
+                cancellation_token: self.status.cancellation_token,
+            },
+        }
+    }
+
+    /// Change the number of spots. Added spots get new active tokens appended to the end of the token list;
+    /// removed spots expire the most recently issued active tokens, which are returned. Used tokens are
+    /// never taken back, so callers must not go below the number already checked in.
+    pub fn adjust_spots(mut self, spot_count: u32, adjusted_at: OffsetDateTime) -> (ConfirmedReservation, Vec<ExpiredReservationToken>) {
+        let mut expired = Vec::new();
+
+        if spot_count > self.spot_count {
+            for index in self.spot_count as usize..spot_count as usize {
+                let label = self.spot_labels.as_ref().and_then(|labels| labels.get(index).cloned());
+                self.status.reservation_tokens.push(AnyReservationToken::from_active(ReservationToken::new(self.id, adjusted_at).with_label(label)));
+            }
+        } else {
+            let mut to_expire = (self.spot_count - spot_count) as usize;
+            for token in self.status.reservation_tokens.iter_mut().rev() {
+                if to_expire == 0 {
+                    break;
+                }
+                if let AnyReservationToken::Active(active) = token {
+                    let retired = active.clone().expire();
+                    expired.push(retired.clone());
+                    *token = AnyReservationToken::Expired(retired);
+                    to_expire -= 1;
+                }
+            }
+            if let Some(labels) = self.spot_labels.as_mut() {
+                labels.truncate(spot_count as usize);
+            }
+        }
+
+        self.spot_count = spot_count;
+        self.status.updated_at = adjusted_at;
+
+        (self, expired)
+    }
+
     /// Get all active reservation tokens with full type safety
     pub fn get_active_reservation_tokens(&self) -> Vec<ActiveReservationToken> {
         self.status.reservation_tokens
@@ -233,6 +1389,7 @@ impl ConfirmedReservation {
     }
 
     /// Get all expired reservation tokens with full type safety
+    #[cfg_attr(not(test), expect(dead_code))]
     pub fn get_expired_reservation_tokens(&self) -> Vec<ExpiredReservationToken> {
         self.status.reservation_tokens
             .iter()
@@ -252,9 +1409,10 @@ impl ConfirmedReservation {
 pub type CreatingReservation = Reservation<Creating>;
 pub type PendingReservation = Reservation<Pending>;
 pub type ConfirmedReservation = Reservation<Confirmed>;
+pub type CancelledReservation = Reservation<ReservationCancelled>;
 
 impl CreatingReservation {
-    pub fn prepare(event_id: Uuid, user_name: String, user_email: String, spot_count: u32) -> Self {
+    pub fn prepare(event_id: Uuid, user_name: String, user_email: String, spot_count: u32, spot_labels: Option<Vec<String>>) -> Self {
         Reservation {
             id: Uuid::new_v4(),
             event_id,
@@ -262,29 +1420,217 @@ impl CreatingReservation {
             user_email,
             verification_token: VerificationToken::new(),
             spot_count,
+            spot_labels,
+            answers: None,
+            party_name: None,
             status: Creating,
         }
     }
 
-    pub fn create(self, created_at: OffsetDateTime) -> Reservation<Pending> {
-        Reservation {
-            id: self.id,
-            event_id: self.event_id,
-            user_name: self.user_name,
-            user_email: self.user_email,
-            verification_token: self.verification_token,
-            spot_count: self.spot_count,
-            status: Pending {
-                created_at,
-                updated_at: created_at,
+    /// Replace the generated verification token (e.g. with one of a configured length)
+    pub fn with_verification_token(self, verification_token: VerificationToken) -> Self {
+        Self { verification_token, ..self }
+    }
+
+    /// Attach answers to the event's custom questions
+    pub fn with_answers(self, answers: Option<serde_json::Value>) -> Self {
+        Self { answers, ..self }
+    }
+
+    /// Name the party the reservation is for
+    pub fn with_party_name(self, party_name: Option<String>) -> Self {
+        Self { party_name, ..self }
+    }
+}
+
+/// A custom question organizers ask at reserve time; answers are keyed by `key`
+#[derive(Debug, Clone)]
+pub struct EventQuestion {
+    pub key: String,
+    pub prompt: String,
+    pub required: bool,
+}
+
+impl EventQuestion {
+    /// Check reserve-time answers against an event's questions: every required question
+    /// needs a non-blank string answer and no answers to unknown questions are accepted
+    pub fn validate_answers(questions: &[EventQuestion], answers: Option<&serde_json::Value>) -> Result<(), String> {
+        let empty = serde_json::Map::new();
+        let answers = match answers {
+            None | Some(serde_json::Value::Null) => &empty,
+            Some(serde_json::Value::Object(answers)) => answers,
+            Some(_) => return Err("Answers must be an object keyed by question".to_string()),
+        };
+
+        for (key, value) in answers {
+            if !questions.iter().any(|question| &question.key == key) {
+                return Err(format!("Unknown question: {}", key));
+            }
+            if !value.is_string() {
+                return Err(format!("Answer to {} must be a string", key));
+            }
+        }
+
+        for question in questions.iter().filter(|question| question.required) {
+            let answered = answers
+                .get(&question.key)
+                .and_then(serde_json::Value::as_str)
+                .is_some_and(|answer| !answer.trim().is_empty());
+            if !answered {
+                return Err(format!("Missing answer to required question: {}", question.key));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl From<EventQuestion> for api::EventQuestionResponse {
+    fn from(question: EventQuestion) -> Self {
+        api::EventQuestionResponse {
+            key: question.key,
+            prompt: question.prompt,
+            required: question.required,
+        }
+    }
+}
+
+impl From<api::EventQuestionRequest> for EventQuestion {
+    fn from(question: api::EventQuestionRequest) -> Self {
+        EventQuestion {
+            key: question.key,
+            prompt: question.prompt,
+            required: question.required,
+        }
+    }
+}
+
+/// A reservation's answers to its event's custom questions
+#[derive(Debug)]
+pub struct ReservationAnswers {
+    pub reservation_id: Uuid,
+    pub user_name: String,
+    pub user_email: String,
+    pub answers: serde_json::Value,
+}
+
+impl From<ReservationAnswers> for api::ReservationAnswersResponse {
+    fn from(entry: ReservationAnswers) -> Self {
+        api::ReservationAnswersResponse {
+            reservation_id: entry.reservation_id,
+            user_name: entry.user_name,
+            user_email: entry.user_email,
+            answers: entry.answers,
+        }
+    }
+}
+
+/// A pending or confirmed reservation as listed on an event's roster
+#[derive(Debug)]
+pub struct RosterEntry {
+    pub reservation_id: Uuid,
+    pub user_name: String,
+    pub user_email: String,
+    pub party_name: Option<String>,
+    pub spot_count: u32,
+    pub confirmed: bool,
+}
+
+impl From<RosterEntry> for api::RosterEntryResponse {
+    fn from(entry: RosterEntry) -> Self {
+        api::RosterEntryResponse {
+            reservation_id: entry.reservation_id,
+            party_name: entry.party_name.unwrap_or_else(|| entry.user_name.clone()),
+            user_name: entry.user_name,
+            user_email: entry.user_email,
+            spot_count: entry.spot_count,
+            status: if entry.confirmed { api::ReservationStatus::Confirmed } else { api::ReservationStatus::Pending },
+        }
+    }
+}
+
+/// Spots set aside for a short window while a user completes checkout
+#[derive(Debug, Clone)]
+pub struct Holding {
+    pub hold_token: String,
+    pub created_at: OffsetDateTime,
+    pub expires_at: OffsetDateTime,
+}
+
+impl Display for Holding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Holding")
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Hold<State> {
+    pub id: Uuid,
+    pub event_id: Uuid,
+    pub spot_count: u32,
+    pub status: State,
+}
+
+pub type HoldingReservation = Hold<Holding>;
+
+impl HoldingReservation {
+    /// Place a hold on spots for an event that lapses after `ttl`
+    pub fn place(event_id: Uuid, spot_count: u32, placed_at: OffsetDateTime, ttl: time::Duration) -> Self {
+        Hold {
+            id: Uuid::new_v4(),
+            event_id,
+            spot_count,
+            status: Holding {
+                hold_token: format!("h-{}", Uuid::new_v4()),
+                created_at: placed_at,
+                expires_at: placed_at + ttl,
             },
         }
     }
+
+    #[cfg_attr(not(test), expect(dead_code))]
+    pub fn is_expired(&self, now: OffsetDateTime) -> bool {
+        self.status.expires_at <= now
+    }
+}
+
+/// Generate one active token per spot, attaching spot labels where provided
+fn issue_reservation_tokens(reservation_id: Uuid, spot_count: u32, spot_labels: Option<&[String]>, issued_at: OffsetDateTime) -> Vec<AnyReservationToken> {
+    (0..spot_count as usize).map(|index| {
+        let label = spot_labels.and_then(|labels| labels.get(index).cloned());
+        AnyReservationToken::from_active(ReservationToken::new(reservation_id, issued_at).with_label(label))
+    }).collect()
 }
 
 impl PendingReservation {
+    /// Deadline for verifying this reservation, when verification links expire
+    pub fn verification_expires_at(&self, ttl: Option<time::Duration>) -> Option<OffsetDateTime> {
+        ttl.map(|ttl| self.status.created_at + ttl)
+    }
+
+    /// Inactive tokens to store alongside the pending reservation, one per spot with its label
+    pub fn pre_issue_tokens(&self, issued_at: OffsetDateTime) -> Vec<AnyReservationToken> {
+        (0..self.spot_count as usize).map(|index| {
+            let label = self.spot_labels.as_ref().and_then(|labels| labels.get(index).cloned());
+            AnyReservationToken::Inactive(ReservationToken::pre_issue(self.id, issued_at).with_label(label))
+        }).collect()
+    }
+
     /// Confirm a pending reservation
     pub fn confirm(self, confirmed_at: OffsetDateTime) -> Reservation<Confirmed> {
+        let reservation_tokens = issue_reservation_tokens(self.id, self.spot_count, self.spot_labels.as_deref(), confirmed_at);
+        self.into_confirmed(confirmed_at, reservation_tokens)
+    }
+
+    /// Confirm a pending reservation by activating its pre-issued tokens instead of issuing new ones
+    pub fn confirm_pre_issued(self, confirmed_at: OffsetDateTime, pre_issued: Vec<ReservationToken<Inactive>>) -> Reservation<Confirmed> {
+        let reservation_tokens = pre_issued.into_iter()
+            .map(|token| AnyReservationToken::Active(token.activate()))
+            .collect();
+        self.into_confirmed(confirmed_at, reservation_tokens)
+    }
+
+    fn into_confirmed(self, confirmed_at: OffsetDateTime, reservation_tokens: Vec<AnyReservationToken>) -> Reservation<Confirmed> {
         Reservation {
             id: self.id,
             event_id: self.event_id,
@@ -292,13 +1638,15 @@ impl PendingReservation {
             user_email: self.user_email,
             verification_token: self.verification_token,
             spot_count: self.spot_count,
+            spot_labels: self.spot_labels,
+            answers: self.answers,
+            party_name: self.party_name,
             status: Confirmed {
                 created_at: self.status.created_at,
                 updated_at: confirmed_at,
                 verified_at: confirmed_at, 
-                reservation_tokens: (0..self.spot_count).map(|_| {
-                    AnyReservationToken::from_active(ReservationToken::new(self.id, confirmed_at))
-                }).collect(),
+                reservation_tokens,
+                cancellation_token: VerificationToken::new().0,
             },
         }
     }
@@ -311,61 +1659,111 @@ impl AnyReservationToken {
         AnyReservationToken::Active(token)
     }
 
-    pub fn from_used(token: ReservationToken<Used>) -> Self {
-        AnyReservationToken::Used(token)
-    }
-
-    pub fn from_expired(token: ReservationToken<Expired>) -> Self {
-        AnyReservationToken::Expired(token)
-    }
-
-    /// Try to extract a typed token (returns None if wrong variant)
-    pub fn as_active(&self) -> Option<&ReservationToken<Active>> {
-        if let AnyReservationToken::Active(token) = self {
-            Some(token)
-        } else {
-            None
-        }
-    }
-
-    pub fn as_used(&self) -> Option<&ReservationToken<Used>> {
-        if let AnyReservationToken::Used(token) = self {
-            Some(token)
-        } else {
-            None
-        }
-    }
-
-    pub fn as_expired(&self) -> Option<&ReservationToken<Expired>> {
-        if let AnyReservationToken::Expired(token) = self {
-            Some(token)
-        } else {
-            None
-        }
+    /// Check if this token was pre-issued and is waiting for its reservation to be confirmed
+    pub fn is_inactive(&self) -> bool {
+        matches!(self, AnyReservationToken::Inactive(_))
     }
 
     /// Check if this token is in the active state
+    #[cfg_attr(not(test), expect(dead_code))]
     pub fn is_active(&self) -> bool {
         matches!(self, AnyReservationToken::Active(_))
     }
 
     /// Check if this token is in the used state
+    #[cfg_attr(not(test), expect(dead_code))]
     pub fn is_used(&self) -> bool {
         matches!(self, AnyReservationToken::Used(_))
     }
 
     /// Check if this token is in the expired state
+    #[cfg_attr(not(test), expect(dead_code))]
     pub fn is_expired(&self) -> bool {
         matches!(self, AnyReservationToken::Expired(_))
     }
+}
 
-    /// Get the state as a string representation
-    pub fn state_name(&self) -> &'static str {
-        match self {
-            AnyReservationToken::Active(_) => "Active",
-            AnyReservationToken::Used(_) => "Used",
-            AnyReservationToken::Expired(_) => "Expired",
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_generate_slug_from_name() {
+        let mut event = NewEvent {
+            name: "Summer Concert: Live!".to_string(),
+            description: None,
+            start_time: OffsetDateTime::now_utc(),
+            end_time: OffsetDateTime::now_utc(),
+            capacity: 10,
+            location: None,
+            max_reservations: None,
+            max_spots_per_reservation: None,
+            min_spots_per_reservation: None,
+            visibility: EventVisibility::Public,
+            reservations_open_at: None,
+            reservations_close_at: None,
+            skip_verification: false,
+            require_name_match: false,
+            token_rotation_minutes: None,
+            series_id: None,
+        };
+
+        let slug = event.generate_slug();
+        assert!(slug.starts_with("summer-concert-live-"), "{}", slug);
+        assert_eq!(slug.len(), "summer-concert-live-".len() + SLUG_SUFFIX_LENGTH);
+        assert_ne!(event.generate_slug(), slug);
+
+        event.name = "!!!".to_string();
+        assert!(event.generate_slug().starts_with("event-"));
+    }
+
+    #[test]
+    fn test_rotated_token_is_only_current_in_its_window() {
+        let now = OffsetDateTime::now_utc();
+        let rotated = RotatedToken::issue("r-abc", "secret", 5, now);
+
+        let parsed = RotatedToken::parse(&rotated.to_string()).unwrap();
+        assert_eq!(parsed, rotated);
+        assert!(parsed.is_current("secret", 5, now));
+        assert!(!parsed.is_current("other-secret", 5, now));
+        assert!(!parsed.is_current("secret", 5, now + time::Duration::minutes(5)));
+        assert!(RotatedToken::parse("r-abc").is_none());
+    }
+
+    #[test]
+    fn test_event_status_transitions() {
+        assert!(EventStatus::Open.can_transition_to(EventStatus::Cancelled));
+        assert!(EventStatus::Full.can_transition_to(EventStatus::Open));
+        assert!(EventStatus::Finished.can_transition_to(EventStatus::Open));
+        assert!(!EventStatus::Finished.can_transition_to(EventStatus::Cancelled));
+        assert!(!EventStatus::Cancelled.can_transition_to(EventStatus::Open));
+        assert!(EventStatus::Cancelled.can_transition_to(EventStatus::Cancelled));
+    }
+
+    #[test]
+    fn test_verification_token_new_with_len() {
+        let token = VerificationToken::new_with_len(24);
+        assert_eq!(token.0.len(), 24);
+        assert!(token.0.chars().all(|c| c.is_ascii_alphanumeric()));
+
+        // Requested lengths below the minimum are raised to it
+        let token = VerificationToken::new_with_len(4);
+        assert_eq!(token.0.len(), MIN_VERIFICATION_TOKEN_LENGTH);
+    }
+
+    #[test]
+    fn test_verification_tokens_are_unique() {
+        let tokens: HashSet<String> = (0..10_000)
+            .map(|_| VerificationToken::new_with_len(MIN_VERIFICATION_TOKEN_LENGTH).0)
+            .collect();
+        assert_eq!(tokens.len(), 10_000);
+    }
+
+    #[test]
+    fn test_verification_token_generate_defaults_to_uuid() {
+        let token = VerificationToken::generate(None);
+        assert!(Uuid::parse_str(&token.0).is_ok());
     }
 }
 
@@ -384,7 +1782,7 @@ fn example_usage() {
         token: "used456".to_string(),
         reservation_id: Uuid::new_v4(),
         created_at: OffsetDateTime::now_utc(),
-        status: Used,
+        status: Used { used_at: OffsetDateTime::now_utc() },
     };
 
     // Store them in the enum
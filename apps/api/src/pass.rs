@@ -0,0 +1,77 @@
+use std::io::{Cursor, Write};
+
+use serde_json::json;
+use sha1::{Digest, Sha1};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+use zip::write::SimpleFileOptions;
+
+/// Content type Wallet apps recognise for pass bundles
+pub const PKPASS_CONTENT_TYPE: &str = "application/vnd.apple.pkpass";
+
+/// What goes on an event ticket pass
+#[derive(Debug)]
+pub struct PassDetails<'a> {
+    pub pass_type_id: &'a str,
+    pub team_id: &'a str,
+    pub organization_name: &'a str,
+    /// Unique per pass; the reservation id
+    pub serial_number: String,
+    pub event_name: &'a str,
+    pub location: Option<&'a str>,
+    pub start_time: OffsetDateTime,
+    pub spot_count: u32,
+    /// Encoded in the QR code; the same reservation token the door scanner accepts
+    pub barcode_message: &'a str,
+}
+
+/// Build a `.pkpass` bundle: a zip of `pass.json` and a `manifest.json` of SHA-1 hashes.
+/// The bundle is unsigned; Apple Wallet only installs passes signed with the pass type's
+/// certificate, which has to be added to the `signature` entry by whoever holds it.
+pub fn build_pass(details: &PassDetails) -> zip::result::ZipResult<Vec<u8>> {
+    let start_time = details.start_time.format(&Rfc3339).expect("Event start time should format as RFC 3339");
+
+    let mut secondary_fields = vec![json!({ "key": "spots", "label": "SPOTS", "value": details.spot_count })];
+    if let Some(location) = details.location {
+        secondary_fields.push(json!({ "key": "location", "label": "LOCATION", "value": location }));
+    }
+
+    let pass = json!({
+        "formatVersion": 1,
+        "passTypeIdentifier": details.pass_type_id,
+        "teamIdentifier": details.team_id,
+        "organizationName": details.organization_name,
+        "serialNumber": details.serial_number,
+        "description": details.event_name,
+        "relevantDate": start_time,
+        "eventTicket": {
+            "primaryFields": [{ "key": "event", "label": "EVENT", "value": details.event_name }],
+            "secondaryFields": secondary_fields,
+            "auxiliaryFields": [{
+                "key": "starts",
+                "label": "STARTS",
+                "value": start_time,
+                "dateStyle": "PKDateStyleMedium",
+                "timeStyle": "PKDateStyleShort"
+            }]
+        },
+        "barcodes": [{
+            "format": "PKBarcodeFormatQR",
+            "message": details.barcode_message,
+            "messageEncoding": "iso-8859-1"
+        }]
+    })
+    .to_string();
+
+    let pass_digest: String = Sha1::digest(pass.as_bytes()).iter().map(|byte| format!("{:02x}", byte)).collect();
+    let manifest = json!({ "pass.json": pass_digest }).to_string();
+
+    let mut bundle = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+    bundle.start_file("pass.json", options)?;
+    bundle.write_all(pass.as_bytes())?;
+    bundle.start_file("manifest.json", options)?;
+    bundle.write_all(manifest.as_bytes())?;
+
+    Ok(bundle.finish()?.into_inner())
+}
@@ -0,0 +1,34 @@
+use qrcode::types::QrError;
+use qrcode::{Color, EcLevel, QrCode};
+
+/// Modules of blank margin scanners need around a QR code (ISO/IEC 18004 §6.3.8)
+pub const QUIET_ZONE_MODULES: usize = 4;
+
+/// The QR code for a single ticket, as a grid of dark and light modules
+#[derive(Debug, Clone)]
+pub struct TicketQr {
+    width: usize,
+    dark: Vec<bool>,
+}
+
+impl TicketQr {
+    /// Encode what the door scanner reads for a ticket: its reservation token. Medium error
+    /// correction keeps the code small while still scanning from a creased printout.
+    pub fn new(message: &str) -> Result<Self, QrError> {
+        let code = QrCode::with_error_correction_level(message.as_bytes(), EcLevel::M)?;
+        Ok(Self {
+            width: code.width(),
+            dark: code.to_colors().into_iter().map(|color| color == Color::Dark).collect(),
+        })
+    }
+
+    /// Modules per side, not counting the quiet zone
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Whether the module at column `x`, row `y` (from the top left) is dark
+    pub fn is_dark(&self, x: usize, y: usize) -> bool {
+        self.dark[y * self.width + x]
+    }
+}
@@ -0,0 +1,161 @@
+use std::io::Write;
+
+use crate::qr::{TicketQr, QUIET_ZONE_MODULES};
+
+/// Content type for the printable QR sheet
+pub const PDF_CONTENT_TYPE: &str = "application/pdf";
+
+/// US Letter, in PDF points (1/72 inch)
+const PAGE_WIDTH: f32 = 612.0;
+const PAGE_HEIGHT: f32 = 792.0;
+const MARGIN: f32 = 36.0;
+/// Space under the top margin for the sheet title
+const TITLE_HEIGHT: f32 = 30.0;
+const COLUMNS: usize = 3;
+const ROWS: usize = 4;
+/// Side of each printed QR code, quiet zone included
+const QR_SIZE: f32 = 120.0;
+const CAPTION_FONT_SIZE: f32 = 9.0;
+const CAPTION_LINE_HEIGHT: f32 = 11.0;
+/// Captions longer than this are cut short so they stay inside their cell
+const MAX_CAPTION_CHARS: usize = 32;
+
+/// One ticket on the sheet: its QR code and the lines printed under it (guest name, spot label, ...)
+#[derive(Debug)]
+pub struct SheetTicket {
+    pub qr: TicketQr,
+    pub caption: Vec<String>,
+}
+
+/// Lay tickets out on a grid of Letter pages, `COLUMNS` x `ROWS` to a page, each page headed by
+/// `title`. Only the QR modules and Helvetica text are drawn, so the PDF needs no embedded fonts or images.
+pub fn build_qr_sheet(title: &str, tickets: &[SheetTicket]) -> Vec<u8> {
+    let per_page = COLUMNS * ROWS;
+    let pages: Vec<Vec<u8>> = if tickets.is_empty() {
+        vec![page_content(title, &[])]
+    } else {
+        tickets.chunks(per_page).map(|page| page_content(title, page)).collect()
+    };
+
+    pdf_document(&pages)
+}
+
+/// Content stream drawing one page's title and tickets
+fn page_content(title: &str, tickets: &[SheetTicket]) -> Vec<u8> {
+    let mut content = Vec::new();
+    write_text(&mut content, MARGIN, PAGE_HEIGHT - MARGIN - 14.0, 14.0, title);
+
+    let cell_width = (PAGE_WIDTH - 2.0 * MARGIN) / COLUMNS as f32;
+    let cell_height = (PAGE_HEIGHT - 2.0 * MARGIN - TITLE_HEIGHT) / ROWS as f32;
+
+    for (index, ticket) in tickets.iter().enumerate() {
+        let (column, row) = (index % COLUMNS, index / COLUMNS);
+        let left = MARGIN + column as f32 * cell_width + (cell_width - QR_SIZE) / 2.0;
+        let top = PAGE_HEIGHT - MARGIN - TITLE_HEIGHT - row as f32 * cell_height;
+
+        write_qr(&mut content, &ticket.qr, left, top - QR_SIZE);
+
+        let mut baseline = top - QR_SIZE - CAPTION_LINE_HEIGHT;
+        for line in &ticket.caption {
+            write_text(&mut content, left, baseline, CAPTION_FONT_SIZE, &shorten(line));
+            baseline -= CAPTION_LINE_HEIGHT;
+        }
+    }
+
+    content
+}
+
+/// Fill a rectangle for every dark module, with the quiet zone left blank around them
+fn write_qr(content: &mut Vec<u8>, qr: &TicketQr, left: f32, bottom: f32) {
+    let modules = qr.width() + 2 * QUIET_ZONE_MODULES;
+    let module_size = QR_SIZE / modules as f32;
+    let origin_x = left + QUIET_ZONE_MODULES as f32 * module_size;
+    let origin_y = bottom + QR_SIZE - QUIET_ZONE_MODULES as f32 * module_size;
+
+    for y in 0..qr.width() {
+        for x in 0..qr.width() {
+            if qr.is_dark(x, y) {
+                let module_x = origin_x + x as f32 * module_size;
+                let module_y = origin_y - (y + 1) as f32 * module_size;
+                let _ = writeln!(content, "{:.2} {:.2} {:.2} {:.2} re", module_x, module_y, module_size, module_size);
+            }
+        }
+    }
+    content.extend_from_slice(b"f\n");
+}
+
+fn write_text(content: &mut Vec<u8>, x: f32, y: f32, size: f32, text: &str) {
+    let _ = write!(content, "BT /F1 {:.1} Tf {:.2} {:.2} Td (", size, x, y);
+    content.extend(pdf_string(text));
+    content.extend_from_slice(b") Tj ET\n");
+}
+
+/// Cut a caption down to `MAX_CAPTION_CHARS`, marking the cut with an ellipsis
+fn shorten(text: &str) -> String {
+    if text.chars().count() <= MAX_CAPTION_CHARS {
+        return text.to_string();
+    }
+    text.chars().take(MAX_CAPTION_CHARS - 3).chain("...".chars()).collect()
+}
+
+/// Bytes for a PDF literal string in WinAnsi encoding: Latin-1 characters map directly,
+/// anything else (and control characters) becomes `?`
+fn pdf_string(text: &str) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '(' | ')' | '\\' => {
+                bytes.push(b'\\');
+                bytes.push(ch as u8);
+            }
+            ' '..='~' | '\u{a0}'..='\u{ff}' => bytes.push(ch as u32 as u8),
+            _ => bytes.push(b'?'),
+        }
+    }
+
+    bytes
+}
+
+/// Assemble the catalog, page tree, font and pages into a PDF with a cross-reference table
+fn pdf_document(pages: &[Vec<u8>]) -> Vec<u8> {
+    // Objects 1-3 are the catalog, page tree and font; each page then takes a page object and its content stream
+    let page_ids: Vec<usize> = (0..pages.len()).map(|index| 4 + 2 * index).collect();
+    let kids: Vec<String> = page_ids.iter().map(|id| format!("{} 0 R", id)).collect();
+
+    let mut objects: Vec<Vec<u8>> = vec![
+        b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+        format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.join(" "), pages.len()).into_bytes(),
+        b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>".to_vec(),
+    ];
+    for (page_id, content) in page_ids.iter().zip(pages) {
+        objects.push(
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+                PAGE_WIDTH, PAGE_HEIGHT, page_id + 1
+            )
+            .into_bytes(),
+        );
+        let mut stream = format!("<< /Length {} >>\nstream\n", content.len()).into_bytes();
+        stream.extend_from_slice(content);
+        stream.extend_from_slice(b"\nendstream");
+        objects.push(stream);
+    }
+
+    let mut pdf = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (index, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend(format!("{} 0 obj\n", index + 1).into_bytes());
+        pdf.extend_from_slice(object);
+        pdf.extend_from_slice(b"\nendobj\n");
+    }
+
+    let xref_offset = pdf.len();
+    pdf.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).into_bytes());
+    for offset in offsets {
+        pdf.extend(format!("{:010} 00000 n \n", offset).into_bytes());
+    }
+    pdf.extend(format!("trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n", objects.len() + 1, xref_offset).into_bytes());
+
+    pdf
+}
@@ -0,0 +1,79 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::http::HeaderMap;
+
+/// In-memory sliding-window limiter keyed by client, for endpoints that could be used to enumerate data
+#[derive(Clone, Debug)]
+pub struct RateLimiter {
+    limit: u32,
+    window: Duration,
+    hits: Arc<Mutex<HashMap<String, VecDeque<Instant>>>>,
+}
+
+impl RateLimiter {
+    pub fn new(limit: u32, window: Duration) -> Self {
+        Self {
+            limit,
+            window,
+            hits: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn per_minute(limit: u32) -> Self {
+        Self::new(limit, Duration::from_secs(60))
+    }
+
+    /// Record a request from `key`, returning whether it is within the limit
+    pub fn check(&self, key: &str) -> bool {
+        self.check_at(key, Instant::now())
+    }
+
+    fn check_at(&self, key: &str, now: Instant) -> bool {
+        let mut hits = self.hits.lock().expect("Rate limiter lock poisoned");
+
+        // Forget clients whose requests have all aged out so the map doesn't grow without bound
+        hits.retain(|_, times| times.back().is_some_and(|last| now.duration_since(*last) < self.window));
+
+        let times = hits.entry(key.to_string()).or_default();
+        while times.front().is_some_and(|first| now.duration_since(*first) >= self.window) {
+            times.pop_front();
+        }
+
+        if times.len() >= self.limit as usize {
+            return false;
+        }
+        times.push_back(now);
+        true
+    }
+}
+
+/// Best guess at the client's address: the first `X-Forwarded-For` hop set by the proxy in front of us
+pub fn client_key(headers: &HeaderMap) -> String {
+    headers
+        .get("x-forwarded-for")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(',').next())
+        .map(|ip| ip.trim().to_string())
+        .filter(|ip| !ip.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limit_applies_per_key_and_window() {
+        let limiter = RateLimiter::new(2, Duration::from_secs(60));
+        let start = Instant::now();
+
+        assert!(limiter.check_at("a", start));
+        assert!(limiter.check_at("a", start));
+        assert!(!limiter.check_at("a", start));
+        assert!(limiter.check_at("b", start));
+
+        assert!(limiter.check_at("a", start + Duration::from_secs(61)));
+    }
+}